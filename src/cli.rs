@@ -0,0 +1,235 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+
+use crate::rng::native::{NATIVE_GENERATORS, NativeGenerator};
+use crate::rng::sampler::Sampler;
+use crate::rng::RNG_EXAMPLES;
+
+const DEFAULT_COUNT: u64 = 1_000_000;
+const DEFAULT_BITS: u32 = 32;
+const DEFAULT_SEED: i64 = 12345;
+
+const USAGE: &str = "\
+Usage: prng3d sample [options]
+
+Generate outputs without opening a window.
+
+Options:
+  --example NAME     use a built-in generator (see --list)
+  --code FILE        read Aelys code from FILE, or stdin with -
+  --native NAME      use a native reference generator (see --list)
+  --seed N           seed (default 12345)
+  -n, --count N      number of outputs, 0 for an endless stream (default 1000000)
+  --bits B           output width, defaults to the example's or 32
+  --format FMT       bin (little-endian), text or csv (default bin)
+  -o, --output FILE  write to FILE instead of stdout
+  --list             list built-in generators";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Binary,
+    Text,
+    Csv,
+}
+
+enum SampleSource {
+    Code(String),
+    Native(&'static NativeGenerator),
+}
+
+struct SampleArgs {
+    source: SampleSource,
+    seed: i64,
+    count: u64,
+    bits: u32,
+    format: Format,
+    output: Option<String>,
+}
+
+/// Runs the CLI when the first argument is a subcommand. Returns the exit code,
+/// or `None` when the GUI should start instead.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let result = match command.as_str() {
+        "sample" => parse_sample(rest).and_then(|parsed| match parsed {
+            Some(args) => sample(&args),
+            None => Ok(()),
+        }),
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => return None,
+    };
+
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            Some(1)
+        }
+    }
+}
+
+fn example_key(name: &str) -> String {
+    let base = name.split(" (").next().unwrap_or(name);
+    base.to_lowercase().replace(' ', "-")
+}
+
+fn parse_sample(args: &[String]) -> Result<Option<SampleArgs>, String> {
+    let mut source = None;
+    let mut source_bits = None;
+    let mut seed = DEFAULT_SEED;
+    let mut count = DEFAULT_COUNT;
+    let mut bits = None;
+    let mut format = Format::Binary;
+    let mut output = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--example" => {
+                let name = value()?;
+                let key = example_key(&name);
+                let ex = RNG_EXAMPLES
+                    .iter()
+                    .find(|ex| example_key(ex.name) == key)
+                    .ok_or_else(|| format!("unknown example '{}', try --list", name))?;
+                source = Some(SampleSource::Code(ex.code.to_string()));
+                source_bits = Some(ex.bits);
+            }
+            "--native" => {
+                let name = value()?;
+                let key = example_key(&name);
+                let generator = NATIVE_GENERATORS
+                    .iter()
+                    .find(|g| example_key(g.name) == key)
+                    .ok_or_else(|| format!("unknown native generator '{}', try --list", name))?;
+                source = Some(SampleSource::Native(generator));
+                source_bits = Some(generator.bits);
+            }
+            "--code" => {
+                let path = value()?;
+                let text = if path == "-" {
+                    let mut text = String::new();
+                    io::stdin()
+                        .read_to_string(&mut text)
+                        .map_err(|e| format!("failed to read stdin: {}", e))?;
+                    text
+                } else {
+                    std::fs::read_to_string(&path)
+                        .map_err(|e| format!("failed to read {}: {}", path, e))?
+                };
+                source = Some(SampleSource::Code(text));
+                source_bits = None;
+            }
+            "--seed" => {
+                seed = value()?
+                    .parse()
+                    .map_err(|e| format!("invalid seed: {}", e))?;
+            }
+            "-n" | "--count" => {
+                count = value()?
+                    .parse()
+                    .map_err(|e| format!("invalid count: {}", e))?;
+            }
+            "--bits" => {
+                let b: u32 = value()?
+                    .parse()
+                    .map_err(|e| format!("invalid bit width: {}", e))?;
+                if !(1..=64).contains(&b) {
+                    return Err(format!("bit width must be between 1 and 64, got {}", b));
+                }
+                bits = Some(b);
+            }
+            "--format" => {
+                format = match value()?.as_str() {
+                    "bin" => Format::Binary,
+                    "text" => Format::Text,
+                    "csv" => Format::Csv,
+                    other => return Err(format!("unknown format '{}', expected bin, text or csv", other)),
+                };
+            }
+            "-o" | "--output" => output = Some(value()?),
+            "--list" => {
+                println!("Examples (--example):");
+                for ex in RNG_EXAMPLES {
+                    println!("  {:<24} {:>2} bits  {}", example_key(ex.name), ex.bits, ex.description);
+                }
+                println!("Native generators (--native):");
+                for g in NATIVE_GENERATORS {
+                    println!("  {:<24} {:>2} bits  {}", example_key(g.name), g.bits, g.description);
+                }
+                return Ok(None);
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(None);
+            }
+            other => return Err(format!("unknown option '{}'\n\n{}", other, USAGE)),
+        }
+    }
+
+    let source = source
+        .ok_or_else(|| format!("one of --example, --native or --code is required\n\n{}", USAGE))?;
+    Ok(Some(SampleArgs {
+        source,
+        seed,
+        count,
+        bits: bits.or(source_bits).unwrap_or(DEFAULT_BITS),
+        format,
+        output,
+    }))
+}
+
+fn sample(args: &SampleArgs) -> Result<(), String> {
+    let mut sampler = match &args.source {
+        SampleSource::Code(code) => Sampler::aelys(code, args.seed)?,
+        SampleSource::Native(generator) => Sampler::native(generator, args.seed),
+    };
+
+    let sink: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(
+            File::create(path).map_err(|e| format!("failed to create {}: {}", path, e))?,
+        ),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut out = BufWriter::new(sink);
+
+    let mask = if args.bits >= 64 { u64::MAX } else { (1u64 << args.bits) - 1 };
+    let bytes = args.bits.div_ceil(8).next_power_of_two() as usize;
+
+    if args.format == Format::Csv {
+        write_out(writeln!(out, "index,value"))?;
+    }
+
+    let mut i = 0u64;
+    while args.count == 0 || i < args.count {
+        let value = sampler.next_value()? as u64 & mask;
+        let written = match args.format {
+            Format::Binary => out.write_all(&value.to_le_bytes()[..bytes]),
+            Format::Text => writeln!(out, "{}", value),
+            Format::Csv => writeln!(out, "{},{}", i, value),
+        };
+        if !write_out(written)? {
+            return Ok(());
+        }
+        i += 1;
+    }
+
+    write_out(out.flush()).map(|_| ())
+}
+
+/// A closed pipe (e.g. `| head`) ends the stream quietly; other IO errors are reported.
+fn write_out(result: io::Result<()>) -> Result<bool, String> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+        Err(e) => Err(format!("write failed: {}", e)),
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant};
@@ -13,17 +14,41 @@ use winit::{
 
 use glam::Vec2;
 
+mod cli;
 mod math;
 mod renderer;
 mod rng;
 mod ui;
 
 use math::examples::MathFunctionKind;
+use math::fractal::FractalView;
+use math::vector_field::StreamlineSeeds;
 use math::{MathEngine, MathResult};
-use renderer::{Camera, GpuState, generate_grid_vertices};
-use rng::RngEngine;
-use ui::state::{AppMode, MathViewMode, ViewMode};
-use ui::{UiActions, UiState, apply_theme, draw_help_overlay, draw_side_panel};
+use renderer::histogram::HistogramPipelines;
+use renderer::point_cloud::{KeyScale, KeyedPoint, PointCloudBuffers};
+use renderer::{
+    Camera, GpuState, generate_cell_vertices, generate_grid_vertices, generate_marker_vertices,
+    generate_plane_vertices, pick_point, project_point,
+};
+use rng::spectral::{self, Lcg, format_param, parse_param};
+use rng::{
+    BitPlot, Embedding, HistoryEntry, MappingMode, NATIVE_GENERATORS, PointOrigin, RNG_EXAMPLES,
+    Reference, RngEngine, SeedSweeper, Verifier,
+};
+use ui::state::{AppMode, MathViewMode, PointColor, VerifyAgainst, ViewMode};
+use ui::{
+    EngineReadout, UiActions, UiState, apply_theme, draw_compare_overlay, draw_help_overlay,
+    draw_pick_tooltip, draw_side_panel,
+};
+
+/// Denser plane families are reported but not drawn.
+const MAX_OVERLAY_PLANES: usize = 1024;
+/// Half-length of the debugger marker, relative to the largest bound.
+const MARKER_SCALE: f32 = 0.03;
+/// How far from a click, in pixels, a point can be and still get picked.
+const PICK_RADIUS: f32 = 8.0;
+/// Most 3D points kept per generator.
+const MAX_3D_POINTS: usize = 4_000_000;
 
 struct InputState {
     forward: f32,
@@ -31,6 +56,10 @@ struct InputState {
     up: f32,
     mouse_captured: bool,
     mouse_delta: Vec2,
+    /// Physical pixels.
+    cursor: [f32; 2],
+    /// Left button held down over the fractal view.
+    panning: bool,
 }
 
 impl Default for InputState {
@@ -41,6 +70,8 @@ impl Default for InputState {
             up: 0.0,
             mouse_captured: false,
             mouse_delta: Vec2::ZERO,
+            cursor: [0.0, 0.0],
+            panning: false,
         }
     }
 }
@@ -50,6 +81,11 @@ enum CurrentMathMesh {
     Surface,
     Curve,
     ParametricSurface,
+    ImplicitSurface,
+    Trajectory,
+    VectorField,
+    DomainColoring,
+    Fractal,
 }
 
 fn surface_to_heatmap(vertices: &[f32], _z_min: f32, _z_max: f32) -> Vec<f32> {
@@ -108,6 +144,275 @@ fn curve_to_2d(vertices: &[f32]) -> Vec<f32> {
         .collect()
 }
 
+/// Spectral test hyperplanes in world space, when they line up with how points are plotted.
+fn plane_overlay(state: &UiState) -> Vec<f32> {
+    let Some(report) = state.spectral_report.as_ref().filter(|_| state.show_planes) else {
+        return Vec::new();
+    };
+    if state.mapping.mode != MappingMode::Scale {
+        return Vec::new();
+    }
+    let lag = match state.embedding {
+        Embedding::Triples => 1,
+        Embedding::Lagged => state.embedding_lag as usize,
+        Embedding::Differences => return Vec::new(),
+    };
+    let Some(planes) = spectral::hyperplanes_3d(&report.lcg, lag, MAX_OVERLAY_PLANES) else {
+        return Vec::new();
+    };
+
+    // Points are scaled by 2^bits while the lattice is in units of m.
+    let scale = report.lcg.m as f64 / (state.mapping.output_bits as f64).exp2();
+    let levels: Vec<f64> = planes.levels.iter().map(|level| level * scale).collect();
+    generate_plane_vertices(planes.normal, &levels, state.bounds_min, state.bounds_max)
+}
+
+/// Cross on the point completed by the call selected in the debugger, or the first
+/// point after it.
+fn call_marker(state: &UiState, history: &[HistoryEntry]) -> Vec<f32> {
+    let Some(selected) = state.selected_call else {
+        return Vec::new();
+    };
+    let Some(point) = history.iter().filter(|e| e.index >= selected).find_map(|e| e.point) else {
+        return Vec::new();
+    };
+    generate_marker_vertices(point, marker_size(state))
+}
+
+fn marker_size(state: &UiState) -> f32 {
+    let extent = (0..3)
+        .map(|axis| state.bounds_max[axis] - state.bounds_min[axis])
+        .fold(0.0, f32::max);
+    extent * MARKER_SCALE
+}
+
+/// Points received from one generator, kept separately for the 3D and 2D views.
+#[derive(Default)]
+struct PointAccumulator {
+    /// Position and colour key of each 3D point, laid out as uploaded.
+    points_3d: Vec<KeyedPoint>,
+    /// One per 3D point, for picking.
+    origins_3d: Vec<PointOrigin>,
+    /// Batch each 3D point arrived in, counted from the last clear.
+    batches_3d: Vec<u32>,
+    batches_received: u32,
+    /// 3D points received since the last clear; the window keeps the newest.
+    received_3d: u64,
+    /// Points at the end of `points_3d` not uploaded yet.
+    fresh_3d: usize,
+    /// Point count and call index range of each batch in the window, oldest first. A
+    /// batch partly out of the window keeps its whole range.
+    batch_indices: VecDeque<(usize, u64, u64)>,
+    /// The GPU buffer is a ring of `ring_capacity` points, overwritten from `ring_head`.
+    ring_capacity: usize,
+    ring_head: usize,
+    /// Key carried by the points on the GPU; `None` forces a full upload.
+    uploaded_color: Option<PointColor>,
+    /// x/y pairs not yet binned into the 2D histogram.
+    pending_2d: Vec<f32>,
+    /// The 2D histogram is cleared on the next upload.
+    clear_2d: bool,
+    /// Latest seed of each stream.
+    seeds: Vec<Option<i64>>,
+}
+
+impl PointAccumulator {
+    fn with_capacity(points_3d: usize) -> Self {
+        Self {
+            points_3d: Vec::with_capacity(points_3d),
+            origins_3d: Vec::with_capacity(points_3d),
+            batches_3d: Vec::with_capacity(points_3d),
+            ..Self::default()
+        }
+    }
+
+    fn clear(&mut self) {
+        self.points_3d.clear();
+        self.origins_3d.clear();
+        self.batches_3d.clear();
+        self.batches_received = 0;
+        self.received_3d = 0;
+        self.fresh_3d = 0;
+        self.batch_indices.clear();
+        self.uploaded_color = None;
+        self.pending_2d.clear();
+        self.clear_2d = true;
+    }
+
+    fn drain_engine(&mut self, engine: &RngEngine, state: &UiState) {
+        while let Some(batch) = engine.try_recv_batch() {
+            if self.seeds.len() <= batch.stream {
+                self.seeds.resize(batch.stream + 1, None);
+            }
+            self.seeds[batch.stream] = batch.seed;
+
+            match state.view_mode {
+                ViewMode::Mode3D => {
+                    let max_points = state.max_points.min(MAX_3D_POINTS);
+                    let overflow = (self.origins_3d.len() + batch.len()).saturating_sub(max_points);
+                    if overflow < self.origins_3d.len() {
+                        self.points_3d.drain(0..overflow);
+                        self.origins_3d.drain(0..overflow);
+                        self.batches_3d.drain(0..overflow);
+                        let mut dropped = overflow;
+                        while dropped > 0 {
+                            let Some(front) = self.batch_indices.front_mut() else {
+                                break;
+                            };
+                            if front.0 > dropped {
+                                front.0 -= dropped;
+                                break;
+                            }
+                            dropped -= front.0;
+                            self.batch_indices.pop_front();
+                        }
+                    } else {
+                        self.points_3d.clear();
+                        self.origins_3d.clear();
+                        self.batches_3d.clear();
+                        self.batch_indices.clear();
+                    }
+                    if !batch.origins.is_empty() {
+                        let (min, max) = batch
+                            .origins
+                            .iter()
+                            .fold((u64::MAX, 0), |(min, max), o| (min.min(o.index), max.max(o.index)));
+                        self.batch_indices.push_back((batch.len(), min, max));
+                    }
+                    self.points_3d.extend(batch.points.chunks_exact(3).map(|p| KeyedPoint {
+                        position: [p[0], p[1], p[2]],
+                        key: 0,
+                    }));
+                    self.origins_3d.extend(&batch.origins);
+                    self.batches_3d.extend(std::iter::repeat_n(self.batches_received, batch.len()));
+                    self.batches_received = self.batches_received.wrapping_add(1);
+                    self.received_3d += batch.len() as u64;
+                    self.fresh_3d += batch.len();
+                }
+                ViewMode::Mode2D => {
+                    self.pending_2d
+                        .extend(batch.points.chunks_exact(3).flat_map(|p| [p[0], p[1]]));
+                }
+                ViewMode::Bits => {}
+            }
+        }
+    }
+
+    /// Colormap key of the `i`-th 3D point in the window.
+    fn color_key(&self, i: usize, color: PointColor) -> u64 {
+        match color {
+            PointColor::Position => 0,
+            PointColor::Age => self.received_3d - self.origins_3d.len() as u64 + i as u64,
+            PointColor::Index => self.origins_3d[i].index,
+            PointColor::Batch => self.batches_3d[i] as u64,
+            PointColor::Stream => self.origins_3d[i].stream as u64,
+        }
+    }
+
+    /// How the shader spreads the keys of the current window over the colormap.
+    fn key_scale(&self, state: &UiState) -> KeyScale {
+        let color = state.point_color;
+        let period = match color {
+            PointColor::Position | PointColor::Stream => 0,
+            _ => state.color_period.min(u32::MAX as u64),
+        };
+        let len = self.origins_3d.len();
+        let (min, max) = match color {
+            _ if len == 0 => (0, 0),
+            PointColor::Position => (0, 0),
+            PointColor::Age => (self.received_3d - len as u64, self.received_3d - 1),
+            PointColor::Index => self
+                .batch_indices
+                .iter()
+                .fold((u64::MAX, 0), |(min, max), &(_, lo, hi)| (min.min(lo), max.max(hi))),
+            PointColor::Batch => (self.batches_3d[0] as u64, self.batches_3d[len - 1] as u64),
+            PointColor::Stream => (0, self.seeds.len().saturating_sub(1) as u64),
+        };
+        // Keys wrap at 32 bits; a base on a multiple of the period keeps their residues.
+        let base = if period > 0 { min - min % period } else { min };
+        KeyScale {
+            base: base as u32,
+            span: max.wrapping_sub(min) as u32,
+            period: period as u32,
+        }
+    }
+
+    fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pipelines: &HistogramPipelines,
+        buffers: &mut PointCloudBuffers,
+        state: &UiState,
+    ) -> usize {
+        match state.view_mode {
+            ViewMode::Mode3D => {
+                let color = state.point_color;
+                let capacity = state.max_points.clamp(1, MAX_3D_POINTS);
+                let count = self.origins_3d.len();
+                let fresh = std::mem::take(&mut self.fresh_3d).min(count);
+                let rebuild = self.uploaded_color != Some(color)
+                    || self.ring_capacity != capacity
+                    || count > capacity
+                    || fresh == count;
+
+                let first = if rebuild { 0 } else { count - fresh };
+                for i in first..count {
+                    self.points_3d[i].key = self.color_key(i, color) as u32;
+                }
+
+                if rebuild {
+                    buffers.write_3d(queue, 0, &self.points_3d);
+                    self.ring_capacity = capacity;
+                    self.ring_head = count % capacity;
+                    self.uploaded_color = Some(color);
+                } else {
+                    // The ring drops the oldest points, exactly as the window just did.
+                    let new = &self.points_3d[first..];
+                    let split = (capacity - self.ring_head).min(fresh);
+                    buffers.write_3d(queue, self.ring_head, &new[..split]);
+                    buffers.write_3d(queue, 0, &new[split..]);
+                    self.ring_head = (self.ring_head + fresh) % capacity;
+                }
+                buffers.set_points_count_3d(count);
+                let colormap = (color != PointColor::Position).then_some(state.colormap);
+                buffers.set_style(queue, colormap, self.key_scale(state));
+                count
+            }
+            ViewMode::Mode2D => {
+                let histogram = &mut buffers.histogram;
+                if std::mem::take(&mut self.clear_2d) {
+                    histogram.clear(device, queue);
+                }
+                let bounds = [state.bounds_min[0], state.bounds_min[1], state.bounds_max[0], state.bounds_max[1]];
+                histogram.add_points(device, queue, pipelines, &self.pending_2d, state.grid_size, bounds);
+                self.pending_2d.clear();
+                histogram.samples() as usize
+            }
+            ViewMode::Bits => 0,
+        }
+    }
+}
+
+/// A point picked in the 3D view.
+struct PickedPoint {
+    /// 0 for A, 1 for B.
+    slot: usize,
+    position: [f32; 3],
+    origin: PointOrigin,
+    seed: Option<i64>,
+}
+
+/// The two side-by-side viewports `[x, y, width, height]` of the compare view.
+fn split_viewports(rect: egui::Rect) -> [[f32; 4]; 2] {
+    let half = (rect.width() / 2.0).max(1.0);
+    let height = rect.height().max(1.0);
+    [
+        [rect.left(), rect.top(), half, height],
+        [rect.left() + half, rect.top(), half, height],
+    ]
+}
+
 struct App {
     window: Option<Arc<Window>>,
     gpu: Option<GpuState>,
@@ -117,6 +422,9 @@ struct App {
 
     camera: Camera,
     rng_engine: RngEngine,
+    rng_engine_b: Option<RngEngine>,
+    verifier: Verifier,
+    sweeper: SeedSweeper,
     math_engine: MathEngine,
     ui_state: UiState,
     input: InputState,
@@ -125,8 +433,12 @@ struct App {
     frame_count: u32,
     fps_timer: Instant,
 
-    accumulated_points_3d: Vec<f32>,
-    accumulated_points_2d: Vec<f32>,
+    points: PointAccumulator,
+    points_b: PointAccumulator,
+    /// Pixel rect left of the side panel, split between A and B while comparing.
+    compare_rect: Option<egui::Rect>,
+    bit_view_enabled: bool,
+    picked: Option<PickedPoint>,
 
     last_vsync_state: bool,
     last_frame_time: Instant,
@@ -153,6 +465,9 @@ impl App {
 
             camera: Camera::default(),
             rng_engine: RngEngine::new(),
+            rng_engine_b: None,
+            verifier: Verifier::default(),
+            sweeper: SeedSweeper::default(),
             math_engine: MathEngine::new(),
             ui_state: UiState::default(),
             input: InputState::default(),
@@ -161,8 +476,11 @@ impl App {
             frame_count: 0,
             fps_timer: Instant::now(),
 
-            accumulated_points_3d: Vec::with_capacity(4_000_000 * 3),
-            accumulated_points_2d: Vec::with_capacity(1_000_000 * 3),
+            points: PointAccumulator::with_capacity(MAX_3D_POINTS),
+            points_b: PointAccumulator::default(),
+            compare_rect: None,
+            bit_view_enabled: false,
+            picked: None,
 
             last_vsync_state: false,
             last_frame_time: Instant::now(),
@@ -202,6 +520,7 @@ impl App {
         self.egui_renderer = Some(egui_renderer);
 
         if self.ui_state.code_needs_compile {
+            self.rng_engine.set_mapping(self.ui_state.mapping);
             self.rng_engine.update_code(&self.ui_state.code);
             self.ui_state.code_needs_compile = false;
         }
@@ -252,6 +571,72 @@ impl App {
                     self.ui_state.math_v_samples as usize,
                 );
             }
+            MathFunctionKind::Implicit => {
+                let range = |r: (f32, f32)| (r.0 as f64, r.1 as f64);
+                self.math_engine.compile_implicit(
+                    &self.ui_state.math_code,
+                    [
+                        range(self.ui_state.math_x_range),
+                        range(self.ui_state.math_y_range),
+                        range(self.ui_state.math_z_range),
+                    ],
+                    self.ui_state.math_implicit_resolution as usize,
+                    self.ui_state.math_iso_level as f64,
+                );
+            }
+            MathFunctionKind::Ode => {
+                self.math_engine.integrate_ode(
+                    &self.ui_state.math_code,
+                    self.ui_state.math_initial.map(|v| v as f64),
+                    (
+                        self.ui_state.math_t_range.0 as f64,
+                        self.ui_state.math_t_range.1 as f64,
+                    ),
+                    self.ui_state.math_ode_step as f64,
+                    self.ui_state.math_ode_method,
+                );
+            }
+            MathFunctionKind::Complex => {
+                self.math_engine.compile_complex(
+                    &self.ui_state.math_code,
+                    (
+                        self.ui_state.math_x_range.0 as f64,
+                        self.ui_state.math_x_range.1 as f64,
+                    ),
+                    (
+                        self.ui_state.math_y_range.0 as f64,
+                        self.ui_state.math_y_range.1 as f64,
+                    ),
+                    self.ui_state.math_resolution as usize,
+                );
+            }
+            MathFunctionKind::VectorField => {
+                let range = |r: (f32, f32)| (r.0 as f64, r.1 as f64);
+                let seeds = self.ui_state.math_streamlines.then_some(StreamlineSeeds {
+                    axis: self.ui_state.math_stream_axis,
+                    offset: self.ui_state.math_stream_offset as f64,
+                    count: self.ui_state.math_stream_seeds as usize,
+                });
+                self.math_engine.compile_vector_field(
+                    &self.ui_state.math_code,
+                    [
+                        range(self.ui_state.math_x_range),
+                        range(self.ui_state.math_y_range),
+                        range(self.ui_state.math_z_range),
+                    ],
+                    self.ui_state.math_field_resolution as usize,
+                    seeds,
+                );
+            }
+            MathFunctionKind::Fractal => {
+                let julia = self.ui_state.fractal_julia.then_some(self.ui_state.fractal_c);
+                self.math_engine.compile_fractal(
+                    &self.ui_state.math_code,
+                    self.fractal_view(),
+                    self.ui_state.fractal_max_iterations,
+                    julia,
+                );
+            }
         }
         self.ui_state.math_needs_compile = false;
     }
@@ -285,80 +670,86 @@ impl App {
     }
 
     fn update_rng(&mut self) {
-        self.rng_engine.bounds().set(
-            self.ui_state.bounds_min[0] as i64,
-            self.ui_state.bounds_max[0] as i64,
-            self.ui_state.bounds_min[1] as i64,
-            self.ui_state.bounds_max[1] as i64,
-            self.ui_state.bounds_min[2] as i64,
-            self.ui_state.bounds_max[2] as i64,
-        );
+        let engines = std::iter::once(&self.rng_engine).chain(self.rng_engine_b.as_ref());
+        for engine in engines {
+            engine.bounds().set(
+                self.ui_state.bounds_min[0] as i64,
+                self.ui_state.bounds_max[0] as i64,
+                self.ui_state.bounds_min[1] as i64,
+                self.ui_state.bounds_max[1] as i64,
+                self.ui_state.bounds_min[2] as i64,
+                self.ui_state.bounds_max[2] as i64,
+            );
+        }
 
-        while let Some(batch) = self.rng_engine.try_recv_batch() {
-            let max_floats = self.ui_state.max_points.min(4_000_000) * 3;
+        let bit_view = self.ui_state.view_mode == ViewMode::Bits;
+        if bit_view != self.bit_view_enabled {
+            self.for_each_engine(|e| e.set_bit_view(bit_view));
+            self.bit_view_enabled = bit_view;
+        }
 
-            match self.ui_state.view_mode {
-                ViewMode::Mode3D => {
-                    if self.accumulated_points_3d.len() + batch.len() > max_floats {
-                        let overflow =
-                            (self.accumulated_points_3d.len() + batch.len()) - max_floats;
-                        if overflow < self.accumulated_points_3d.len() {
-                            self.accumulated_points_3d.drain(0..overflow);
-                        } else {
-                            self.accumulated_points_3d.clear();
-                        }
-                    }
-                    self.accumulated_points_3d.extend(&batch);
-                }
-                ViewMode::Mode2D => {
-                    let grid = self.ui_state.grid_size as usize;
-                    let max_2d = grid * grid * 3;
-
-                    if self.accumulated_points_2d.len() + batch.len() > max_2d {
-                        let overflow = (self.accumulated_points_2d.len() + batch.len()) - max_2d;
-                        if overflow < self.accumulated_points_2d.len() {
-                            self.accumulated_points_2d.drain(0..overflow);
-                        } else {
-                            self.accumulated_points_2d.clear();
-                        }
-                    }
-                    self.accumulated_points_2d.extend(&batch);
-                }
-            }
+        self.points.drain_engine(&self.rng_engine, &self.ui_state);
+        if let Some(engine_b) = &self.rng_engine_b {
+            self.points_b.drain_engine(engine_b, &self.ui_state);
         }
 
         if let Some(gpu) = &mut self.gpu {
-            match self.ui_state.view_mode {
-                ViewMode::Mode3D => {
-                    gpu.point_buffers
-                        .upload_3d(&gpu.queue, &self.accumulated_points_3d);
-                    self.rng_engine
-                        .stats()
-                        .points_rendered
-                        .store(self.accumulated_points_3d.len() / 3, Ordering::Relaxed);
+            if bit_view {
+                let engines = std::iter::once(&self.rng_engine).chain(self.rng_engine_b.as_ref());
+                for (slot, engine) in engines.enumerate() {
+                    let (cols, rows, values) = match self.ui_state.bit_plot {
+                        BitPlot::Avalanche => {
+                            engine.avalanche().matrix().map(|m| m.grid()).unwrap_or_default()
+                        }
+                        plot => engine.bit_stats().grid(plot, self.ui_state.bit_lag),
+                    };
+                    gpu.upload_bit_plot(slot, &generate_cell_vertices(cols, rows, &values));
                 }
-                ViewMode::Mode2D => {
-                    let points_2d: Vec<f32> = self
-                        .accumulated_points_2d
-                        .chunks(3)
-                        .flat_map(|chunk| {
-                            if chunk.len() >= 3 {
-                                let x = (chunk[0] / 500.0) * 0.9;
-                                let y = (chunk[1] / 500.0) * 0.9;
-                                let v = (chunk[2] + 500.0) / 1000.0;
-                                vec![x, y, v]
-                            } else {
-                                vec![]
-                            }
-                        })
-                        .collect();
+                return;
+            }
 
-                    gpu.point_buffers.upload_2d(&gpu.queue, &points_2d);
-                    self.rng_engine
-                        .stats()
-                        .points_rendered
-                        .store(points_2d.len() / 3, Ordering::Relaxed);
+            let overlay = match self.ui_state.view_mode {
+                ViewMode::Mode3D => {
+                    let mut overlay = call_marker(&self.ui_state, &self.rng_engine.history());
+                    // The overlay is only drawn over A.
+                    if let Some(picked) = self.picked.as_ref().filter(|p| p.slot == 0) {
+                        overlay.extend(generate_marker_vertices(picked.position, marker_size(&self.ui_state)));
+                    }
+                    overlay.extend(plane_overlay(&self.ui_state));
+                    overlay
                 }
+                _ => Vec::new(),
+            };
+            gpu.upload_overlay(&overlay);
+
+            gpu.set_density_style(self.ui_state.tone_map, self.ui_state.colormap);
+
+            let rendered = self.points.upload(
+                &gpu.device,
+                &gpu.queue,
+                &gpu.histogram_pipelines,
+                &mut gpu.point_buffers,
+                &self.ui_state,
+            );
+            self.rng_engine
+                .stats()
+                .points_rendered
+                .store(rendered, Ordering::Relaxed);
+
+            if let (Some(engine_b), Some(buffers_b)) =
+                (&self.rng_engine_b, &mut gpu.point_buffers_b)
+            {
+                let rendered = self.points_b.upload(
+                    &gpu.device,
+                    &gpu.queue,
+                    &gpu.histogram_pipelines,
+                    buffers_b,
+                    &self.ui_state,
+                );
+                engine_b
+                    .stats()
+                    .points_rendered
+                    .store(rendered, Ordering::Relaxed);
             }
         }
     }
@@ -400,6 +791,55 @@ impl App {
                         self.math_2d_uploaded = false;
                     }
                 }
+                MathResult::ImplicitSurface(mesh) => {
+                    if let Some(gpu) = &mut self.gpu {
+                        gpu.math_buffers.upload_surface(&gpu.queue, &mesh);
+
+                        self.current_math_mesh = CurrentMathMesh::ImplicitSurface;
+                        self.math_last_error = None;
+                        self.math_2d_uploaded = false;
+                    }
+                }
+                MathResult::Trajectory(mesh) => {
+                    if let Some(gpu) = &mut self.gpu {
+                        let curve = gpu.math_buffers.upload_trajectory(&gpu.queue, &mesh);
+
+                        self.cached_curve_vertices = curve.vertices;
+
+                        self.current_math_mesh = CurrentMathMesh::Trajectory;
+                        self.math_last_error = None;
+                        self.math_2d_uploaded = false;
+                    }
+                }
+                MathResult::DomainColoring(mesh) => {
+                    if let Some(gpu) = &mut self.gpu {
+                        gpu.math_buffers.upload_surface(&gpu.queue, &mesh);
+
+                        self.cached_surface_vertices = mesh.mesh.vertices;
+
+                        self.current_math_mesh = CurrentMathMesh::DomainColoring;
+                        self.math_last_error = None;
+                        self.math_2d_uploaded = false;
+                    }
+                }
+                MathResult::VectorField(mesh) => {
+                    if let Some(gpu) = &mut self.gpu {
+                        gpu.math_buffers.upload_vector_field(&gpu.queue, &mesh);
+
+                        self.current_math_mesh = CurrentMathMesh::VectorField;
+                        self.math_last_error = None;
+                        self.math_2d_uploaded = false;
+                    }
+                }
+                MathResult::Fractal(image) => {
+                    if let Some(gpu) = &mut self.gpu {
+                        gpu.fractal.upload(&gpu.device, &gpu.queue, &image);
+
+                        self.current_math_mesh = CurrentMathMesh::Fractal;
+                        self.math_last_error = None;
+                        self.math_2d_uploaded = false;
+                    }
+                }
                 MathResult::Error(e) => {
                     self.math_last_error = Some(e);
                 }
@@ -409,7 +849,7 @@ impl App {
         if self.ui_state.math_view_mode == MathViewMode::Mode2D && !self.math_2d_uploaded {
             if let Some(gpu) = &mut self.gpu {
                 match self.current_math_mesh {
-                    CurrentMathMesh::Surface => {
+                    CurrentMathMesh::Surface | CurrentMathMesh::DomainColoring => {
                         let heatmap_data = surface_to_heatmap(
                             &self.cached_surface_vertices,
                             self.cached_surface_z_min,
@@ -418,17 +858,26 @@ impl App {
                         gpu.math_buffers.upload_heatmap(&gpu.queue, &heatmap_data);
                         self.math_2d_uploaded = true;
                     }
-                    CurrentMathMesh::Curve => {
+                    CurrentMathMesh::Curve | CurrentMathMesh::Trajectory => {
                         let curve_2d_data = curve_to_2d(&self.cached_curve_vertices);
                         gpu.math_buffers.upload_curve_2d(&gpu.queue, &curve_2d_data);
                         self.math_2d_uploaded = true;
                     }
-                    CurrentMathMesh::ParametricSurface => {}
+                    CurrentMathMesh::ParametricSurface
+                    | CurrentMathMesh::ImplicitSurface
+                    | CurrentMathMesh::VectorField
+                    | CurrentMathMesh::Fractal => {}
                     CurrentMathMesh::None => {}
                 }
             }
         }
 
+        // Trajectory points, arrows and streamlines use the PRNG point colormap uniform.
+        if let Some(gpu) = &self.gpu {
+            gpu.set_point_colormap(Some(self.ui_state.colormap));
+            gpu.set_fractal_style(self.ui_state.colormap, self.ui_state.fractal_cycle);
+        }
+
         if self.ui_state.show_grid && !self.grid_uploaded {
             if let Some(gpu) = &mut self.gpu {
                 let grid_verts = generate_grid_vertices(250.0, 20);
@@ -454,15 +903,16 @@ impl App {
 
         let raw_input = egui_state.take_egui_input(window);
 
-        let stats = Arc::clone(self.rng_engine.stats());
-
-        let last_error = match self.ui_state.app_mode {
-            AppMode::Rng => self.rng_engine.last_error(),
-            AppMode::Math => self
+        let mut primary = EngineReadout::from_engine(&self.rng_engine);
+        if self.ui_state.app_mode == AppMode::Math {
+            primary.last_error = self
                 .math_last_error
                 .clone()
-                .or_else(|| self.math_engine.last_error()),
-        };
+                .or_else(|| self.math_engine.last_error());
+        }
+        let secondary = self.rng_engine_b.as_ref().map(EngineReadout::from_engine);
+        let verify = self.verifier.status();
+        let sweep = self.sweeper.status();
 
         let camera_pos = self.camera.position.to_array();
         let camera_speed = self.camera.move_speed;
@@ -470,9 +920,33 @@ impl App {
         let app_mode = self.ui_state.app_mode;
 
         let mut ui_actions = UiActions::default();
+        let mut compare_rect = None;
+        let picked = self
+            .picked_screen_pos()
+            .zip(self.picked.as_ref())
+            .filter(|_| app_mode == AppMode::Rng && self.ui_state.view_mode == ViewMode::Mode3D);
+        let comparing = secondary.is_some();
 
         let full_output = self.egui_ctx.run(raw_input, |ctx| {
-            ui_actions = draw_side_panel(ctx, &mut self.ui_state, &stats, &last_error, is_paused);
+            ui_actions = draw_side_panel(
+                ctx,
+                &mut self.ui_state,
+                &primary,
+                secondary.as_ref(),
+                &verify,
+                &sweep,
+                is_paused,
+            );
+
+            if app_mode == AppMode::Rng && secondary.is_some() {
+                let rect = ctx.available_rect();
+                draw_compare_overlay(
+                    ctx,
+                    rect,
+                    self.ui_state.source_labels,
+                );
+                compare_rect = Some(rect * ctx.pixels_per_point());
+            }
 
             let show_overlay = match app_mode {
                 AppMode::Rng => self.ui_state.view_mode == ViewMode::Mode3D,
@@ -482,8 +956,14 @@ impl App {
             if show_overlay {
                 draw_help_overlay(ctx, camera_pos, camera_speed);
             }
+
+            if let Some((pos, picked)) = picked {
+                let engine = comparing.then_some(picked.slot);
+                draw_pick_tooltip(ctx, pos, engine, &picked.origin, picked.seed);
+            }
         });
 
+        self.compare_rect = compare_rect;
         self.handle_ui_actions(ui_actions);
 
         let Some(gpu) = &mut self.gpu else { return };
@@ -520,6 +1000,13 @@ impl App {
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
+        let viewports = self.compare_rect.map(split_viewports);
+        match viewports {
+            Some([[_, _, w, h], _]) => self.camera.set_aspect(w, h),
+            None => self
+                .camera
+                .set_aspect(gpu.config.width as f32, gpu.config.height as f32),
+        }
         gpu.update_camera(&self.camera);
 
         let paint_jobs = self
@@ -550,9 +1037,19 @@ impl App {
         );
 
         match self.ui_state.app_mode {
-            AppMode::Rng => match self.ui_state.view_mode {
-                ViewMode::Mode3D => gpu.render_3d(&view, &mut encoder),
-                ViewMode::Mode2D => gpu.render_2d(&view, &mut encoder),
+            AppMode::Rng => match (self.ui_state.view_mode, viewports) {
+                (ViewMode::Mode3D, viewports) if self.ui_state.density => {
+                    gpu.render_density(&view, &mut encoder, viewports)
+                }
+                (ViewMode::Mode3D, None) => gpu.render_3d(&view, &mut encoder),
+                (ViewMode::Mode3D, Some(viewports)) => {
+                    gpu.render_3d_split(&view, &mut encoder, viewports)
+                }
+                (ViewMode::Mode2D, viewports) => gpu.render_histogram(&view, &mut encoder, viewports),
+                (ViewMode::Bits, viewports) => {
+                    let diverging = self.ui_state.bit_plot != BitPlot::Raster;
+                    gpu.render_bit_plot(&view, &mut encoder, viewports, diverging)
+                }
             },
             AppMode::Math => match self.ui_state.math_view_mode {
                 MathViewMode::Mode3D => {
@@ -575,14 +1072,34 @@ impl App {
                                 gpu.render_curve(&view, &mut encoder);
                             }
                         }
-                        CurrentMathMesh::ParametricSurface => {
+                        CurrentMathMesh::VectorField => {
+                            gpu.render_vector_field(
+                                &view,
+                                &mut encoder,
+                                !self.ui_state.show_grid,
+                                self.ui_state.math_streamlines,
+                            );
+                        }
+                        CurrentMathMesh::Trajectory if self.ui_state.math_ode_points => {
+                            gpu.render_trajectory_points(&view, &mut encoder, !self.ui_state.show_grid);
+                        }
+                        CurrentMathMesh::Trajectory => {
+                            if self.ui_state.show_grid {
+                                gpu.render_curve_no_clear(&view, &mut encoder);
+                            } else {
+                                gpu.render_curve(&view, &mut encoder);
+                            }
+                        }
+                        CurrentMathMesh::ParametricSurface
+                        | CurrentMathMesh::ImplicitSurface
+                        | CurrentMathMesh::DomainColoring => {
                             if self.ui_state.show_grid {
                                 gpu.render_surface_no_clear(&view, &mut encoder);
                             } else {
                                 gpu.render_surface(&view, &mut encoder);
                             }
                         }
-                        CurrentMathMesh::None => {
+                        CurrentMathMesh::Fractal | CurrentMathMesh::None => {
                             if !self.ui_state.show_grid {
                                 gpu.render_grid(&view, &mut encoder, true);
                             }
@@ -593,10 +1110,18 @@ impl App {
                     CurrentMathMesh::Surface => {
                         gpu.render_math_2d(&view, &mut encoder);
                     }
-                    CurrentMathMesh::Curve => {
+                    CurrentMathMesh::Curve | CurrentMathMesh::Trajectory => {
                         gpu.render_curve_2d(&view, &mut encoder);
                     }
-                    CurrentMathMesh::ParametricSurface => {
+                    CurrentMathMesh::DomainColoring => {
+                        gpu.render_domain_2d(&view, &mut encoder);
+                    }
+                    CurrentMathMesh::Fractal => {
+                        gpu.render_fractal(&view, &mut encoder);
+                    }
+                    CurrentMathMesh::ParametricSurface
+                    | CurrentMathMesh::ImplicitSurface
+                    | CurrentMathMesh::VectorField => {
                         gpu.render_grid(&view, &mut encoder, true);
                     }
                     CurrentMathMesh::None => {
@@ -637,29 +1162,131 @@ impl App {
     }
 
     fn handle_ui_actions(&mut self, actions: UiActions) {
+        if actions.set_compare {
+            self.set_compare(self.ui_state.compare_enabled);
+        }
+
         if actions.compile_code {
             self.rng_engine.update_code(&self.ui_state.code);
+            self.ui_state.source_labels[0] = RNG_EXAMPLES[self.ui_state.selected_example].name;
+        }
+
+        if actions.start_verify {
+            let reference = match self.ui_state.verify_against {
+                VerifyAgainst::Native => Reference::Native(&NATIVE_GENERATORS[self.ui_state.selected_native]),
+                VerifyAgainst::Aelys => Reference::Aelys(self.ui_state.verify_code.clone()),
+            };
+            self.verifier.start(
+                &self.ui_state.code,
+                reference,
+                self.ui_state.seed,
+                self.ui_state.verify_count,
+                self.ui_state.mapping.output_bits,
+            );
+        }
+
+        if actions.start_sweep {
+            self.sweeper.start(
+                &self.ui_state.code,
+                &self.ui_state.sweep_seeds,
+                self.ui_state.sweep_length,
+                self.ui_state.mapping.output_bits,
+            );
+        }
+
+        if actions.run_avalanche {
+            let trials = self.ui_state.avalanche_trials;
+            self.for_each_engine(|e| e.run_avalanche(trials));
+        }
+
+        if actions.detect_lcg {
+            match spectral::detect_lcg(&self.ui_state.code) {
+                Ok(lcg) => {
+                    self.ui_state.spectral_a = format_param(lcg.a);
+                    self.ui_state.spectral_c = format_param(lcg.c);
+                    self.ui_state.spectral_m = format_param(lcg.m);
+                    self.run_spectral();
+                }
+                Err(e) => {
+                    self.ui_state.spectral_report = None;
+                    self.ui_state.spectral_error = Some(e);
+                }
+            }
+        }
+
+        if actions.run_spectral {
+            self.run_spectral();
+        }
+
+        if actions.open_stream {
+            self.rng_engine.open_stream(self.ui_state.stream_spec());
+            self.ui_state.source_labels[0] = "Stream";
+        }
+
+        if let Some(slot) = actions.run_native {
+            let generator = &NATIVE_GENERATORS[self.ui_state.selected_native];
+            let engine = match slot {
+                0 => Some(&self.rng_engine),
+                _ => self.rng_engine_b.as_ref(),
+            };
+            if let Some(engine) = engine {
+                engine.use_native(generator);
+                self.ui_state.source_labels[slot] = generator.name;
+            }
         }
 
         if actions.reset_rng {
-            self.rng_engine.reset();
+            self.for_each_engine(|e| e.reset());
         }
 
         if let Some(seed) = actions.set_seed {
             self.rng_engine.set_seed(seed);
         }
 
-        if actions.toggle_pause {
-            if self.rng_engine.is_paused() {
-                self.rng_engine.resume();
-            } else {
-                self.rng_engine.pause();
+        if let Some(engine_b) = &self.rng_engine_b {
+            if actions.compile_code_b {
+                engine_b.update_code(&self.ui_state.code_b);
+                self.ui_state.source_labels[1] = RNG_EXAMPLES[self.ui_state.selected_example_b].name;
+            }
+            if let Some(seed) = actions.set_seed_b {
+                engine_b.set_seed(seed);
+            }
+        }
+
+        if actions.set_embedding {
+            let (embedding, lag) = (self.ui_state.embedding, self.ui_state.embedding_lag as usize);
+            self.for_each_engine(|e| e.set_embedding(embedding, lag));
+        }
+
+        if actions.set_mapping {
+            self.rng_engine.set_mapping(self.ui_state.mapping);
+            if let Some(engine_b) = &self.rng_engine_b {
+                engine_b.set_mapping(self.ui_state.mapping_b());
             }
         }
 
+        if let Some(count) = actions.step {
+            self.for_each_engine(|e| e.step(count));
+        }
+
+        if actions.set_history {
+            let enabled = self.ui_state.record_history;
+            self.for_each_engine(|e| e.set_history(enabled));
+        }
+
+        if actions.set_streams {
+            let streams = self.ui_state.streams;
+            self.for_each_engine(|e| e.set_streams(streams));
+        }
+
+        if actions.toggle_pause {
+            self.toggle_pause();
+        }
+
         if actions.clear_points {
-            self.accumulated_points_3d.clear();
-            self.accumulated_points_2d.clear();
+            self.points.clear();
+            self.points_b.clear();
+            self.picked = None;
         }
 
         if actions.compile_math {
@@ -667,6 +1294,151 @@ impl App {
         }
     }
 
+    fn run_spectral(&mut self) {
+        let state = &mut self.ui_state;
+        let lcg = parse_param(&state.spectral_a).and_then(|a| {
+            let c = parse_param(&state.spectral_c)?;
+            let m = parse_param(&state.spectral_m)?;
+            Lcg::new(a, c, m)
+        });
+        match lcg {
+            Ok(lcg) => {
+                state.spectral_report = Some(spectral::spectral_test(&lcg));
+                state.spectral_error = None;
+            }
+            Err(e) => {
+                state.spectral_report = None;
+                state.spectral_error = Some(e);
+            }
+        }
+    }
+
+    fn set_compare(&mut self, enabled: bool) {
+        if !enabled {
+            self.rng_engine_b = None;
+            self.points_b = PointAccumulator::default();
+            self.compare_rect = None;
+            if let Some(gpu) = &mut self.gpu {
+                gpu.point_buffers_b = None;
+            }
+            return;
+        }
+
+        let engine_b = RngEngine::new();
+        engine_b.set_embedding(self.ui_state.embedding, self.ui_state.embedding_lag as usize);
+        engine_b.set_mapping(self.ui_state.mapping_b());
+        engine_b.set_bit_view(self.bit_view_enabled);
+        engine_b.set_streams(self.ui_state.streams);
+        engine_b.set_history(self.ui_state.record_history);
+        engine_b.set_seed(self.ui_state.seed_b);
+        engine_b.update_code(&self.ui_state.code_b);
+        self.ui_state.source_labels[1] = RNG_EXAMPLES[self.ui_state.selected_example_b].name;
+        if self.rng_engine.is_paused() {
+            engine_b.pause();
+        }
+        self.ui_state.code_b_needs_compile = false;
+        self.rng_engine_b = Some(engine_b);
+
+        if let Some(gpu) = &mut self.gpu {
+            gpu.point_buffers_b = Some(PointCloudBuffers::new(
+                &gpu.device,
+                &gpu.histogram_pipelines,
+                &gpu.camera_bind_group_layout,
+                &gpu.camera_buffer,
+            ));
+        }
+    }
+
+    /// Picks the 3D point under `cursor` (physical pixels), or clears the pick.
+    fn pick(&mut self, cursor: [f32; 2]) {
+        self.picked = None;
+        if self.ui_state.app_mode != AppMode::Rng || self.ui_state.view_mode != ViewMode::Mode3D {
+            return;
+        }
+        let Some(gpu) = &self.gpu else { return };
+
+        let (slot, viewport) = match self.compare_rect.map(split_viewports) {
+            Some(viewports) => {
+                let slot = if cursor[0] < viewports[1][0] { 0 } else { 1 };
+                (slot, viewports[slot])
+            }
+            None => (0, [0.0, 0.0, gpu.config.width as f32, gpu.config.height as f32]),
+        };
+        let points = if slot == 0 { &self.points } else { &self.points_b };
+        let view_proj = self.camera.view_projection_matrix();
+
+        let Some(i) = pick_point(&points.points_3d, view_proj, viewport, cursor, PICK_RADIUS) else {
+            return;
+        };
+        let origin = points.origins_3d[i];
+        self.picked = Some(PickedPoint {
+            slot,
+            position: points.points_3d[i].position,
+            origin,
+            seed: points.seeds.get(origin.stream as usize).copied().flatten(),
+        });
+    }
+
+    /// Where the picked point is on screen now, in egui points.
+    fn picked_screen_pos(&self) -> Option<egui::Pos2> {
+        let picked = self.picked.as_ref()?;
+        let gpu = self.gpu.as_ref()?;
+        let viewport = match self.compare_rect.map(split_viewports) {
+            Some(viewports) => viewports[picked.slot],
+            None => [0.0, 0.0, gpu.config.width as f32, gpu.config.height as f32],
+        };
+        let [x, y, _] = project_point(self.camera.view_projection_matrix(), viewport, picked.position)?;
+        Some(egui::pos2(x, y) / self.egui_ctx.pixels_per_point())
+    }
+
+    /// Whether the mouse pans and zooms the fractal instead of moving the camera.
+    fn fractal_active(&self) -> bool {
+        self.ui_state.app_mode == AppMode::Math
+            && self.ui_state.math_view_mode == MathViewMode::Mode2D
+            && self.ui_state.math_function_type == MathFunctionKind::Fractal
+    }
+
+    /// The fractal view over the whole window, one grid cell per physical pixel.
+    fn fractal_view(&self) -> FractalView {
+        let (width, height) = self
+            .gpu
+            .as_ref()
+            .map_or((1, 1), |gpu| (gpu.config.width, gpu.config.height));
+        FractalView {
+            center: self.ui_state.fractal_center,
+            scale: self.ui_state.fractal_scale,
+            width: width as usize,
+            height: height as usize,
+        }
+    }
+
+    /// Zooms in by `scroll` notches (out if negative), keeping the point under the cursor fixed.
+    fn zoom_fractal(&mut self, scroll: f32) {
+        let anchor = self.fractal_view().point(self.input.cursor.map(|v| v as f64));
+        let state = &mut self.ui_state;
+        // Past about 1e-13 neighbouring pixels round to the same f64.
+        let scale = (state.fractal_scale * 0.8f64.powf(scroll as f64)).clamp(1e-13, 16.0);
+        let factor = scale / state.fractal_scale;
+        state.fractal_scale = scale;
+        state.fractal_center = std::array::from_fn(|i| anchor[i] + (state.fractal_center[i] - anchor[i]) * factor);
+        self.compile_math();
+    }
+
+    fn for_each_engine(&self, f: impl Fn(&RngEngine)) {
+        f(&self.rng_engine);
+        if let Some(engine_b) = &self.rng_engine_b {
+            f(engine_b);
+        }
+    }
+
+    fn toggle_pause(&self) {
+        if self.rng_engine.is_paused() {
+            self.for_each_engine(|e| e.resume());
+        } else {
+            self.for_each_engine(|e| e.pause());
+        }
+    }
+
     fn handle_key(&mut self, key: KeyCode, pressed: bool) {
         let value = if pressed { 1.0 } else { 0.0 };
 
@@ -684,13 +1456,7 @@ impl App {
                     window.set_cursor_visible(true);
                 }
             }
-            KeyCode::KeyP if pressed => {
-                if self.rng_engine.is_paused() {
-                    self.rng_engine.resume();
-                } else {
-                    self.rng_engine.pause();
-                }
-            }
+            KeyCode::KeyP if pressed => self.toggle_pause(),
             _ => {}
         }
     }
@@ -718,7 +1484,7 @@ impl ApplicationHandler for App {
 
         match event {
             WindowEvent::CloseRequested => {
-                self.rng_engine.stop();
+                self.for_each_engine(|e| e.stop());
                 self.math_engine.stop();
                 event_loop.exit();
             }
@@ -729,6 +1495,9 @@ impl ApplicationHandler for App {
                     self.camera
                         .set_aspect(size.width as f32, size.height as f32);
                 }
+                if self.fractal_active() && matches!(self.current_math_mesh, CurrentMathMesh::Fractal) {
+                    self.compile_math();
+                }
             }
 
             WindowEvent::KeyboardInput { event, .. } => {
@@ -755,12 +1524,39 @@ impl ApplicationHandler for App {
                 }
             }
 
+            WindowEvent::CursorMoved { position, .. } => {
+                let cursor = [position.x as f32, position.y as f32];
+                if self.input.panning && self.fractal_active() {
+                    let unit = self.fractal_view().pixel_size();
+                    self.ui_state.fractal_center[0] -= (cursor[0] - self.input.cursor[0]) as f64 * unit;
+                    self.ui_state.fractal_center[1] += (cursor[1] - self.input.cursor[1]) as f64 * unit;
+                    self.compile_math();
+                }
+                self.input.cursor = cursor;
+            }
+
+            WindowEvent::MouseInput {
+                button: MouseButton::Left,
+                state,
+                ..
+            } => {
+                let pressed = state == ElementState::Pressed;
+                self.input.panning = pressed && self.fractal_active();
+                if pressed {
+                    self.pick(self.input.cursor);
+                }
+            }
+
             WindowEvent::MouseWheel { delta, .. } => {
                 let scroll = match delta {
                     winit::event::MouseScrollDelta::LineDelta(_, y) => y,
                     winit::event::MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 50.0,
                 };
-                self.camera.process_scroll(scroll);
+                if self.fractal_active() {
+                    self.zoom_fractal(scroll);
+                } else {
+                    self.camera.process_scroll(scroll);
+                }
             }
 
             WindowEvent::RedrawRequested => {
@@ -789,6 +1585,11 @@ impl ApplicationHandler for App {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     let event_loop = EventLoop::new().unwrap();
     event_loop.set_control_flow(ControlFlow::Poll);
 
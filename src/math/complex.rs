@@ -0,0 +1,33 @@
+use std::f64::consts::TAU;
+
+/// Domain colouring of `re + i im`: the hue is the argument (red on the positive real axis),
+/// and the brightness ramps up through each band between powers of two of the modulus,
+/// so the bands crowd together around zeros and poles.
+pub fn domain_color(re: f64, im: f64) -> [f32; 3] {
+    let modulus = re.hypot(im);
+    if modulus == 0.0 {
+        return [0.0; 3];
+    }
+    if !modulus.is_finite() {
+        return [1.0; 3];
+    }
+
+    let hue = (im.atan2(re) / TAU).rem_euclid(1.0);
+    let band = modulus.log2().rem_euclid(1.0);
+    hsv_to_rgb(hue, 0.85, 0.6 + 0.4 * band)
+}
+
+fn hsv_to_rgb(h: f64, s: f64, v: f64) -> [f32; 3] {
+    let sector = h * 6.0;
+    let f = sector.fract();
+    let (p, q, t) = (v * (1.0 - s), v * (1.0 - s * f), v * (1.0 - s * (1.0 - f)));
+    let (r, g, b) = match sector as u32 % 6 {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    [r as f32, g as f32, b as f32]
+}
@@ -1,10 +1,17 @@
-use aelys::{Value, get_function, new_vm, run_with_vm};
+use aelys::{CallableFunction, Value, get_function, new_vm, run_with_vm};
 use crossbeam::channel::{self, Receiver, Sender};
 use parking_lot::Mutex;
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 
-use crate::math::mesh::{CurveMesh, ParametricSurfaceMesh, SurfaceMesh, TriangleMesh};
+use crate::math::complex::domain_color;
+use crate::math::fractal::{FractalImage, FractalView, render};
+use crate::math::implicit::{SampledField, extract_isosurface};
+use crate::math::mesh::{
+    CurveMesh, ParametricSurfaceMesh, SurfaceMesh, TrajectoryMesh, TriangleMesh, VectorFieldMesh,
+};
+use crate::math::ode::{OdeMethod, integrate};
+use crate::math::vector_field::{StreamlineSeeds, sample_vector_field};
 
 pub enum MathCommand {
     CompileSurface {
@@ -25,6 +32,39 @@ pub enum MathCommand {
         u_samples: usize,
         v_samples: usize,
     },
+    CompileImplicit {
+        code: String,
+        x_range: (f64, f64),
+        y_range: (f64, f64),
+        z_range: (f64, f64),
+        resolution: usize,
+        iso_level: f64,
+    },
+    IntegrateOde {
+        code: String,
+        initial: [f64; 3],
+        t_range: (f64, f64),
+        step: f64,
+        method: OdeMethod,
+    },
+    CompileComplex {
+        code: String,
+        x_range: (f64, f64),
+        y_range: (f64, f64),
+        resolution: usize,
+    },
+    CompileVectorField {
+        code: String,
+        ranges: [(f64, f64); 3],
+        resolution: usize,
+        seeds: Option<StreamlineSeeds>,
+    },
+    CompileFractal {
+        code: String,
+        view: FractalView,
+        max_iterations: u32,
+        julia: Option<[f64; 2]>,
+    },
     Stop,
 }
 
@@ -32,6 +72,11 @@ pub enum MathResult {
     Surface(SurfaceMesh),
     ParametricCurve(CurveMesh),
     ParametricSurface(ParametricSurfaceMesh),
+    ImplicitSurface(SurfaceMesh),
+    Trajectory(TrajectoryMesh),
+    VectorField(VectorFieldMesh),
+    DomainColoring(SurfaceMesh),
+    Fractal(FractalImage),
     Error(String),
 }
 
@@ -101,6 +146,88 @@ impl MathEngine {
         });
     }
 
+    pub fn compile_implicit(
+        &self,
+        code: &str,
+        ranges: [(f64, f64); 3],
+        resolution: usize,
+        iso_level: f64,
+    ) {
+        let [x_range, y_range, z_range] = ranges;
+        let _ = self.tx_cmd.send(MathCommand::CompileImplicit {
+            code: code.to_string(),
+            x_range,
+            y_range,
+            z_range,
+            resolution,
+            iso_level,
+        });
+    }
+
+    pub fn integrate_ode(
+        &self,
+        code: &str,
+        initial: [f64; 3],
+        t_range: (f64, f64),
+        step: f64,
+        method: OdeMethod,
+    ) {
+        let _ = self.tx_cmd.send(MathCommand::IntegrateOde {
+            code: code.to_string(),
+            initial,
+            t_range,
+            step,
+            method,
+        });
+    }
+
+    pub fn compile_complex(
+        &self,
+        code: &str,
+        x_range: (f64, f64),
+        y_range: (f64, f64),
+        resolution: usize,
+    ) {
+        let _ = self.tx_cmd.send(MathCommand::CompileComplex {
+            code: code.to_string(),
+            x_range,
+            y_range,
+            resolution,
+        });
+    }
+
+    pub fn compile_vector_field(
+        &self,
+        code: &str,
+        ranges: [(f64, f64); 3],
+        resolution: usize,
+        seeds: Option<StreamlineSeeds>,
+    ) {
+        let _ = self.tx_cmd.send(MathCommand::CompileVectorField {
+            code: code.to_string(),
+            ranges,
+            resolution,
+            seeds,
+        });
+    }
+
+    /// Renders progressively, sending a result after each pass; `julia` is the constant c
+    /// of a Julia set, `None` for the Mandelbrot-style set over c.
+    pub fn compile_fractal(
+        &self,
+        code: &str,
+        view: FractalView,
+        max_iterations: u32,
+        julia: Option<[f64; 2]>,
+    ) {
+        let _ = self.tx_cmd.send(MathCommand::CompileFractal {
+            code: code.to_string(),
+            view,
+            max_iterations,
+            julia,
+        });
+    }
+
     pub fn try_recv_result(&self) -> Option<MathResult> {
         self.rx_result.try_recv().ok()
     }
@@ -128,6 +255,9 @@ fn math_thread(
     tx_result: Sender<MathResult>,
     last_error: Arc<Mutex<Option<String>>>,
 ) {
+    // The last sampled implicit field, so moving the iso-level only re-extracts the surface.
+    let mut implicit_cache: Option<(String, SampledField)> = None;
+
     loop {
         let cmd = match rx_cmd.recv() {
             Ok(c) => c,
@@ -191,11 +321,316 @@ fn math_thread(
                     }
                 }
             }
+            MathCommand::CompileImplicit {
+                code,
+                x_range,
+                y_range,
+                z_range,
+                resolution,
+                iso_level,
+            } => {
+                *last_error.lock() = None;
+
+                let ranges = [x_range, y_range, z_range];
+                let cached = implicit_cache.as_ref().is_some_and(|(cached_code, field)| {
+                    *cached_code == code && field.ranges == ranges && field.n == resolution
+                });
+                if !cached {
+                    implicit_cache = None;
+                    match sample_implicit_field(&code, ranges, resolution) {
+                        Ok(field) => implicit_cache = Some((code, field)),
+                        Err(e) => {
+                            *last_error.lock() = Some(e.clone());
+                            let _ = tx_result.send(MathResult::Error(e));
+                            continue;
+                        }
+                    }
+                }
+
+                if let Some((_, field)) = &implicit_cache {
+                    let mesh = extract_isosurface(field, iso_level);
+                    let _ = tx_result.send(MathResult::ImplicitSurface(mesh));
+                }
+            }
+            MathCommand::IntegrateOde {
+                code,
+                initial,
+                t_range,
+                step,
+                method,
+            } => {
+                *last_error.lock() = None;
+
+                match compile_and_integrate_ode(&code, initial, t_range, step, method) {
+                    Ok(mesh) => {
+                        let _ = tx_result.send(MathResult::Trajectory(mesh));
+                    }
+                    Err(e) => {
+                        *last_error.lock() = Some(e.clone());
+                        let _ = tx_result.send(MathResult::Error(e));
+                    }
+                }
+            }
+            MathCommand::CompileComplex {
+                code,
+                x_range,
+                y_range,
+                resolution,
+            } => {
+                *last_error.lock() = None;
+
+                match compile_and_sample_complex(&code, x_range, y_range, resolution) {
+                    Ok(mesh) => {
+                        let _ = tx_result.send(MathResult::DomainColoring(mesh));
+                    }
+                    Err(e) => {
+                        *last_error.lock() = Some(e.clone());
+                        let _ = tx_result.send(MathResult::Error(e));
+                    }
+                }
+            }
+            MathCommand::CompileVectorField {
+                code,
+                ranges,
+                resolution,
+                seeds,
+            } => {
+                *last_error.lock() = None;
+
+                match compile_and_sample_vector_field(&code, ranges, resolution, seeds.as_ref()) {
+                    Ok(mesh) => {
+                        let _ = tx_result.send(MathResult::VectorField(mesh));
+                    }
+                    Err(e) => {
+                        *last_error.lock() = Some(e.clone());
+                        let _ = tx_result.send(MathResult::Error(e));
+                    }
+                }
+            }
+            MathCommand::CompileFractal {
+                code,
+                view,
+                max_iterations,
+                julia,
+            } => {
+                *last_error.lock() = None;
+
+                // Any newer command, like the next step of a pan or zoom, abandons this render.
+                let cancelled = || !rx_cmd.is_empty();
+                let emit = |image: FractalImage, last: bool| {
+                    // Coarse passes are skipped if the previous ones haven't been picked up yet.
+                    if last {
+                        let _ = tx_result.send(MathResult::Fractal(image));
+                    } else {
+                        let _ = tx_result.try_send(MathResult::Fractal(image));
+                    }
+                };
+
+                if let Err(e) =
+                    compile_and_render_fractal(&code, &view, max_iterations, julia, &cancelled, emit)
+                {
+                    *last_error.lock() = Some(e.clone());
+                    let _ = tx_result.send(MathResult::Error(e));
+                }
+            }
             MathCommand::Stop => return,
         }
     }
 }
 
+fn compile_and_render_fractal(
+    code: &str,
+    view: &FractalView,
+    max_iterations: u32,
+    julia: Option<[f64; 2]>,
+    cancelled: &(dyn Fn() -> bool + Sync),
+    emit: impl FnMut(FractalImage, bool),
+) -> Result<(), String> {
+    let full_code = format!("needs std.math;\n{}", code);
+
+    // Each worker thread compiles the code into its own VM.
+    let make_iterate = || -> Result<_, String> {
+        let mut vm = new_vm().map_err(|e| format!("VM init error: {}", e))?;
+        run_with_vm(&mut vm, &full_code, "math_fractal").map_err(|e| format!("{}", e))?;
+
+        let func_re = get_function(&vm, "next_re").map_err(|e| format!("next_re: {}", e))?;
+        let func_im = get_function(&vm, "next_im").map_err(|e| format!("next_im: {}", e))?;
+
+        if func_re.arity() != 4 || func_im.arity() != 4 {
+            return Err(
+                "Functions next_re, next_im must each take 4 arguments (zr, zi, cr, ci)".to_string(),
+            );
+        }
+
+        Ok(move |[zr, zi]: [f64; 2], [cr, ci]: [f64; 2]| {
+            let args = [Value::float(zr), Value::float(zi), Value::float(cr), Value::float(ci)];
+            let mut call = |func: &CallableFunction, name: &str| {
+                let result = func
+                    .call(&mut vm, &args)
+                    .map_err(|e| format!("{} error at z = ({}, {}), c = ({}, {}): {}", name, zr, zi, cr, ci, e))?;
+                Ok::<f64, String>(
+                    result
+                        .as_float()
+                        .unwrap_or_else(|| result.as_int().unwrap_or(0) as f64),
+                )
+            };
+            Ok([call(&func_re, "next_re")?, call(&func_im, "next_im")?])
+        })
+    };
+
+    render(view, max_iterations, julia, make_iterate, cancelled, emit)
+}
+
+fn compile_and_sample_vector_field(
+    code: &str,
+    ranges: [(f64, f64); 3],
+    resolution: usize,
+    seeds: Option<&StreamlineSeeds>,
+) -> Result<VectorFieldMesh, String> {
+    let mut vm = new_vm().map_err(|e| format!("VM init error: {}", e))?;
+
+    let full_code = format!("needs std.math;\n{}", code);
+    run_with_vm(&mut vm, &full_code, "math_vector_field").map_err(|e| format!("{}", e))?;
+
+    let func_x = get_function(&vm, "vx").map_err(|e| format!("vx: {}", e))?;
+    let func_y = get_function(&vm, "vy").map_err(|e| format!("vy: {}", e))?;
+    let func_z = get_function(&vm, "vz").map_err(|e| format!("vz: {}", e))?;
+
+    if func_x.arity() != 3 || func_y.arity() != 3 || func_z.arity() != 3 {
+        return Err("Functions vx, vy, vz must each take 3 arguments (x, y, z)".to_string());
+    }
+
+    let field = |[x, y, z]: [f64; 3]| {
+        let args = [Value::float(x), Value::float(y), Value::float(z)];
+        let mut call = |func: &CallableFunction, name: &str| {
+            let result = func
+                .call(&mut vm, &args)
+                .map_err(|e| format!("{} error at ({}, {}, {}): {}", name, x, y, z, e))?;
+            Ok::<f64, String>(
+                result
+                    .as_float()
+                    .unwrap_or_else(|| result.as_int().unwrap_or(0) as f64),
+            )
+        };
+        Ok([call(&func_x, "vx")?, call(&func_y, "vy")?, call(&func_z, "vz")?])
+    };
+
+    sample_vector_field(field, ranges, resolution, seeds)
+}
+
+fn compile_and_integrate_ode(
+    code: &str,
+    initial: [f64; 3],
+    t_range: (f64, f64),
+    step: f64,
+    method: OdeMethod,
+) -> Result<TrajectoryMesh, String> {
+    let mut vm = new_vm().map_err(|e| format!("VM init error: {}", e))?;
+
+    let full_code = format!("needs std.math;\n{}", code);
+    run_with_vm(&mut vm, &full_code, "math_ode").map_err(|e| format!("{}", e))?;
+
+    let func_x = get_function(&vm, "dx").map_err(|e| format!("dx: {}", e))?;
+    let func_y = get_function(&vm, "dy").map_err(|e| format!("dy: {}", e))?;
+    let func_z = get_function(&vm, "dz").map_err(|e| format!("dz: {}", e))?;
+
+    if func_x.arity() != 4 || func_y.arity() != 4 || func_z.arity() != 4 {
+        return Err("Functions dx, dy, dz must each take 4 arguments (x, y, z, t)".to_string());
+    }
+
+    let deriv = |t: f64, [x, y, z]: [f64; 3]| {
+        let args = [Value::float(x), Value::float(y), Value::float(z), Value::float(t)];
+        let mut call = |func: &CallableFunction, name: &str| {
+            let result = func
+                .call(&mut vm, &args)
+                .map_err(|e| format!("{} error at t = {}: {}", name, t, e))?;
+            Ok::<f64, String>(
+                result
+                    .as_float()
+                    .unwrap_or_else(|| result.as_int().unwrap_or(0) as f64),
+            )
+        };
+        Ok([call(&func_x, "dx")?, call(&func_y, "dy")?, call(&func_z, "dz")?])
+    };
+
+    let trajectory = integrate(deriv, initial, t_range.0, t_range.1 - t_range.0, step, method)?;
+
+    // Attractors live at very different scales, so fit the trajectory's bounding box
+    // into the view instead of using a fixed scale.
+    let mut min = [f64::MAX; 3];
+    let mut max = [f64::MIN; 3];
+    for (_, p) in &trajectory {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    let extent = (0..3).map(|i| max[i] - min[i]).fold(0.001, f64::max);
+    let scale = 200.0 / extent;
+    let center: [f64; 3] = std::array::from_fn(|i| (min[i] + max[i]) / 2.0);
+
+    let t_end = trajectory.last().map_or(t_range.1, |(t, _)| *t);
+    let t_span = (t_end - t_range.0).max(f64::EPSILON);
+
+    let mut vertices = Vec::with_capacity(trajectory.len() * 3);
+    let mut times = Vec::with_capacity(trajectory.len());
+    for (t, p) in &trajectory {
+        vertices.extend((0..3).map(|i| ((p[i] - center[i]) * scale) as f32));
+        times.push(((t - t_range.0) / t_span) as f32);
+    }
+
+    Ok(TrajectoryMesh { vertices, times })
+}
+
+fn sample_implicit_field(
+    code: &str,
+    ranges: [(f64, f64); 3],
+    resolution: usize,
+) -> Result<SampledField, String> {
+    let mut vm = new_vm().map_err(|e| format!("VM init error: {}", e))?;
+
+    let full_code = format!("needs std.math;\n{}", code);
+    run_with_vm(&mut vm, &full_code, "math_implicit").map_err(|e| format!("{}", e))?;
+
+    let func = get_function(&vm, "f").map_err(|e| format!("{}", e))?;
+
+    if func.arity() != 3 {
+        return Err(format!(
+            "Function 'f' must take 3 arguments (x, y, z), got {}",
+            func.arity()
+        ));
+    }
+
+    let step = ranges.map(|(min, max)| (max - min) / (resolution - 1) as f64);
+    let mut values = Vec::with_capacity(resolution * resolution * resolution);
+
+    for i in 0..resolution {
+        let x = ranges[0].0 + i as f64 * step[0];
+        for j in 0..resolution {
+            let y = ranges[1].0 + j as f64 * step[1];
+            for k in 0..resolution {
+                let z = ranges[2].0 + k as f64 * step[2];
+
+                let result = func
+                    .call(&mut vm, &[Value::float(x), Value::float(y), Value::float(z)])
+                    .map_err(|e| format!("Evaluation error at ({}, {}, {}): {}", x, y, z, e))?;
+
+                let value = result
+                    .as_float()
+                    .unwrap_or_else(|| result.as_int().unwrap_or(0) as f64);
+                // Treat holes in the domain as outside the surface.
+                values.push(if value.is_finite() { value } else { f64::MAX });
+            }
+        }
+    }
+
+    Ok(SampledField {
+        n: resolution,
+        ranges,
+        values,
+    })
+}
+
 fn compile_and_sample_surface(
     code: &str,
     x_range: (f64, f64),
@@ -216,16 +651,10 @@ fn compile_and_sample_surface(
         ));
     }
 
-    let mut vertices = Vec::with_capacity(resolution * resolution * 3);
-    let mut normals = Vec::with_capacity(resolution * resolution * 3);
-    let mut indices = Vec::new();
-
     let dx = (x_range.1 - x_range.0) / (resolution - 1) as f64;
     let dy = (y_range.1 - y_range.0) / (resolution - 1) as f64;
 
     let mut z_values = vec![vec![0.0f64; resolution]; resolution];
-    let mut z_min = f64::MAX;
-    let mut z_max = f64::MIN;
 
     for i in 0..resolution {
         for j in 0..resolution {
@@ -236,18 +665,95 @@ fn compile_and_sample_surface(
                 .call(&mut vm, &[Value::float(x), Value::float(y)])
                 .map_err(|e| format!("Evaluation error at ({}, {}): {}", x, y, e))?;
 
-            let z = result
+            z_values[i][j] = result
                 .as_float()
                 .unwrap_or_else(|| result.as_int().unwrap_or(0) as f64);
-            z_values[i][j] = z;
+        }
+    }
 
-            if z.is_finite() {
-                z_min = z_min.min(z);
-                z_max = z_max.max(z);
-            }
+    Ok(surface_from_grid(&z_values, x_range, y_range))
+}
+
+/// Samples `re(x, y) + i im(x, y)` as a surface of height |f| coloured by domain colouring.
+/// Heights are capped at the 95th percentile so a pole doesn't flatten everything else.
+fn compile_and_sample_complex(
+    code: &str,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    resolution: usize,
+) -> Result<SurfaceMesh, String> {
+    let mut vm = new_vm().map_err(|e| format!("VM init error: {}", e))?;
+
+    let full_code = format!("needs std.math;\n{}", code);
+    run_with_vm(&mut vm, &full_code, "math_complex").map_err(|e| format!("{}", e))?;
+
+    let func_re = get_function(&vm, "re").map_err(|e| format!("re: {}", e))?;
+    let func_im = get_function(&vm, "im").map_err(|e| format!("im: {}", e))?;
+
+    if func_re.arity() != 2 || func_im.arity() != 2 {
+        return Err("Functions re, im must each take 2 arguments (x, y)".to_string());
+    }
+
+    let dx = (x_range.1 - x_range.0) / (resolution - 1) as f64;
+    let dy = (y_range.1 - y_range.0) / (resolution - 1) as f64;
+
+    let mut moduli = vec![vec![0.0f64; resolution]; resolution];
+    let mut colors = Vec::with_capacity(resolution * resolution * 3);
+
+    for (i, row) in moduli.iter_mut().enumerate() {
+        for (j, modulus) in row.iter_mut().enumerate() {
+            let x = x_range.0 + i as f64 * dx;
+            let y = y_range.0 + j as f64 * dy;
+
+            let mut call = |func: &CallableFunction, name: &str| {
+                let result = func
+                    .call(&mut vm, &[Value::float(x), Value::float(y)])
+                    .map_err(|e| format!("{} error at ({}, {}): {}", name, x, y, e))?;
+                Ok::<f64, String>(
+                    result
+                        .as_float()
+                        .unwrap_or_else(|| result.as_int().unwrap_or(0) as f64),
+                )
+            };
+            let re = call(&func_re, "re")?;
+            let im = call(&func_im, "im")?;
+
+            *modulus = re.hypot(im);
+            colors.extend(domain_color(re, im));
         }
     }
 
+    let mut sorted: Vec<f64> = moduli.iter().flatten().copied().filter(|m| m.is_finite()).collect();
+    sorted.sort_by(f64::total_cmp);
+    let cap = sorted.get(sorted.len() * 95 / 100).copied().unwrap_or(1.0);
+    for modulus in moduli.iter_mut().flatten() {
+        *modulus = modulus.min(cap);
+    }
+
+    Ok(SurfaceMesh {
+        colors,
+        ..surface_from_grid(&moduli, x_range, y_range)
+    })
+}
+
+/// Triangulates `z_values[i][j]`, sampled on a regular grid over the ranges, into a
+/// surface with z scaled to a fixed height and y up.
+fn surface_from_grid(z_values: &[Vec<f64>], x_range: (f64, f64), y_range: (f64, f64)) -> SurfaceMesh {
+    let resolution = z_values.len();
+    let dx = (x_range.1 - x_range.0) / (resolution - 1) as f64;
+    let dy = (y_range.1 - y_range.0) / (resolution - 1) as f64;
+
+    let mut vertices = Vec::with_capacity(resolution * resolution * 3);
+    let mut normals = Vec::with_capacity(resolution * resolution * 3);
+    let mut indices = Vec::new();
+
+    let mut z_min = f64::MAX;
+    let mut z_max = f64::MIN;
+    for z in z_values.iter().flatten().filter(|z| z.is_finite()) {
+        z_min = z_min.min(*z);
+        z_max = z_max.max(*z);
+    }
+
     let z_range = (z_max - z_min).max(0.001);
     let scale = 100.0 / z_range;
     let z_offset = (z_min + z_max) / 2.0;
@@ -305,7 +811,7 @@ fn compile_and_sample_surface(
         }
     }
 
-    Ok(SurfaceMesh {
+    SurfaceMesh {
         mesh: TriangleMesh {
             vertices,
             normals,
@@ -313,7 +819,8 @@ fn compile_and_sample_surface(
         },
         z_min: z_min as f32,
         z_max: z_max as f32,
-    })
+        colors: Vec::new(),
+    }
 }
 
 fn compile_and_sample_parametric(
@@ -5,11 +5,14 @@ pub struct MathExample {
     pub code: &'static str,
     pub x_range: (f64, f64),
     pub y_range: (f64, f64),
+    pub z_range: (f64, f64),
     pub t_range: (f64, f64),
     pub u_range: (f64, f64),
     pub v_range: (f64, f64),
     pub u_samples: usize,
     pub v_samples: usize,
+    pub initial: [f64; 3],
+    pub step: f64,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -17,6 +20,11 @@ pub enum MathFunctionKind {
     Surface,
     ParametricCurve,
     ParametricSurface,
+    Implicit,
+    Ode,
+    VectorField,
+    Complex,
+    Fractal,
 }
 
 pub const MATH_EXAMPLES: &[MathExample] = &[
@@ -29,11 +37,14 @@ pub const MATH_EXAMPLES: &[MathExample] = &[
 }"#,
         x_range: (-6.28, 6.28),
         y_range: (-6.28, 6.28),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 1.0),
         u_range: (0.0, 1.0),
         v_range: (0.0, 1.0),
         u_samples: 50,
         v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Ripple",
@@ -45,11 +56,14 @@ pub const MATH_EXAMPLES: &[MathExample] = &[
 }"#,
         x_range: (-5.0, 5.0),
         y_range: (-5.0, 5.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 1.0),
         u_range: (0.0, 1.0),
         v_range: (0.0, 1.0),
         u_samples: 50,
         v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Saddle",
@@ -60,11 +74,14 @@ pub const MATH_EXAMPLES: &[MathExample] = &[
 }"#,
         x_range: (-3.0, 3.0),
         y_range: (-3.0, 3.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 1.0),
         u_range: (0.0, 1.0),
         v_range: (0.0, 1.0),
         u_samples: 50,
         v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Peaks",
@@ -78,11 +95,14 @@ pub const MATH_EXAMPLES: &[MathExample] = &[
 }"#,
         x_range: (-3.0, 3.0),
         y_range: (-3.0, 3.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 1.0),
         u_range: (0.0, 1.0),
         v_range: (0.0, 1.0),
         u_samples: 50,
         v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Helix",
@@ -93,11 +113,14 @@ fn fy(t: float) -> float { t }
 fn fz(t: float) -> float { math.sin(t * 4.0) }"#,
         x_range: (-1.0, 1.0),
         y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 6.28),
         u_range: (0.0, 1.0),
         v_range: (0.0, 1.0),
         u_samples: 50,
         v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Trefoil Knot",
@@ -108,11 +131,14 @@ fn fy(t: float) -> float { math.cos(t) - 2.0 * math.cos(2.0*t) }
 fn fz(t: float) -> float { -math.sin(3.0*t) }"#,
         x_range: (-1.0, 1.0),
         y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 6.28),
         u_range: (0.0, 1.0),
         v_range: (0.0, 1.0),
         u_samples: 50,
         v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Lissajous",
@@ -123,11 +149,14 @@ fn fy(t: float) -> float { math.sin(4.0*t) }
 fn fz(t: float) -> float { math.sin(5.0*t) }"#,
         x_range: (-1.0, 1.0),
         y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 6.28),
         u_range: (0.0, 1.0),
         v_range: (0.0, 1.0),
         u_samples: 50,
         v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Torus Knot",
@@ -144,11 +173,14 @@ fn fz(t: float) -> float {
 }"#,
         x_range: (-1.0, 1.0),
         y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 6.28),
         u_range: (0.0, 1.0),
         v_range: (0.0, 1.0),
         u_samples: 50,
         v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Sphere",
@@ -159,11 +191,14 @@ fn fy(u: float, v: float) -> float { math.cos(u) }
 fn fz(u: float, v: float) -> float { math.sin(u) * math.sin(v) }"#,
         x_range: (-1.0, 1.0),
         y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 1.0),
         u_range: (0.0, 3.14159),
         v_range: (0.0, 6.28318),
         u_samples: 40,
         v_samples: 80,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Torus",
@@ -174,11 +209,14 @@ fn fy(u: float, v: float) -> float { math.sin(v) }
 fn fz(u: float, v: float) -> float { (2.0 + math.cos(v)) * math.sin(u) }"#,
         x_range: (-1.0, 1.0),
         y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 1.0),
         u_range: (0.0, 6.28318),
         v_range: (0.0, 6.28318),
         u_samples: 60,
         v_samples: 40,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
     MathExample {
         name: "Möbius Strip",
@@ -195,10 +233,327 @@ fn fz(u: float, v: float) -> float {
 }"#,
         x_range: (-1.0, 1.0),
         y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
         t_range: (0.0, 1.0),
         u_range: (0.0, 6.28318),
         v_range: (-0.5, 0.5),
         u_samples: 80,
         v_samples: 20,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Sphere",
+        description: "Unit sphere, the iso-level grows it",
+        function_type: MathFunctionKind::Implicit,
+        code: r#"fn f(x: float, y: float, z: float) -> float {
+    x*x + y*y + z*z - 1.0
+}"#,
+        x_range: (-1.5, 1.5),
+        y_range: (-1.5, 1.5),
+        z_range: (-1.5, 1.5),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Gyroid",
+        description: "Triply periodic minimal surface",
+        function_type: MathFunctionKind::Implicit,
+        code: r#"fn f(x: float, y: float, z: float) -> float {
+    math.sin(x) * math.cos(y) + math.sin(y) * math.cos(z) + math.sin(z) * math.cos(x)
+}"#,
+        x_range: (-6.28, 6.28),
+        y_range: (-6.28, 6.28),
+        z_range: (-6.28, 6.28),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Implicit Torus",
+        description: "Donut as a level set",
+        function_type: MathFunctionKind::Implicit,
+        code: r#"fn f(x: float, y: float, z: float) -> float {
+    let q = math.sqrt(x*x + z*z) - 2.0
+    q*q + y*y - 0.5
+}"#,
+        x_range: (-3.0, 3.0),
+        y_range: (-1.5, 1.5),
+        z_range: (-3.0, 3.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Metaballs",
+        description: "Three blobs that merge as the iso-level rises",
+        function_type: MathFunctionKind::Implicit,
+        code: r#"fn ball(x: float, y: float, z: float) -> float {
+    1.0 / (x*x + y*y + z*z + 0.01)
+}
+fn f(x: float, y: float, z: float) -> float {
+    1.0 - ball(x - 1.0, y, z) - ball(x + 1.0, y, z) - ball(x, y - 1.2, z)
+}"#,
+        x_range: (-2.5, 2.5),
+        y_range: (-2.5, 2.5),
+        z_range: (-2.5, 2.5),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Lorenz",
+        description: "σ = 10, ρ = 28, β = 8/3",
+        function_type: MathFunctionKind::Ode,
+        code: r#"fn dx(x: float, y: float, z: float, t: float) -> float { 10.0 * (y - x) }
+fn dy(x: float, y: float, z: float, t: float) -> float { x * (28.0 - z) - y }
+fn dz(x: float, y: float, z: float, t: float) -> float { x * y - 8.0 / 3.0 * z }"#,
+        x_range: (-1.0, 1.0),
+        y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 50.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.005,
+    },
+    MathExample {
+        name: "Rössler",
+        description: "a = 0.2, b = 0.2, c = 5.7",
+        function_type: MathFunctionKind::Ode,
+        code: r#"fn dx(x: float, y: float, z: float, t: float) -> float { -y - z }
+fn dy(x: float, y: float, z: float, t: float) -> float { x + 0.2 * y }
+fn dz(x: float, y: float, z: float, t: float) -> float { 0.2 + z * (x - 5.7) }"#,
+        x_range: (-1.0, 1.0),
+        y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 200.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 0.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Thomas",
+        description: "Cyclically symmetric, b = 0.208186",
+        function_type: MathFunctionKind::Ode,
+        code: r#"fn dx(x: float, y: float, z: float, t: float) -> float { math.sin(y) - 0.208186 * x }
+fn dy(x: float, y: float, z: float, t: float) -> float { math.sin(z) - 0.208186 * y }
+fn dz(x: float, y: float, z: float, t: float) -> float { math.sin(x) - 0.208186 * z }"#,
+        x_range: (-1.0, 1.0),
+        y_range: (-1.0, 1.0),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 1000.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.1, 1.1, -0.01],
+        step: 0.05,
+    },
+    MathExample {
+        name: "Vortex",
+        description: "Rotation about y with a slow upward drift",
+        function_type: MathFunctionKind::VectorField,
+        code: r#"fn vx(x: float, y: float, z: float) -> float { -z }
+fn vy(x: float, y: float, z: float) -> float { 0.2 }
+fn vz(x: float, y: float, z: float) -> float { x }"#,
+        x_range: (-2.0, 2.0),
+        y_range: (-2.0, 2.0),
+        z_range: (-2.0, 2.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "ABC Flow",
+        description: "Arnold-Beltrami-Childress, A = √3, B = √2, C = 1",
+        function_type: MathFunctionKind::VectorField,
+        code: r#"fn vx(x: float, y: float, z: float) -> float { 1.732 * math.sin(z) + math.cos(y) }
+fn vy(x: float, y: float, z: float) -> float { 1.414 * math.sin(x) + 1.732 * math.cos(z) }
+fn vz(x: float, y: float, z: float) -> float { math.sin(y) + 1.414 * math.cos(x) }"#,
+        x_range: (0.0, 6.28),
+        y_range: (0.0, 6.28),
+        z_range: (0.0, 6.28),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Lorenz Flow",
+        description: "The velocity field the Lorenz attractor follows",
+        function_type: MathFunctionKind::VectorField,
+        code: r#"fn vx(x: float, y: float, z: float) -> float { 10.0 * (y - x) }
+fn vy(x: float, y: float, z: float) -> float { x * (28.0 - z) - y }
+fn vz(x: float, y: float, z: float) -> float { x * y - 8.0 / 3.0 * z }"#,
+        x_range: (-20.0, 20.0),
+        y_range: (-25.0, 25.0),
+        z_range: (0.0, 50.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "z³ - 1",
+        description: "Three roots of unity",
+        function_type: MathFunctionKind::Complex,
+        code: r#"fn re(x: float, y: float) -> float { x*x*x - 3.0*x*y*y - 1.0 }
+fn im(x: float, y: float) -> float { 3.0*x*x*y - y*y*y }"#,
+        x_range: (-2.0, 2.0),
+        y_range: (-2.0, 2.0),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "(z² - 1) / (z² + 1)",
+        description: "Zeros at ±1, poles at ±i",
+        function_type: MathFunctionKind::Complex,
+        code: r#"fn re(x: float, y: float) -> float {
+    let u = x*x - y*y
+    let v = 2.0*x*y
+    (u*u - 1.0 + v*v) / ((u + 1.0)*(u + 1.0) + v*v)
+}
+fn im(x: float, y: float) -> float {
+    let u = x*x - y*y
+    let v = 2.0*x*y
+    2.0*v / ((u + 1.0)*(u + 1.0) + v*v)
+}"#,
+        x_range: (-2.5, 2.5),
+        y_range: (-2.5, 2.5),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "sin z",
+        description: "Zeros along the real axis, growing along the imaginary one",
+        function_type: MathFunctionKind::Complex,
+        code: r#"fn re(x: float, y: float) -> float { math.sin(x) * (math.exp(y) + math.exp(-y)) / 2.0 }
+fn im(x: float, y: float) -> float { math.cos(x) * (math.exp(y) - math.exp(-y)) / 2.0 }"#,
+        x_range: (-6.28, 6.28),
+        y_range: (-6.28, 6.28),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Mandelbrot",
+        description: "z² + c; the Julia option fixes c and iterates from each point instead",
+        function_type: MathFunctionKind::Fractal,
+        code: r#"fn next_re(zr: float, zi: float, cr: float, ci: float) -> float { zr*zr - zi*zi + cr }
+fn next_im(zr: float, zi: float, cr: float, ci: float) -> float { 2.0*zr*zi + ci }"#,
+        x_range: (-2.5, 1.5),
+        y_range: (-1.25, 1.25),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Burning Ship",
+        description: "Takes absolute values of the parts before squaring",
+        function_type: MathFunctionKind::Fractal,
+        code: r#"fn next_re(zr: float, zi: float, cr: float, ci: float) -> float { zr*zr - zi*zi + cr }
+fn next_im(zr: float, zi: float, cr: float, ci: float) -> float {
+    let p = 2.0*zr*zi
+    if p < 0.0 { ci + p } else { ci - p }
+}"#,
+        x_range: (-2.2, 1.4),
+        y_range: (-0.7, 1.7),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Tricorn",
+        description: "Squares the conjugate of z, with threefold symmetry",
+        function_type: MathFunctionKind::Fractal,
+        code: r#"fn next_re(zr: float, zi: float, cr: float, ci: float) -> float { zr*zr - zi*zi + cr }
+fn next_im(zr: float, zi: float, cr: float, ci: float) -> float { ci - 2.0*zr*zi }"#,
+        x_range: (-2.2, 1.6),
+        y_range: (-1.5, 1.5),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
+    },
+    MathExample {
+        name: "Cubic Mandelbrot",
+        description: "z³ + c, symmetric under a half turn",
+        function_type: MathFunctionKind::Fractal,
+        code: r#"fn next_re(zr: float, zi: float, cr: float, ci: float) -> float { zr*zr*zr - 3.0*zr*zi*zi + cr }
+fn next_im(zr: float, zi: float, cr: float, ci: float) -> float { 3.0*zr*zr*zi - zi*zi*zi + ci }"#,
+        x_range: (-1.5, 1.5),
+        y_range: (-1.5, 1.5),
+        z_range: (-1.0, 1.0),
+        t_range: (0.0, 1.0),
+        u_range: (0.0, 1.0),
+        v_range: (0.0, 1.0),
+        u_samples: 50,
+        v_samples: 50,
+        initial: [1.0, 1.0, 1.0],
+        step: 0.01,
     },
 ];
@@ -0,0 +1,265 @@
+use parking_lot::Mutex;
+use std::sync::Barrier;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+/// |z| past which an orbit counts as escaped. Large, so the smooth count is nearly exact.
+const ESCAPE_RADIUS: f64 = 256.0;
+
+/// Block size of each progressive pass, in pixels. The first pass computes one pixel per
+/// 16x16 block, each later pass only the pixels the previous ones skipped.
+const PASS_STEPS: [usize; 5] = [16, 8, 4, 2, 1];
+
+/// Rows per tile handed to a worker; a multiple of the coarsest step so blocks never
+/// straddle two tiles.
+const TILE_ROWS: usize = PASS_STEPS[0];
+
+/// The part of the complex plane shown on a `width` by `height` pixel grid, with
+/// `scale` the distance from `center` to the top edge.
+#[derive(Clone, Copy)]
+pub struct FractalView {
+    pub center: [f64; 2],
+    pub scale: f64,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl FractalView {
+    /// The complex number under a pixel position, with y going down the screen.
+    pub fn point(&self, pixel: [f64; 2]) -> [f64; 2] {
+        let unit = self.pixel_size();
+        [
+            self.center[0] + (pixel[0] - self.width as f64 / 2.0) * unit,
+            self.center[1] - (pixel[1] - self.height as f64 / 2.0) * unit,
+        ]
+    }
+
+    pub fn pixel_size(&self) -> f64 {
+        2.0 * self.scale / self.height.max(1) as f64
+    }
+}
+
+/// Smooth escape counts, row by row from the top; negative where the orbit never escaped.
+pub struct FractalImage {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f32>,
+}
+
+/// Iterates `z -> iterate(z, c)` for every pixel, from `z = 0` with `c` at the pixel, or
+/// from the pixel with `c = julia` for a Julia set. One worker per core builds its own
+/// iteration from `make_iterate` and then shares the tiles of rows of every pass with the
+/// others. `emit` gets the image after every pass, with `true` for the last one.
+/// Stops early, without emitting, once `cancelled` returns true.
+pub fn render<M, I>(
+    view: &FractalView,
+    max_iterations: u32,
+    julia: Option<[f64; 2]>,
+    make_iterate: M,
+    cancelled: &(dyn Fn() -> bool + Sync),
+    mut emit: impl FnMut(FractalImage, bool),
+) -> Result<(), String>
+where
+    M: Fn() -> Result<I, String> + Sync,
+    I: FnMut([f64; 2], [f64; 2]) -> Result<[f64; 2], String>,
+{
+    let workers = thread::available_parallelism().map_or(4, |n| n.get());
+    let tiles: Vec<_> = vec![-1.0f32; view.width * view.height]
+        .chunks(TILE_ROWS * view.width.max(1))
+        .map(|rows| Mutex::new(rows.to_vec()))
+        .collect();
+    let next_tile = AtomicUsize::new(0);
+    let error = Mutex::new(None);
+    let stopped = AtomicBool::new(false);
+    // Every pass ends with two waits: one once all tiles are done, one once the image
+    // is copied and the next pass may start.
+    let barrier = Barrier::new(workers + 1);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                let mut iterate = match make_iterate() {
+                    Ok(iterate) => Some(iterate),
+                    Err(e) => {
+                        error.lock().get_or_insert(e);
+                        None
+                    }
+                };
+                for (pass, &step) in PASS_STEPS.iter().enumerate() {
+                    while let Some(iterate) = iterate.as_mut() {
+                        if cancelled() || error.lock().is_some() {
+                            break;
+                        }
+                        let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                        let Some(rows) = tiles.get(index) else {
+                            break;
+                        };
+                        let tile = Tile {
+                            top: index * TILE_ROWS,
+                            step,
+                            refine: pass > 0,
+                        };
+                        if let Err(e) = tile.render(&mut rows.lock(), view, max_iterations, julia, iterate) {
+                            error.lock().get_or_insert(e);
+                        }
+                    }
+                    barrier.wait();
+                    barrier.wait();
+                    if stopped.load(Ordering::Relaxed) {
+                        return;
+                    }
+                }
+            });
+        }
+
+        for &step in &PASS_STEPS {
+            barrier.wait();
+            let stop = cancelled() || error.lock().is_some();
+            let image = (!stop).then(|| {
+                let mut values = Vec::with_capacity(view.width * view.height);
+                for rows in &tiles {
+                    values.extend_from_slice(&rows.lock());
+                }
+                FractalImage {
+                    width: view.width,
+                    height: view.height,
+                    values,
+                }
+            });
+            next_tile.store(0, Ordering::Relaxed);
+            stopped.store(stop, Ordering::Relaxed);
+            barrier.wait();
+
+            match image {
+                Some(image) => emit(image, step == 1),
+                None => break,
+            }
+        }
+    });
+
+    match error.into_inner() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+struct Tile {
+    top: usize,
+    step: usize,
+    /// Skip the pixels an earlier, coarser pass already computed.
+    refine: bool,
+}
+
+impl Tile {
+    /// Computes one pixel per `step`-sized block of `rows` and fills the block with it.
+    fn render<I>(
+        &self,
+        rows: &mut [f32],
+        view: &FractalView,
+        max_iterations: u32,
+        julia: Option<[f64; 2]>,
+        iterate: &mut I,
+    ) -> Result<(), String>
+    where
+        I: FnMut([f64; 2], [f64; 2]) -> Result<[f64; 2], String>,
+    {
+        let width = view.width;
+        let coarse = 2 * self.step;
+
+        for dy in (0..rows.len() / width).step_by(self.step) {
+            for x in (0..width).step_by(self.step) {
+                if self.refine && x % coarse == 0 && dy % coarse == 0 {
+                    continue;
+                }
+
+                let pixel = view.point([x as f64 + 0.5, (self.top + dy) as f64 + 0.5]);
+                let value = match julia {
+                    Some(c) => escape_time(iterate, pixel, c, max_iterations)?,
+                    None => escape_time(iterate, [0.0, 0.0], pixel, max_iterations)?,
+                };
+
+                let block = x..(x + self.step).min(width);
+                for row in rows[dy * width..].chunks_mut(width).take(self.step) {
+                    row[block.clone()].fill(value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Normalized iteration count `n + 1 - log2(ln |z|)`, continuous across the bands of
+/// integer counts for quadratic iterations; -1 if `z` is still bounded after `max_iterations`.
+fn escape_time<I>(iterate: &mut I, mut z: [f64; 2], c: [f64; 2], max_iterations: u32) -> Result<f32, String>
+where
+    I: FnMut([f64; 2], [f64; 2]) -> Result<[f64; 2], String>,
+{
+    for n in 0..max_iterations {
+        let r2 = z[0] * z[0] + z[1] * z[1];
+        if !r2.is_finite() {
+            return Ok(n as f32);
+        }
+        if r2 > ESCAPE_RADIUS * ESCAPE_RADIUS {
+            let smooth = n as f64 + 1.0 - (r2.ln() / 2.0).log2();
+            return Ok(smooth.max(0.0) as f32);
+        }
+        z = iterate(z, c)?;
+    }
+    Ok(-1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(z: [f64; 2], c: [f64; 2]) -> Result<[f64; 2], String> {
+        Ok([z[0] * z[0] - z[1] * z[1] + c[0], 2.0 * z[0] * z[1] + c[1]])
+    }
+
+    #[test]
+    fn render_builds_one_iteration_per_worker_and_fills_every_pixel() {
+        let view = FractalView {
+            center: [-0.5, 0.0],
+            scale: 1.5,
+            width: 70,
+            height: 37,
+        };
+        let built = AtomicUsize::new(0);
+        let make_iterate = || {
+            built.fetch_add(1, Ordering::Relaxed);
+            Ok(square)
+        };
+        let mut passes = Vec::new();
+        render(&view, 64, None, make_iterate, &|| false, |image, last| passes.push((image, last))).unwrap();
+
+        let workers = thread::available_parallelism().map_or(4, |n| n.get());
+        assert_eq!(built.load(Ordering::Relaxed), workers);
+        assert_eq!(passes.len(), PASS_STEPS.len());
+        assert!(passes[..PASS_STEPS.len() - 1].iter().all(|(_, last)| !last));
+
+        let (image, last) = passes.last().unwrap();
+        assert!(last);
+        for y in 0..view.height {
+            for x in 0..view.width {
+                let c = view.point([x as f64 + 0.5, y as f64 + 0.5]);
+                let expected = escape_time(&mut square, [0.0, 0.0], c, 64).unwrap();
+                assert_eq!(image.values[y * view.width + x], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn render_reports_a_failed_build_without_emitting() {
+        let view = FractalView {
+            center: [0.0, 0.0],
+            scale: 1.0,
+            width: 8,
+            height: 8,
+        };
+        let make_iterate = || Err::<fn([f64; 2], [f64; 2]) -> Result<[f64; 2], String>, _>("bad".to_string());
+        let mut emitted = 0;
+        let result = render(&view, 16, None, make_iterate, &|| false, |_, _| emitted += 1);
+        assert_eq!(result, Err("bad".to_string()));
+        assert_eq!(emitted, 0);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::math::mesh::{SurfaceMesh, TriangleMesh};
+
+/// World units per function unit, as for parametric surfaces.
+const SCALE: f64 = 50.0;
+
+/// Corner pairs of a cell's 12 edges; corner `c` sits at `(c & 1, c >> 1 & 1, c >> 2 & 1)`.
+const CELL_EDGES: [(usize, usize); 12] = [
+    (0, 1), (2, 3), (4, 5), (6, 7),
+    (0, 2), (1, 3), (4, 6), (5, 7),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+];
+
+/// `f(x, y, z)` sampled on an `n`^3 lattice, x outermost.
+pub struct SampledField {
+    pub n: usize,
+    pub ranges: [(f64, f64); 3],
+    pub values: Vec<f64>,
+}
+
+impl SampledField {
+    fn at(&self, i: usize, j: usize, k: usize) -> f64 {
+        self.values[(i * self.n + j) * self.n + k]
+    }
+
+    fn step(&self, axis: usize) -> f64 {
+        (self.ranges[axis].1 - self.ranges[axis].0) / (self.n - 1) as f64
+    }
+}
+
+/// Extracts `f = iso` with surface nets, the averaging form of dual contouring: one vertex
+/// per cell the surface crosses, at the mean of its edge crossings, and one quad per crossed
+/// lattice edge. Normals are the gradient of the trilinear interpolant at each vertex.
+pub fn extract_isosurface(field: &SampledField, iso: f64) -> SurfaceMesh {
+    let n = field.n;
+    let cells = n - 1;
+    let steps = [field.step(0), field.step(1), field.step(2)];
+    let inside = |v: f64| v < 0.0;
+
+    let mut cell_vertex = vec![u32::MAX; cells * cells * cells];
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut height = (f32::MAX, f32::MIN);
+
+    for i in 0..cells {
+        for j in 0..cells {
+            for k in 0..cells {
+                let corners: [f64; 8] = std::array::from_fn(|c| {
+                    field.at(i + (c & 1), j + (c >> 1 & 1), k + (c >> 2 & 1)) - iso
+                });
+                let mask = corners.iter().fold(0u8, |m, &v| (m << 1) | inside(v) as u8);
+                if mask == 0 || mask == u8::MAX {
+                    continue;
+                }
+
+                let mut sum = [0.0; 3];
+                let mut crossings = 0;
+                for &(a, b) in &CELL_EDGES {
+                    let (va, vb) = (corners[a], corners[b]);
+                    if inside(va) == inside(vb) {
+                        continue;
+                    }
+                    let t = va / (va - vb);
+                    for (axis, s) in sum.iter_mut().enumerate() {
+                        let pa = (a >> axis & 1) as f64;
+                        let pb = (b >> axis & 1) as f64;
+                        *s += pa + t * (pb - pa);
+                    }
+                    crossings += 1;
+                }
+                let local = sum.map(|s| s / crossings as f64);
+                let gradient = trilinear_gradient(&corners, local, steps);
+
+                let position: [f64; 3] = std::array::from_fn(|axis| {
+                    let cell = [i, j, k][axis] as f64;
+                    field.ranges[axis].0 + (cell + local[axis]) * steps[axis]
+                });
+                let len = gradient.iter().map(|g| g * g).sum::<f64>().sqrt().max(1e-12);
+
+                cell_vertex[(i * cells + j) * cells + k] = (vertices.len() / 3) as u32;
+                vertices.extend(position.map(|p| (p * SCALE) as f32));
+                normals.extend(gradient.map(|g| (g / len) as f32));
+
+                let y = (position[1] * SCALE) as f32;
+                height = (height.0.min(y), height.1.max(y));
+            }
+        }
+    }
+
+    let mut indices = Vec::new();
+    for axis in 0..3 {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        for a in 0..cells {
+            for b in 1..cells {
+                for c in 1..cells {
+                    let mut p = [0; 3];
+                    p[axis] = a;
+                    p[u] = b;
+                    p[v] = c;
+                    let mut q = p;
+                    q[axis] += 1;
+
+                    let start = inside(field.at(p[0], p[1], p[2]) - iso);
+                    if start == inside(field.at(q[0], q[1], q[2]) - iso) {
+                        continue;
+                    }
+
+                    // The four cells around the edge, going round it.
+                    let quad = [(1, 1), (0, 1), (0, 0), (1, 0)].map(|(du, dv)| {
+                        let mut cell = p;
+                        cell[u] -= du;
+                        cell[v] -= dv;
+                        cell_vertex[(cell[0] * cells + cell[1]) * cells + cell[2]]
+                    });
+                    let [q0, q1, q2, q3] = if start { quad } else { [quad[3], quad[2], quad[1], quad[0]] };
+                    indices.extend([q0, q1, q2, q0, q2, q3]);
+                }
+            }
+        }
+    }
+
+    if vertices.is_empty() {
+        height = (0.0, 1.0);
+    }
+
+    SurfaceMesh {
+        mesh: TriangleMesh {
+            vertices,
+            normals,
+            indices,
+        },
+        z_min: height.0,
+        z_max: height.1,
+        colors: Vec::new(),
+    }
+}
+
+/// Gradient of the trilinear interpolation of a cell's corners at `local` in `[0, 1]^3`.
+fn trilinear_gradient(corners: &[f64; 8], local: [f64; 3], steps: [f64; 3]) -> [f64; 3] {
+    std::array::from_fn(|axis| {
+        let mut derivative = 0.0;
+        for c in 0..8 {
+            if c >> axis & 1 == 1 {
+                continue;
+            }
+            let weight: f64 = (0..3)
+                .filter(|&other| other != axis)
+                .map(|other| if c >> other & 1 == 1 { local[other] } else { 1.0 - local[other] })
+                .product();
+            derivative += weight * (corners[c | 1 << axis] - corners[c]);
+        }
+        derivative / steps[axis]
+    })
+}
@@ -8,6 +8,8 @@ pub struct SurfaceMesh {
     pub mesh: TriangleMesh,
     pub z_min: f32,
     pub z_max: f32,
+    /// RGB per vertex, used instead of the height colouring when not empty.
+    pub colors: Vec<f32>,
 }
 
 pub struct ParametricSurfaceMesh {
@@ -17,3 +19,17 @@ pub struct ParametricSurfaceMesh {
 pub struct CurveMesh {
     pub vertices: Vec<f32>,
 }
+
+/// Arrows are `[x, y, z, dx, dy, dz, length, magnitude]` per instance, with a unit direction.
+/// Streamlines are line segments of `[x, y, z, magnitude]` vertices. Magnitudes are
+/// normalized to `[0, 1]` by the largest arrow.
+pub struct VectorFieldMesh {
+    pub arrows: Vec<f32>,
+    pub streamlines: Vec<f32>,
+}
+
+/// An integrated trajectory, with each point's time normalized to `[0, 1]`.
+pub struct TrajectoryMesh {
+    pub vertices: Vec<f32>,
+    pub times: Vec<f32>,
+}
@@ -1,6 +1,11 @@
+pub mod complex;
 pub mod engine;
 pub mod examples;
+pub mod fractal;
+pub mod implicit;
 pub mod mesh;
+pub mod ode;
+pub mod vector_field;
 
 pub use engine::{MathEngine, MathResult};
 pub use examples::MATH_EXAMPLES;
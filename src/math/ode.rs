@@ -0,0 +1,163 @@
+/// Longest trajectory kept; integration stops there.
+pub const MAX_TRAJECTORY_POINTS: usize = 1_000_000;
+
+/// Per-step error allowed by RK45, relative to the size of the state.
+const TOLERANCE: f64 = 1e-6;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OdeMethod {
+    Rk4,
+    Rk45,
+}
+
+type State = [f64; 3];
+
+/// Integrates `s' = deriv(t, s)` from `t0` for `duration`, returning every `(t, s)` visited.
+/// RK4 takes fixed steps of `step`; RK45 (Dormand-Prince) adapts its step and uses `step`
+/// as the largest one. Stops early if the state blows up.
+pub fn integrate<F>(
+    mut deriv: F,
+    initial: State,
+    t0: f64,
+    duration: f64,
+    step: f64,
+    method: OdeMethod,
+) -> Result<Vec<(f64, State)>, String>
+where
+    F: FnMut(f64, State) -> Result<State, String>,
+{
+    if step <= 0.0 || duration <= 0.0 {
+        return Err("Step and duration must be positive".to_string());
+    }
+
+    let end = t0 + duration;
+    let mut t = t0;
+    let mut state = initial;
+    let mut trajectory = vec![(t, state)];
+    let mut h = step;
+
+    while t < end && trajectory.len() < MAX_TRAJECTORY_POINTS {
+        h = h.min(end - t);
+        let (next, error) = match method {
+            OdeMethod::Rk4 => (rk4_step(&mut deriv, t, state, h)?, 0.0),
+            OdeMethod::Rk45 => dormand_prince_step(&mut deriv, t, state, h)?,
+        };
+
+        if error > 1.0 {
+            h *= (0.9 * error.powf(-0.2)).max(0.2);
+            if h < duration * 1e-12 {
+                return Err(format!("Step size underflow at t = {}", t));
+            }
+            continue;
+        }
+
+        if next.iter().any(|v| !v.is_finite()) {
+            break;
+        }
+        t += h;
+        state = next;
+        trajectory.push((t, state));
+
+        if method == OdeMethod::Rk45 {
+            let growth = if error > 0.0 { 0.9 * error.powf(-0.2) } else { 5.0 };
+            h = (h * growth.min(5.0)).min(step);
+        }
+    }
+
+    Ok(trajectory)
+}
+
+fn offset(state: State, h: f64, terms: &[(f64, &State)]) -> State {
+    std::array::from_fn(|i| state[i] + h * terms.iter().map(|(w, k)| w * k[i]).sum::<f64>())
+}
+
+pub fn rk4_step<F>(deriv: &mut F, t: f64, s: State, h: f64) -> Result<State, String>
+where
+    F: FnMut(f64, State) -> Result<State, String>,
+{
+    let k1 = deriv(t, s)?;
+    let k2 = deriv(t + h / 2.0, offset(s, h, &[(0.5, &k1)]))?;
+    let k3 = deriv(t + h / 2.0, offset(s, h, &[(0.5, &k2)]))?;
+    let k4 = deriv(t + h, offset(s, h, &[(1.0, &k3)]))?;
+    Ok(offset(
+        s,
+        h,
+        &[(1.0 / 6.0, &k1), (1.0 / 3.0, &k2), (1.0 / 3.0, &k3), (1.0 / 6.0, &k4)],
+    ))
+}
+
+/// One Dormand-Prince 5(4) step: the 5th order result and its error estimate, scaled so
+/// that 1.0 is the tolerance.
+fn dormand_prince_step<F>(deriv: &mut F, t: f64, s: State, h: f64) -> Result<(State, f64), String>
+where
+    F: FnMut(f64, State) -> Result<State, String>,
+{
+    let k1 = deriv(t, s)?;
+    let k2 = deriv(t + h / 5.0, offset(s, h, &[(1.0 / 5.0, &k1)]))?;
+    let k3 = deriv(
+        t + h * 3.0 / 10.0,
+        offset(s, h, &[(3.0 / 40.0, &k1), (9.0 / 40.0, &k2)]),
+    )?;
+    let k4 = deriv(
+        t + h * 4.0 / 5.0,
+        offset(s, h, &[(44.0 / 45.0, &k1), (-56.0 / 15.0, &k2), (32.0 / 9.0, &k3)]),
+    )?;
+    let k5 = deriv(
+        t + h * 8.0 / 9.0,
+        offset(
+            s,
+            h,
+            &[
+                (19372.0 / 6561.0, &k1),
+                (-25360.0 / 2187.0, &k2),
+                (64448.0 / 6561.0, &k3),
+                (-212.0 / 729.0, &k4),
+            ],
+        ),
+    )?;
+    let k6 = deriv(
+        t + h,
+        offset(
+            s,
+            h,
+            &[
+                (9017.0 / 3168.0, &k1),
+                (-355.0 / 33.0, &k2),
+                (46732.0 / 5247.0, &k3),
+                (49.0 / 176.0, &k4),
+                (-5103.0 / 18656.0, &k5),
+            ],
+        ),
+    )?;
+    let next = offset(
+        s,
+        h,
+        &[
+            (35.0 / 384.0, &k1),
+            (500.0 / 1113.0, &k3),
+            (125.0 / 192.0, &k4),
+            (-2187.0 / 6784.0, &k5),
+            (11.0 / 84.0, &k6),
+        ],
+    );
+    let k7 = deriv(t + h, next)?;
+
+    // Difference between the 5th and embedded 4th order weights.
+    let error = offset(
+        [0.0; 3],
+        h,
+        &[
+            (71.0 / 57600.0, &k1),
+            (-71.0 / 16695.0, &k3),
+            (71.0 / 1920.0, &k4),
+            (-17253.0 / 339200.0, &k5),
+            (22.0 / 525.0, &k6),
+            (-1.0 / 40.0, &k7),
+        ],
+    );
+    let scaled = (0..3)
+        .map(|i| error[i].abs() / (TOLERANCE * (1.0 + s[i].abs().max(next[i].abs()))))
+        .fold(0.0, f64::max);
+
+    Ok((next, if scaled.is_nan() { f64::INFINITY } else { scaled }))
+}
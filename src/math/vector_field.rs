@@ -0,0 +1,131 @@
+use crate::math::mesh::VectorFieldMesh;
+use crate::math::ode::rk4_step;
+
+/// Longest streamline traced on each side of its seed, in steps.
+const MAX_STREAMLINE_STEPS: usize = 1000;
+
+/// A square grid of `count`^2 seeds on the plane normal to `axis`, `offset` of the way
+/// (0 to 1) along that axis's range.
+pub struct StreamlineSeeds {
+    pub axis: usize,
+    pub offset: f64,
+    pub count: usize,
+}
+
+/// Samples `field` at the centres of a `resolution`^3 grid of arrows, then traces a
+/// streamline through each seed, forward and backward, until it leaves the box or stalls.
+/// The box is fitted into the view, like an ODE trajectory.
+pub fn sample_vector_field<F>(
+    mut field: F,
+    ranges: [(f64, f64); 3],
+    resolution: usize,
+    seeds: Option<&StreamlineSeeds>,
+) -> Result<VectorFieldMesh, String>
+where
+    F: FnMut([f64; 3]) -> Result<[f64; 3], String>,
+{
+    let cell = ranges.map(|(min, max)| (max - min) / resolution as f64);
+    let scale = 200.0 / ranges.iter().map(|(min, max)| max - min).fold(0.001, f64::max);
+    let to_world = |p: &[f64; 3]| -> [f32; 3] {
+        std::array::from_fn(|i| ((p[i] - (ranges[i].0 + ranges[i].1) / 2.0) * scale) as f32)
+    };
+
+    let mut samples = Vec::with_capacity(resolution * resolution * resolution);
+    for i in 0..resolution {
+        for j in 0..resolution {
+            for k in 0..resolution {
+                let p = [i, j, k].map(|n| n as f64 + 0.5);
+                let p: [f64; 3] = std::array::from_fn(|axis| ranges[axis].0 + p[axis] * cell[axis]);
+                samples.push((p, field(p)?));
+            }
+        }
+    }
+
+    let max_magnitude = samples
+        .iter()
+        .map(|(_, v)| magnitude(v))
+        .filter(|m| m.is_finite())
+        .fold(f64::MIN_POSITIVE, f64::max);
+    let normalized = |m: f64| (m / max_magnitude).clamp(0.0, 1.0) as f32;
+
+    let arrow_length = cell.iter().fold(f64::MAX, |a, &b| a.min(b)) * scale;
+    let mut arrows = Vec::with_capacity(samples.len() * 8);
+    for (p, v) in &samples {
+        let m = magnitude(v);
+        if m == 0.0 || !m.is_finite() {
+            continue;
+        }
+        let t = normalized(m);
+        arrows.extend(to_world(p));
+        arrows.extend(v.map(|c| (c / m) as f32));
+        arrows.push(arrow_length as f32 * (0.2 + 0.7 * t));
+        arrows.push(t);
+    }
+
+    let mut streamlines = Vec::new();
+    if let Some(seeds) = seeds {
+        let step = cell.iter().fold(f64::MAX, |a, &b| a.min(b)) * 0.5;
+        let (u, v) = ((seeds.axis + 1) % 3, (seeds.axis + 2) % 3);
+
+        for a in 0..seeds.count {
+            for b in 0..seeds.count {
+                let mut seed = [0.0; 3];
+                seed[seeds.axis] = ranges[seeds.axis].0
+                    + seeds.offset * (ranges[seeds.axis].1 - ranges[seeds.axis].0);
+                seed[u] = ranges[u].0 + (a as f64 + 0.5) / seeds.count as f64 * (ranges[u].1 - ranges[u].0);
+                seed[v] = ranges[v].0 + (b as f64 + 0.5) / seeds.count as f64 * (ranges[v].1 - ranges[v].0);
+
+                for direction in [1.0, -1.0] {
+                    let line = trace(&mut field, seed, ranges, step * direction)?;
+                    for pair in line.windows(2) {
+                        for (p, m) in pair {
+                            streamlines.extend(to_world(p));
+                            streamlines.push(normalized(*m));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(VectorFieldMesh {
+        arrows,
+        streamlines,
+    })
+}
+
+/// Follows the field's direction with RK4 in steps of `step` arc length, returning each
+/// point and the field's magnitude there.
+fn trace<F>(
+    field: &mut F,
+    seed: [f64; 3],
+    ranges: [(f64, f64); 3],
+    step: f64,
+) -> Result<Vec<([f64; 3], f64)>, String>
+where
+    F: FnMut([f64; 3]) -> Result<[f64; 3], String>,
+{
+    let inside = |p: &[f64; 3]| (0..3).all(|i| p[i] >= ranges[i].0 && p[i] <= ranges[i].1);
+
+    let mut line = Vec::new();
+    let mut p = seed;
+    while line.len() < MAX_STREAMLINE_STEPS && inside(&p) {
+        let m = magnitude(&field(p)?);
+        if m < 1e-12 || !m.is_finite() {
+            break;
+        }
+        line.push((p, m));
+
+        let mut direction = |_: f64, q: [f64; 3]| {
+            let v = field(q)?;
+            let m = magnitude(&v);
+            Ok(if m > 1e-12 { v.map(|c| c / m) } else { [0.0; 3] })
+        };
+        p = rk4_step(&mut direction, 0.0, p, step)?;
+    }
+    Ok(line)
+}
+
+fn magnitude(v: &[f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
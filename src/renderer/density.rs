@@ -0,0 +1,617 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::renderer::point_cloud::{Colormap, PointCloudBuffers, keyed_point_layout};
+
+/// Counts exactly up to 2^24 hits per pixel.
+const DENSITY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+const HISTOGRAM_BINS: usize = 256;
+const WORKGROUP_SIZE: u32 = 16;
+const SPLAT_WORKGROUP_SIZE: u32 = 256;
+
+/// Whether points can be blended straight into the R32Float target (WebGPU's
+/// `float32-blendable`), which wgpu reports as an adapter-specific format feature.
+pub fn float32_blendable(adapter: &wgpu::Adapter) -> bool {
+    adapter.features().contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        && adapter
+            .get_texture_format_features(DENSITY_FORMAT)
+            .flags
+            .contains(wgpu::TextureFormatFeatureFlags::BLENDABLE)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ToneMap {
+    /// log(1 + hits), scaled by the densest pixel.
+    Log,
+    /// Histogram equalization of the log density, so every colour covers the same area.
+    Equalize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct DensityUniform {
+    tone_map: u32,
+    colormap: u32,
+    _padding: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct SplatUniform {
+    /// x, y, width, height in pixels.
+    viewport: [f32; 4],
+    size: [u32; 2],
+    points: u32,
+    _padding: u32,
+}
+
+/// How points reach the density texture.
+enum Accumulate {
+    /// Additive blending of the point list into the target.
+    Blend(wgpu::RenderPipeline),
+    Splat(Box<Splat>),
+}
+
+/// Fallback without float blending: a compute pass projects the points and counts them in
+/// atomic cells, which a second pass copies into the density texture.
+struct Splat {
+    layout: wgpu::BindGroupLayout,
+    counts_buffer: wgpu::Buffer,
+    /// One per generator, so A and B can be splatted in the same submission.
+    uniform_buffers: [wgpu::Buffer; 2],
+    pipeline_splat: wgpu::ComputePipeline,
+    pipeline_resolve: wgpu::ComputePipeline,
+}
+
+/// `DensityStats` in the shader: max hits, covered pixels, padding, then the histogram and its CDF.
+pub const STATS_SIZE: u64 = ((4 + 2 * HISTOGRAM_BINS) * 4) as u64;
+
+/// Additive hit counts in a float target, then a tone-map pass onto the surface.
+pub struct DensityTarget {
+    accumulate: Accumulate,
+    view: wgpu::TextureView,
+    size: [u32; 2],
+    stats_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+
+    compute_layout: wgpu::BindGroupLayout,
+    tonemap_layout: wgpu::BindGroupLayout,
+    compute_bind_group: wgpu::BindGroup,
+    tonemap_bind_group: wgpu::BindGroup,
+
+    pipeline_max: wgpu::ComputePipeline,
+    pipeline_histogram: wgpu::ComputePipeline,
+    pipeline_cdf: wgpu::ComputePipeline,
+    pipeline_tonemap: wgpu::RenderPipeline,
+}
+
+impl DensityTarget {
+    pub fn new(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        config: &wgpu::SurfaceConfiguration,
+        camera_layout: &wgpu::BindGroupLayout,
+        blendable: bool,
+    ) -> Self {
+        let stats_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Density Stats Buffer"),
+            size: STATS_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Density Uniform Buffer"),
+            size: std::mem::size_of::<DensityUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let texture_entry = |visibility| wgpu::BindGroupLayoutEntry {
+            binding: 3,
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let buffer_entry = |binding, visibility, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let compute_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Density Compute Bind Group Layout"),
+            entries: &[
+                texture_entry(wgpu::ShaderStages::COMPUTE),
+                buffer_entry(
+                    4,
+                    wgpu::ShaderStages::COMPUTE,
+                    wgpu::BufferBindingType::Storage { read_only: false },
+                ),
+            ],
+        });
+
+        let tonemap_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Density Tonemap Bind Group Layout"),
+            entries: &[
+                texture_entry(wgpu::ShaderStages::FRAGMENT),
+                buffer_entry(
+                    5,
+                    wgpu::ShaderStages::FRAGMENT,
+                    wgpu::BufferBindingType::Storage { read_only: true },
+                ),
+                buffer_entry(6, wgpu::ShaderStages::FRAGMENT, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+
+        let size = [config.width.max(1), config.height.max(1)];
+        let accumulate = if blendable {
+            Accumulate::Blend(Self::create_blend_pipeline(device, shader, camera_layout))
+        } else {
+            Accumulate::Splat(Box::new(Splat::new(device, shader, size)))
+        };
+
+        let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Density Compute Pipeline Layout"),
+            bind_group_layouts: &[&compute_layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&compute_pipeline_layout),
+                module: shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let pipeline_max = compute_pipeline("Density Max Pipeline", "cs_density_max");
+        let pipeline_histogram = compute_pipeline("Density Histogram Pipeline", "cs_density_histogram");
+        let pipeline_cdf = compute_pipeline("Density CDF Pipeline", "cs_density_cdf");
+
+        let tonemap_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Density Tonemap Pipeline Layout"),
+            bind_group_layouts: &[&tonemap_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline_tonemap = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Density Tonemap Pipeline"),
+            layout: Some(&tonemap_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_fullscreen_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_tonemap_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // Shares its pass with the overlay, which depth tests against a cleared buffer.
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let view = Self::create_view(device, size, blendable);
+        let (compute_bind_group, tonemap_bind_group) = Self::create_bind_groups(
+            device,
+            &compute_layout,
+            &tonemap_layout,
+            &view,
+            &stats_buffer,
+            &uniform_buffer,
+        );
+
+        Self {
+            accumulate,
+            view,
+            size,
+            stats_buffer,
+            uniform_buffer,
+            compute_layout,
+            tonemap_layout,
+            compute_bind_group,
+            tonemap_bind_group,
+            pipeline_max,
+            pipeline_histogram,
+            pipeline_cdf,
+            pipeline_tonemap,
+        }
+    }
+
+    fn create_blend_pipeline(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        camera_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+        let density_target = [Some(wgpu::ColorTargetState {
+            format: DENSITY_FORMAT,
+            blend: Some(additive),
+            write_mask: wgpu::ColorWrites::RED,
+        })];
+
+        let layout_3d = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Density 3D Pipeline Layout"),
+            bind_group_layouts: &[camera_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Density 3D Pipeline"),
+            layout: Some(&layout_3d),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_density_main"),
+                buffers: &[keyed_point_layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_density_main"),
+                targets: &density_target,
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::PointList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn create_view(device: &wgpu::Device, size: [u32; 2], blendable: bool) -> wgpu::TextureView {
+        let written_by = if blendable {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        } else {
+            wgpu::TextureUsages::STORAGE_BINDING
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Density Texture"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DENSITY_FORMAT,
+            usage: written_by | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_bind_groups(
+        device: &wgpu::Device,
+        compute_layout: &wgpu::BindGroupLayout,
+        tonemap_layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        stats_buffer: &wgpu::Buffer,
+        uniform_buffer: &wgpu::Buffer,
+    ) -> (wgpu::BindGroup, wgpu::BindGroup) {
+        let compute = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Density Compute Bind Group"),
+            layout: compute_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: stats_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let tonemap = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Density Tonemap Bind Group"),
+            layout: tonemap_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: stats_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        (compute, tonemap)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        self.size = [config.width.max(1), config.height.max(1)];
+        let blendable = matches!(self.accumulate, Accumulate::Blend(_));
+        self.view = Self::create_view(device, self.size, blendable);
+        if let Accumulate::Splat(splat) = &mut self.accumulate {
+            splat.counts_buffer = Splat::create_counts_buffer(device, self.size);
+        }
+        (self.compute_bind_group, self.tonemap_bind_group) = Self::create_bind_groups(
+            device,
+            &self.compute_layout,
+            &self.tonemap_layout,
+            &self.view,
+            &self.stats_buffer,
+            &self.uniform_buffer,
+        );
+    }
+
+    pub fn set_style(&self, queue: &wgpu::Queue, tone_map: ToneMap, colormap: Colormap) {
+        let uniform = DensityUniform {
+            tone_map: tone_map as u32,
+            colormap: colormap as u32,
+            _padding: [0; 2],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    /// Counts the points of each generator into the density target, each in its own
+    /// viewport when given, starting from zero.
+    pub fn accumulate<'a>(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        camera_buffer: &wgpu::Buffer,
+        camera_bind_group: &wgpu::BindGroup,
+        slots: impl Iterator<Item = (&'a PointCloudBuffers, Option<[f32; 4]>)>,
+    ) {
+        let splat = match &self.accumulate {
+            Accumulate::Blend(pipeline) => {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Density Accumulate Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &self.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_bind_group(0, camera_bind_group, &[]);
+                for (buffers, viewport) in slots {
+                    if let Some([x, y, w, h]) = viewport {
+                        render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+                    }
+                    render_pass.set_vertex_buffer(0, buffers.buffer_3d().slice(..));
+                    render_pass.draw(0..buffers.points_count_3d(), 0..1);
+                }
+                return;
+            }
+            Accumulate::Splat(splat) => splat,
+        };
+
+        encoder.clear_buffer(&splat.counts_buffer, 0, None);
+        let bind_groups: Vec<_> = slots
+            .zip(&splat.uniform_buffers)
+            .map(|((buffers, viewport), uniform_buffer)| {
+                let full = [0.0, 0.0, self.size[0] as f32, self.size[1] as f32];
+                let uniform = SplatUniform {
+                    viewport: viewport.unwrap_or(full),
+                    size: self.size,
+                    points: buffers.points_count_3d(),
+                    _padding: 0,
+                };
+                queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+                let bind_group = splat.create_bind_group(device, camera_buffer, buffers, uniform_buffer, &self.view);
+                (bind_group, uniform.points)
+            })
+            .collect();
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Density Splat Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&splat.pipeline_splat);
+        for (bind_group, points) in &bind_groups {
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.dispatch_workgroups(points.div_ceil(SPLAT_WORKGROUP_SIZE), 1, 1);
+        }
+        if let Some((bind_group, _)) = bind_groups.first() {
+            let groups = self.size.map(|n| n.div_ceil(WORKGROUP_SIZE));
+            pass.set_pipeline(&splat.pipeline_resolve);
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.dispatch_workgroups(groups[0], groups[1], 1);
+        }
+    }
+
+    /// Max, histogram and CDF of the accumulated hits, for the tone map.
+    pub fn compute_stats(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.clear_buffer(&self.stats_buffer, 0, None);
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Density Stats Pass"),
+            timestamp_writes: None,
+        });
+        let groups = self.size.map(|n| n.div_ceil(WORKGROUP_SIZE));
+        pass.set_bind_group(0, &self.compute_bind_group, &[]);
+        pass.set_pipeline(&self.pipeline_max);
+        pass.dispatch_workgroups(groups[0], groups[1], 1);
+        pass.set_pipeline(&self.pipeline_histogram);
+        pass.dispatch_workgroups(groups[0], groups[1], 1);
+        pass.set_pipeline(&self.pipeline_cdf);
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+
+    /// Draws the tone-mapped density as a fullscreen triangle.
+    pub fn draw_tonemap(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.pipeline_tonemap);
+        render_pass.set_bind_group(0, &self.tonemap_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+impl Splat {
+    fn new(device: &wgpu::Device, shader: &wgpu::ShaderModule, size: [u32; 2]) -> Self {
+        let compute = wgpu::ShaderStages::COMPUTE;
+        let buffer_entry = |binding, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: compute,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let storage = |read_only| wgpu::BufferBindingType::Storage { read_only };
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Density Splat Bind Group Layout"),
+            entries: &[
+                buffer_entry(0, wgpu::BufferBindingType::Uniform),
+                buffer_entry(13, storage(true)),
+                buffer_entry(14, storage(false)),
+                buffer_entry(15, wgpu::BufferBindingType::Uniform),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 16,
+                    visibility: compute,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: DENSITY_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Density Splat Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                module: shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let pipeline_splat = compute_pipeline("Density Splat Pipeline", "cs_density_splat");
+        let pipeline_resolve = compute_pipeline("Density Resolve Pipeline", "cs_density_resolve");
+
+        let uniform_buffers = std::array::from_fn(|_| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Density Splat Uniform Buffer"),
+                size: std::mem::size_of::<SplatUniform>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        Self {
+            layout,
+            counts_buffer: Self::create_counts_buffer(device, size),
+            uniform_buffers,
+            pipeline_splat,
+            pipeline_resolve,
+        }
+    }
+
+    fn create_counts_buffer(device: &wgpu::Device, size: [u32; 2]) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Density Counts Buffer"),
+            size: size[0] as u64 * size[1] as u64 * 4,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        camera_buffer: &wgpu::Buffer,
+        buffers: &PointCloudBuffers,
+        uniform_buffer: &wgpu::Buffer,
+        view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        // The point buffer can outgrow a storage binding; the live points fit in its start.
+        let points = buffers.buffer_3d();
+        let binding_size = points.size().min(device.limits().max_storage_buffer_binding_size as u64);
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Density Splat Bind Group"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 13,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: points,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(binding_size),
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 14,
+                    resource: self.counts_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 15,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 16,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+            ],
+        })
+    }
+}
@@ -1,25 +1,41 @@
-use crate::math::mesh::{CurveMesh, ParametricSurfaceMesh, SurfaceMesh};
+use wgpu::util::DeviceExt;
+
+use crate::math::mesh::{CurveMesh, ParametricSurfaceMesh, SurfaceMesh, TrajectoryMesh, VectorFieldMesh};
+use crate::math::ode::MAX_TRAJECTORY_POINTS;
 use crate::renderer::camera::{Camera, CameraUniform};
-use crate::renderer::point_cloud::{PointCloudBuffers, point_2d_layout, point_3d_layout};
+use crate::renderer::density::{DensityTarget, ToneMap, float32_blendable};
+use crate::renderer::fractal::FractalTarget;
+use crate::renderer::histogram::HistogramPipelines;
+use crate::renderer::point_cloud::{
+    Colormap, KeyScale, KeyedPoint, PointCloudBuffers, PointUniform, keyed_point_layout, point_3d_layout,
+};
 
 const MAX_SURFACE_VERTICES: usize = 500_000;
 const MAX_SURFACE_INDICES: usize = 1_000_000;
 const MAX_CURVE_VERTICES: usize = 10_000;
 const MAX_GRID_VERTICES: usize = 2000;
 const MAX_HEATMAP_VERTICES: usize = 500_000;
+const MAX_ARROWS: usize = 32 * 32 * 32;
+const MAX_STREAMLINE_VERTICES: usize = 1 << 20;
+/// Enough for a 64-bit raster of 256 rows, two triangles per cell.
+const MAX_BIT_PLOT_VERTICES: usize = 64 * 256 * 6;
+/// A plane crosses the cube in at most a hexagon, drawn as six segments.
+const MAX_OVERLAY_VERTICES: usize = 1024 * 12;
 
 #[repr(C)]
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct SurfaceUniforms {
     pub z_min: f32,
     pub z_max: f32,
-    pub _pad1: f32,
+    /// 1 to colour by the per-vertex colour buffer instead of height.
+    pub vertex_colors: u32,
     pub _pad2: f32,
 }
 
 pub struct MathBuffers {
     pub surface_vertex_buffer: wgpu::Buffer,
     pub surface_normal_buffer: wgpu::Buffer,
+    pub surface_color_buffer: wgpu::Buffer,
     pub surface_index_buffer: wgpu::Buffer,
     pub surface_vertex_count: u32,
     pub surface_index_count: u32,
@@ -38,6 +54,17 @@ pub struct MathBuffers {
     pub curve_2d_buffer: wgpu::Buffer,
     pub curve_2d_vertex_count: u32,
 
+    pub trajectory_buffer: wgpu::Buffer,
+    pub trajectory_point_count: u32,
+
+    /// One arrow along +y with unit length, drawn once per instance.
+    pub arrow_glyph_buffer: wgpu::Buffer,
+    pub arrow_glyph_vertex_count: u32,
+    pub arrow_instance_buffer: wgpu::Buffer,
+    pub arrow_count: u32,
+    pub streamline_buffer: wgpu::Buffer,
+    pub streamline_vertex_count: u32,
+
     pub z_min: f32,
     pub z_max: f32,
 }
@@ -58,6 +85,13 @@ impl MathBuffers {
             mapped_at_creation: false,
         });
 
+        let surface_color_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Surface Color Buffer"),
+            size: (MAX_SURFACE_VERTICES * 3 * 4) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         let surface_index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Surface Index Buffer"),
             size: (MAX_SURFACE_INDICES * 4) as u64,
@@ -100,9 +134,38 @@ impl MathBuffers {
             mapped_at_creation: false,
         });
 
+        let trajectory_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Trajectory Buffer"),
+            size: (MAX_TRAJECTORY_POINTS * 4 * 4) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let arrow_glyph = generate_arrow_glyph();
+        let arrow_glyph_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Arrow Glyph Buffer"),
+            contents: bytemuck::cast_slice(&arrow_glyph),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let arrow_instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Arrow Instance Buffer"),
+            size: (MAX_ARROWS * 8 * 4) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let streamline_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Streamline Buffer"),
+            size: (MAX_STREAMLINE_VERTICES * 4 * 4) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         Self {
             surface_vertex_buffer,
             surface_normal_buffer,
+            surface_color_buffer,
             surface_index_buffer,
             surface_vertex_count: 0,
             surface_index_count: 0,
@@ -115,6 +178,14 @@ impl MathBuffers {
             heatmap_vertex_count: 0,
             curve_2d_buffer,
             curve_2d_vertex_count: 0,
+            trajectory_buffer,
+            trajectory_point_count: 0,
+            arrow_glyph_buffer,
+            arrow_glyph_vertex_count: (arrow_glyph.len() / 6) as u32,
+            arrow_instance_buffer,
+            arrow_count: 0,
+            streamline_buffer,
+            streamline_vertex_count: 0,
             z_min: 0.0,
             z_max: 1.0,
         }
@@ -139,6 +210,13 @@ impl MathBuffers {
             0,
             bytemuck::cast_slice(&mesh.mesh.indices[..index_count]),
         );
+        if !mesh.colors.is_empty() {
+            queue.write_buffer(
+                &self.surface_color_buffer,
+                0,
+                bytemuck::cast_slice(&mesh.colors[..vertex_count]),
+            );
+        }
 
         self.surface_vertex_count = (vertex_count / 3) as u32;
         self.surface_index_count = index_count as u32;
@@ -148,7 +226,7 @@ impl MathBuffers {
         let uniforms = SurfaceUniforms {
             z_min: mesh.z_min,
             z_max: mesh.z_max,
-            _pad1: 0.0,
+            vertex_colors: u32::from(!mesh.colors.is_empty()),
             _pad2: 0.0,
         };
         queue.write_buffer(
@@ -188,6 +266,46 @@ impl MathBuffers {
         self.curve_vertex_count = (vertex_count / 3) as u32;
     }
 
+    /// Uploads every point of the trajectory, coloured by time, and an evenly thinned copy
+    /// that fits the curve buffer. Returns the thinned curve.
+    pub fn upload_trajectory(&mut self, queue: &wgpu::Queue, mesh: &TrajectoryMesh) -> CurveMesh {
+        let points: Vec<f32> = mesh
+            .vertices
+            .chunks(3)
+            .zip(&mesh.times)
+            .take(MAX_TRAJECTORY_POINTS)
+            .flat_map(|(p, &t)| [p[0], p[1], p[2], t])
+            .collect();
+        queue.write_buffer(&self.trajectory_buffer, 0, bytemuck::cast_slice(&points));
+        self.trajectory_point_count = (points.len() / 4) as u32;
+
+        let stride = mesh.times.len().div_ceil(MAX_CURVE_VERTICES).max(1);
+        let curve = CurveMesh {
+            vertices: mesh.vertices.chunks(3).step_by(stride).flatten().copied().collect(),
+        };
+        self.upload_curve(queue, &curve);
+        curve
+    }
+
+    pub fn upload_vector_field(&mut self, queue: &wgpu::Queue, mesh: &VectorFieldMesh) {
+        let arrow_len = mesh.arrows.len().min(MAX_ARROWS * 8);
+        queue.write_buffer(
+            &self.arrow_instance_buffer,
+            0,
+            bytemuck::cast_slice(&mesh.arrows[..arrow_len]),
+        );
+        self.arrow_count = (arrow_len / 8) as u32;
+
+        // Whole segments only, so a truncated upload never joins two streamlines.
+        let streamline_len = mesh.streamlines.len().min(MAX_STREAMLINE_VERTICES * 4) / 8 * 8;
+        queue.write_buffer(
+            &self.streamline_buffer,
+            0,
+            bytemuck::cast_slice(&mesh.streamlines[..streamline_len]),
+        );
+        self.streamline_vertex_count = (streamline_len / 4) as u32;
+    }
+
     pub fn upload_parametric_surface(&mut self, queue: &wgpu::Queue, mesh: &ParametricSurfaceMesh) {
         let vertex_count = mesh.mesh.vertices.len().min(MAX_SURFACE_VERTICES * 3);
         let index_count = mesh.mesh.indices.len().min(MAX_SURFACE_INDICES);
@@ -216,7 +334,7 @@ impl MathBuffers {
         let uniforms = SurfaceUniforms {
             z_min: 0.0,
             z_max: 1.0,
-            _pad1: 0.0,
+            vertex_colors: 0,
             _pad2: 0.0,
         };
         queue.write_buffer(
@@ -245,19 +363,40 @@ pub struct GpuState {
     pub size: winit::dpi::PhysicalSize<u32>,
 
     pub pipeline_3d: wgpu::RenderPipeline,
-    pub pipeline_2d: wgpu::RenderPipeline,
+    pub pipeline_points: wgpu::RenderPipeline,
     pub pipeline_surface: wgpu::RenderPipeline,
     pub pipeline_curve: wgpu::RenderPipeline,
+    pub pipeline_arrows: wgpu::RenderPipeline,
+    pub pipeline_streamlines: wgpu::RenderPipeline,
     pub pipeline_grid: wgpu::RenderPipeline,
     pub pipeline_math_2d: wgpu::RenderPipeline,
+    pub pipeline_domain_2d: wgpu::RenderPipeline,
+    pub pipeline_bit_plot: wgpu::RenderPipeline,
+    pub pipeline_bias_plot: wgpu::RenderPipeline,
+    pub pipeline_overlay: wgpu::RenderPipeline,
     pub pipeline_curve_2d: wgpu::RenderPipeline,
 
     pub camera_buffer: wgpu::Buffer,
+    pub camera_bind_group_layout: wgpu::BindGroupLayout,
     pub camera_bind_group: wgpu::BindGroup,
+    /// Colormap for trajectory points, arrows and streamlines. Generator points have
+    /// their own in `PointCloudBuffers`.
+    pub point_uniform_buffer: wgpu::Buffer,
     pub math_bind_group: wgpu::BindGroup,
 
+    pub histogram_pipelines: HistogramPipelines,
     pub point_buffers: PointCloudBuffers,
+    /// Second generator's points, only allocated while comparing.
+    pub point_buffers_b: Option<PointCloudBuffers>,
     pub math_buffers: MathBuffers,
+    /// Cell quads for generators A and B, each in its own half of the buffer.
+    pub bit_plot_buffer: wgpu::Buffer,
+    pub bit_plot_counts: [u32; 2],
+    /// Lines drawn over generator A's 3D points, e.g. spectral test hyperplanes.
+    pub overlay_buffer: wgpu::Buffer,
+    pub overlay_vertex_count: u32,
+    pub density: DensityTarget,
+    pub fractal: FractalTarget,
 
     pub depth_texture: wgpu::TextureView,
 }
@@ -286,6 +425,67 @@ fn surface_normal_layout() -> wgpu::VertexBufferLayout<'static> {
     }
 }
 
+fn surface_color_layout() -> wgpu::VertexBufferLayout<'static> {
+    wgpu::VertexBufferLayout {
+        array_stride: 12,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[wgpu::VertexAttribute {
+            offset: 0,
+            shader_location: 2,
+            format: wgpu::VertexFormat::Float32x3,
+        }],
+    }
+}
+
+fn arrow_glyph_layout() -> wgpu::VertexBufferLayout<'static> {
+    wgpu::VertexBufferLayout {
+        array_stride: 24,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x3,
+            },
+            wgpu::VertexAttribute {
+                offset: 12,
+                shader_location: 1,
+                format: wgpu::VertexFormat::Float32x3,
+            },
+        ],
+    }
+}
+
+/// Per arrow: origin, unit direction, length and normalized magnitude.
+fn arrow_instance_layout() -> wgpu::VertexBufferLayout<'static> {
+    wgpu::VertexBufferLayout {
+        array_stride: 32,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &[
+            wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 2,
+                format: wgpu::VertexFormat::Float32x3,
+            },
+            wgpu::VertexAttribute {
+                offset: 12,
+                shader_location: 3,
+                format: wgpu::VertexFormat::Float32x3,
+            },
+            wgpu::VertexAttribute {
+                offset: 24,
+                shader_location: 4,
+                format: wgpu::VertexFormat::Float32,
+            },
+            wgpu::VertexAttribute {
+                offset: 28,
+                shader_location: 5,
+                format: wgpu::VertexFormat::Float32,
+            },
+        ],
+    }
+}
+
 fn heatmap_vertex_layout() -> wgpu::VertexBufferLayout<'static> {
     wgpu::VertexBufferLayout {
         array_stride: 12,
@@ -337,11 +537,17 @@ impl GpuState {
             .await
             .unwrap();
 
+        let blendable = float32_blendable(&adapter);
+        let required_features = if blendable {
+            wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+        } else {
+            wgpu::Features::empty()
+        };
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
-                    required_features: wgpu::Features::empty(),
+                    required_features,
                     required_limits: wgpu::Limits::default(),
                     memory_hints: wgpu::MemoryHints::Performance,
                 },
@@ -382,30 +588,55 @@ impl GpuState {
             mapped_at_creation: false,
         });
 
+        let point_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Point Uniform Buffer"),
+            size: std::mem::size_of::<PointUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         let math_buffers = MathBuffers::new(&device);
 
         let camera_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Camera Bind Group Layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                ],
             });
 
         let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Camera Bind Group"),
             layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: point_uniform_buffer.as_entire_binding(),
+                },
+            ],
         });
 
         let math_bind_group_layout =
@@ -491,24 +722,19 @@ impl GpuState {
             cache: None,
         });
 
-        let pipeline_layout_2d = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("2D Pipeline Layout"),
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
-
-        let pipeline_2d = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("2D Render Pipeline"),
-            layout: Some(&pipeline_layout_2d),
+        // Generator points, with integer colour keys scaled by their own uniform.
+        let pipeline_points = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Point Cloud Render Pipeline"),
+            layout: Some(&pipeline_layout_3d),
             vertex: wgpu::VertexState {
                 module: &shader,
-                entry_point: Some("vs_2d_main"),
-                buffers: &[point_2d_layout()],
+                entry_point: Some("vs_points"),
+                buffers: &[keyed_point_layout()],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: Some("fs_2d_main"),
+                entry_point: Some("fs_main"),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -520,12 +746,24 @@ impl GpuState {
                 topology: wgpu::PrimitiveTopology::PointList,
                 ..Default::default()
             },
-            depth_stencil: None,
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
             multisample: wgpu::MultisampleState::default(),
             multiview: None,
             cache: None,
         });
 
+        let pipeline_layout_2d = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("2D Pipeline Layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
         let pipeline_layout_math = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Math Pipeline Layout"),
             bind_group_layouts: &[&math_bind_group_layout],
@@ -538,7 +776,7 @@ impl GpuState {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs_surface_main"),
-                buffers: &[surface_vertex_layout(), surface_normal_layout()],
+                buffers: &[surface_vertex_layout(), surface_normal_layout(), surface_color_layout()],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
@@ -603,6 +841,78 @@ impl GpuState {
             cache: None,
         });
 
+        let pipeline_arrows = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Arrow Render Pipeline"),
+            layout: Some(&pipeline_layout_3d),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_arrow_main"),
+                buffers: &[arrow_glyph_layout(), arrow_instance_layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_arrow_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        // Same shaders as the point cloud, so streamlines share its colormap.
+        let pipeline_streamlines = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Streamline Render Pipeline"),
+            layout: Some(&pipeline_layout_3d),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[point_3d_layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
         let pipeline_grid = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Grid Render Pipeline"),
             layout: Some(&pipeline_layout_3d),
@@ -638,6 +948,41 @@ impl GpuState {
             cache: None,
         });
 
+        let pipeline_overlay = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overlay Pipeline"),
+            layout: Some(&pipeline_layout_3d),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_overlay_main"),
+                buffers: &[surface_vertex_layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_grid_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
         let pipeline_math_2d = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Math 2D Pipeline"),
             layout: Some(&pipeline_layout_2d),
@@ -667,18 +1012,19 @@ impl GpuState {
             cache: None,
         });
 
-        let pipeline_curve_2d = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Curve 2D Pipeline"),
+        // The flat heatmap positions with the surface's colours and triangles.
+        let pipeline_domain_2d = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Domain Coloring 2D Pipeline"),
             layout: Some(&pipeline_layout_2d),
             vertex: wgpu::VertexState {
                 module: &shader,
-                entry_point: Some("vs_curve_2d_main"),
-                buffers: &[curve_2d_vertex_layout()],
+                entry_point: Some("vs_domain_2d_main"),
+                buffers: &[heatmap_vertex_layout(), surface_color_layout()],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: Some("fs_curve_2d_main"),
+                entry_point: Some("fs_math_2d_main"),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -687,7 +1033,7 @@ impl GpuState {
                 compilation_options: Default::default(),
             }),
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::LineStrip,
+                topology: wgpu::PrimitiveTopology::TriangleList,
                 ..Default::default()
             },
             depth_stencil: None,
@@ -696,27 +1042,122 @@ impl GpuState {
             cache: None,
         });
 
-        let point_buffers = PointCloudBuffers::new(&device);
-        let depth_texture = Self::create_depth_texture(&device, &config);
+        let cell_pipeline = |label: &str, vs_entry: &str| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout_2d),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some(vs_entry),
+                    buffers: &[heatmap_vertex_layout()],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_math_2d_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: config.format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let pipeline_bit_plot = cell_pipeline("Bit Plot Pipeline", "vs_math_2d_main");
+        let pipeline_bias_plot = cell_pipeline("Bias Plot Pipeline", "vs_bias_main");
 
-        Self {
-            surface,
-            device,
-            queue,
-            config,
-            size,
-            pipeline_3d,
-            pipeline_2d,
-            pipeline_surface,
-            pipeline_curve,
-            pipeline_grid,
-            pipeline_math_2d,
-            pipeline_curve_2d,
-            camera_buffer,
-            camera_bind_group,
-            math_bind_group,
+        let pipeline_curve_2d = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Curve 2D Pipeline"),
+            layout: Some(&pipeline_layout_2d),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_curve_2d_main"),
+                buffers: &[curve_2d_vertex_layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_curve_2d_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let density = DensityTarget::new(&device, &shader, &config, &camera_bind_group_layout, blendable);
+
+        let fractal = FractalTarget::new(&device, &shader, &config);
+        let histogram_pipelines = HistogramPipelines::new(&device, &shader, &config);
+        let point_buffers =
+            PointCloudBuffers::new(&device, &histogram_pipelines, &camera_bind_group_layout, &camera_buffer);
+        let bit_plot_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Bit Plot Buffer"),
+            size: (MAX_BIT_PLOT_VERTICES * 2 * 3 * 4) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let overlay_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Overlay Buffer"),
+            size: (MAX_OVERLAY_VERTICES * 3 * 4) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let depth_texture = Self::create_depth_texture(&device, &config);
+
+        Self {
+            surface,
+            device,
+            queue,
+            config,
+            size,
+            pipeline_3d,
+            pipeline_points,
+            pipeline_surface,
+            pipeline_curve,
+            pipeline_arrows,
+            pipeline_streamlines,
+            pipeline_grid,
+            pipeline_math_2d,
+            pipeline_domain_2d,
+            pipeline_bit_plot,
+            pipeline_bias_plot,
+            pipeline_overlay,
+            pipeline_curve_2d,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+            point_uniform_buffer,
+            math_bind_group,
+            histogram_pipelines,
             point_buffers,
+            point_buffers_b: None,
             math_buffers,
+            bit_plot_buffer,
+            bit_plot_counts: [0; 2],
+            overlay_buffer,
+            overlay_vertex_count: 0,
+            density,
+            fractal,
             depth_texture,
         }
     }
@@ -752,6 +1193,7 @@ impl GpuState {
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
             self.depth_texture = Self::create_depth_texture(&self.device, &self.config);
+            self.density.resize(&self.device, &self.config);
         }
     }
 
@@ -761,6 +1203,25 @@ impl GpuState {
             .write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[uniform]));
     }
 
+    pub fn set_point_colormap(&self, colormap: Option<Colormap>) {
+        let uniform = PointUniform::new(colormap, KeyScale::default());
+        self.queue
+            .write_buffer(&self.point_uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn set_density_style(&mut self, tone_map: ToneMap, colormap: Colormap) {
+        self.density.set_style(&self.queue, tone_map, colormap);
+        self.point_buffers.histogram.set_style(&self.queue, tone_map, colormap);
+        if let Some(buffers) = &mut self.point_buffers_b {
+            buffers.histogram.set_style(&self.queue, tone_map, colormap);
+        }
+    }
+
+    pub fn set_fractal_style(&self, colormap: Colormap, cycle: f32) {
+        let screen = [self.config.width, self.config.height];
+        self.fractal.set_style(&self.queue, screen, colormap, cycle);
+    }
+
     pub fn set_vsync(&mut self, enabled: bool) {
         self.config.present_mode = if enabled {
             wgpu::PresentMode::AutoVsync
@@ -793,15 +1254,135 @@ impl GpuState {
             occlusion_query_set: None,
         });
 
-        render_pass.set_pipeline(&self.pipeline_3d);
-        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.point_buffers.current_3d_buffer().slice(..));
+        render_pass.set_pipeline(&self.pipeline_points);
+        render_pass.set_bind_group(0, self.point_buffers.bind_group(), &[]);
+        render_pass.set_vertex_buffer(0, self.point_buffers.buffer_3d().slice(..));
         render_pass.draw(0..self.point_buffers.points_count_3d(), 0..1);
+        self.draw_overlay(&mut render_pass);
+    }
+
+    /// Draws A and B side by side with the same camera. Viewports are `[x, y, w, h]` in pixels.
+    pub fn render_3d_split(
+        &self,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        viewports: [[f32; 4]; 2],
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("3D Split Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        for (i, (buffers, [x, y, w, h])) in self.split_buffers().zip(viewports).enumerate() {
+            render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+            render_pass.set_pipeline(&self.pipeline_points);
+            render_pass.set_bind_group(0, buffers.bind_group(), &[]);
+            render_pass.set_vertex_buffer(0, buffers.buffer_3d().slice(..));
+            render_pass.draw(0..buffers.points_count_3d(), 0..1);
+            if i == 0 {
+                self.draw_overlay(&mut render_pass);
+            }
+        }
+    }
+
+    pub fn upload_overlay(&mut self, vertices: &[f32]) {
+        let count = vertices.len().min(MAX_OVERLAY_VERTICES * 3);
+        self.queue.write_buffer(
+            &self.overlay_buffer,
+            0,
+            bytemuck::cast_slice(&vertices[..count]),
+        );
+        self.overlay_vertex_count = (count / 3) as u32;
+    }
+
+    /// Expects the camera bind group to be set already.
+    fn draw_overlay(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        if self.overlay_vertex_count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline_overlay);
+        render_pass.set_vertex_buffer(0, self.overlay_buffer.slice(..));
+        render_pass.draw(0..self.overlay_vertex_count, 0..1);
+    }
+
+    /// Hit counts of the 3D points, tone mapped onto `view`. With viewports, A and B are split as above.
+    pub fn render_density(
+        &self,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        viewports: Option<[[f32; 4]; 2]>,
+    ) {
+        self.density.accumulate(
+            &self.device,
+            &self.queue,
+            encoder,
+            &self.camera_buffer,
+            &self.camera_bind_group,
+            self.split_buffers().zip(split_viewports(viewports)),
+        );
+        self.density.compute_stats(encoder);
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Density Tonemap Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        self.density.draw_tonemap(&mut render_pass);
+        if let Some([[x, y, w, h], _]) = viewports {
+            render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+        }
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        self.draw_overlay(&mut render_pass);
     }
 
-    pub fn render_2d(&self, view: &wgpu::TextureView, encoder: &mut wgpu::CommandEncoder) {
+    /// The 2D sample histograms of A and B, split like the 3D view when comparing.
+    pub fn render_histogram(
+        &self,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        viewports: Option<[[f32; 4]; 2]>,
+    ) {
+        let split = || self.split_buffers().zip(split_viewports(viewports));
+        for (buffers, _) in split() {
+            buffers.histogram.compute_stats(encoder, &self.histogram_pipelines);
+        }
+
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("2D Render Pass"),
+            label: Some("Histogram Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view,
                 resolve_target: None,
@@ -815,9 +1396,16 @@ impl GpuState {
             occlusion_query_set: None,
         });
 
-        render_pass.set_pipeline(&self.pipeline_2d);
-        render_pass.set_vertex_buffer(0, self.point_buffers.current_2d_buffer().slice(..));
-        render_pass.draw(0..self.point_buffers.points_count_2d(), 0..1);
+        for (buffers, viewport) in split() {
+            if let Some([x, y, w, h]) = viewport {
+                render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+            }
+            buffers.histogram.draw(&mut render_pass, &self.histogram_pipelines);
+        }
+    }
+
+    fn split_buffers(&self) -> impl Iterator<Item = &PointCloudBuffers> {
+        std::iter::once(&self.point_buffers).chain(self.point_buffers_b.as_ref())
     }
 
     pub fn render_surface(&self, view: &wgpu::TextureView, encoder: &mut wgpu::CommandEncoder) {
@@ -847,6 +1435,7 @@ impl GpuState {
         render_pass.set_bind_group(0, &self.math_bind_group, &[]);
         render_pass.set_vertex_buffer(0, self.math_buffers.surface_vertex_buffer.slice(..));
         render_pass.set_vertex_buffer(1, self.math_buffers.surface_normal_buffer.slice(..));
+        render_pass.set_vertex_buffer(2, self.math_buffers.surface_color_buffer.slice(..));
         render_pass.set_index_buffer(
             self.math_buffers.surface_index_buffer.slice(..),
             wgpu::IndexFormat::Uint32,
@@ -885,6 +1474,7 @@ impl GpuState {
         render_pass.set_bind_group(0, &self.math_bind_group, &[]);
         render_pass.set_vertex_buffer(0, self.math_buffers.surface_vertex_buffer.slice(..));
         render_pass.set_vertex_buffer(1, self.math_buffers.surface_normal_buffer.slice(..));
+        render_pass.set_vertex_buffer(2, self.math_buffers.surface_color_buffer.slice(..));
         render_pass.set_index_buffer(
             self.math_buffers.surface_index_buffer.slice(..),
             wgpu::IndexFormat::Uint32,
@@ -954,6 +1544,105 @@ impl GpuState {
         render_pass.draw(0..self.math_buffers.curve_vertex_count, 0..1);
     }
 
+    pub fn render_trajectory_points(
+        &self,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        clear: bool,
+    ) {
+        let load_op = if clear {
+            wgpu::LoadOp::Clear(wgpu::Color::BLACK)
+        } else {
+            wgpu::LoadOp::Load
+        };
+        let depth_load = if clear {
+            wgpu::LoadOp::Clear(1.0)
+        } else {
+            wgpu::LoadOp::Load
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Trajectory Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: load_op,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture,
+                depth_ops: Some(wgpu::Operations {
+                    load: depth_load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline_3d);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.math_buffers.trajectory_buffer.slice(..));
+        render_pass.draw(0..self.math_buffers.trajectory_point_count, 0..1);
+    }
+
+    pub fn render_vector_field(
+        &self,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        clear: bool,
+        streamlines: bool,
+    ) {
+        let load_op = if clear {
+            wgpu::LoadOp::Clear(wgpu::Color::BLACK)
+        } else {
+            wgpu::LoadOp::Load
+        };
+        let depth_load = if clear {
+            wgpu::LoadOp::Clear(1.0)
+        } else {
+            wgpu::LoadOp::Load
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Vector Field Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: load_op,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture,
+                depth_ops: Some(wgpu::Operations {
+                    load: depth_load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        let buffers = &self.math_buffers;
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_pipeline(&self.pipeline_arrows);
+        render_pass.set_vertex_buffer(0, buffers.arrow_glyph_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, buffers.arrow_instance_buffer.slice(..));
+        render_pass.draw(0..buffers.arrow_glyph_vertex_count, 0..buffers.arrow_count);
+
+        if streamlines {
+            render_pass.set_pipeline(&self.pipeline_streamlines);
+            render_pass.set_vertex_buffer(0, buffers.streamline_buffer.slice(..));
+            render_pass.draw(0..buffers.streamline_vertex_count, 0..1);
+        }
+    }
+
     pub fn render_grid(
         &self,
         view: &wgpu::TextureView,
@@ -1020,6 +1709,107 @@ impl GpuState {
         render_pass.draw(0..self.math_buffers.heatmap_vertex_count, 0..1);
     }
 
+    pub fn render_domain_2d(&self, view: &wgpu::TextureView, encoder: &mut wgpu::CommandEncoder) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Domain Coloring 2D Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline_domain_2d);
+        render_pass.set_vertex_buffer(0, self.math_buffers.heatmap_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.math_buffers.surface_color_buffer.slice(..));
+        render_pass.set_index_buffer(
+            self.math_buffers.surface_index_buffer.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..self.math_buffers.surface_index_count, 0, 0..1);
+    }
+
+    pub fn render_fractal(&self, view: &wgpu::TextureView, encoder: &mut wgpu::CommandEncoder) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Fractal Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        self.fractal.draw(&mut render_pass);
+    }
+
+    /// `slot` 0 is generator A, 1 is B.
+    pub fn upload_bit_plot(&mut self, slot: usize, data: &[f32]) {
+        let count = data.len().min(MAX_BIT_PLOT_VERTICES * 3);
+        self.queue.write_buffer(
+            &self.bit_plot_buffer,
+            (slot * MAX_BIT_PLOT_VERTICES * 3 * 4) as u64,
+            bytemuck::cast_slice(&data[..count]),
+        );
+        self.bit_plot_counts[slot] = (count / 3) as u32;
+    }
+
+    /// Draws A full screen, or A and B side by side when `viewports` is given.
+    /// `diverging` picks the bias colormap, centred on 0.5, over viridis.
+    pub fn render_bit_plot(
+        &self,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        viewports: Option<[[f32; 4]; 2]>,
+        diverging: bool,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Bit Plot Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(if diverging {
+            &self.pipeline_bias_plot
+        } else {
+            &self.pipeline_bit_plot
+        });
+        render_pass.set_vertex_buffer(0, self.bit_plot_buffer.slice(..));
+        let slot_range = |slot: usize| {
+            let first = (slot * MAX_BIT_PLOT_VERTICES) as u32;
+            first..first + self.bit_plot_counts[slot]
+        };
+        match viewports {
+            Some(viewports) => {
+                for (slot, [x, y, w, h]) in viewports.into_iter().enumerate() {
+                    render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+                    render_pass.draw(slot_range(slot), 0..1);
+                }
+            }
+            None => render_pass.draw(slot_range(0), 0..1),
+        }
+    }
+
     pub fn render_curve_2d(&self, view: &wgpu::TextureView, encoder: &mut wgpu::CommandEncoder) {
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Curve 2D Render Pass"),
@@ -1042,6 +1832,190 @@ impl GpuState {
     }
 }
 
+/// Viewport per generator: only A without a split, A and B with one.
+fn split_viewports(viewports: Option<[[f32; 4]; 2]>) -> impl Iterator<Item = Option<[f32; 4]>> {
+    let count = if viewports.is_some() { 2 } else { 1 };
+    viewports.map_or([None, None], |v| v.map(Some)).into_iter().take(count)
+}
+
+/// Screen position in pixels and depth in `[0, 1]` of a world-space point, or `None`
+/// outside the depth range. `viewport` is `[x, y, width, height]` in pixels.
+pub fn project_point(view_proj: glam::Mat4, viewport: [f32; 4], point: [f32; 3]) -> Option<[f32; 3]> {
+    let clip = view_proj * glam::Vec3::from(point).extend(1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let ndc = clip.truncate() / clip.w;
+    if !(0.0..=1.0).contains(&ndc.z) {
+        return None;
+    }
+    Some([
+        viewport[0] + (ndc.x + 1.0) * 0.5 * viewport[2],
+        viewport[1] + (1.0 - ndc.y) * 0.5 * viewport[3],
+        ndc.z,
+    ])
+}
+
+/// CPU picking: index of the point in `points` (x, y, z and attribute) drawn closest to `cursor`,
+/// within `radius` pixels. Ties go to the point nearest the camera.
+pub fn pick_point(points: &[KeyedPoint], view_proj: glam::Mat4, viewport: [f32; 4], cursor: [f32; 2], radius: f32) -> Option<usize> {
+    let mut best: Option<(usize, f32, f32)> = None;
+    for (i, p) in points.iter().enumerate() {
+        let Some([x, y, depth]) = project_point(view_proj, viewport, p.position) else {
+            continue;
+        };
+        let dist = (x - cursor[0]).powi(2) + (y - cursor[1]).powi(2);
+        if dist > radius * radius {
+            continue;
+        }
+        let closer = match best {
+            Some((_, best_dist, best_depth)) => (dist, depth) < (best_dist, best_depth),
+            None => true,
+        };
+        if closer {
+            best = Some((i, dist, depth));
+        }
+    }
+    best.map(|(i, _, _)| i)
+}
+
+/// An arrow along +y from the origin to `(0, 1, 0)`: a thin prism shaft and a cone head,
+/// as a triangle list of interleaved position and normal.
+fn generate_arrow_glyph() -> Vec<f32> {
+    const SIDES: usize = 8;
+    const SHAFT_RADIUS: f32 = 0.03;
+    const HEAD_RADIUS: f32 = 0.1;
+    const HEAD_START: f32 = 0.65;
+
+    let ring = |i: f32| {
+        let angle = i / SIDES as f32 * std::f32::consts::TAU;
+        [angle.cos(), angle.sin()]
+    };
+    let mut vertices = Vec::with_capacity(SIDES * 12 * 6);
+    let mut push = |p: [f32; 3], n: [f32; 3]| {
+        vertices.extend(p);
+        vertices.extend(n);
+    };
+
+    // Slant of the cone's side, for its normals.
+    let slope = HEAD_RADIUS / (1.0 - HEAD_START);
+    let cone_normal = |[c, s]: [f32; 2]| {
+        let len = (1.0 + slope * slope).sqrt();
+        [c / len, slope / len, s / len]
+    };
+
+    for i in 0..SIDES {
+        let (a, b) = (ring(i as f32), ring(i as f32 + 1.0));
+        let shaft = |[c, s]: [f32; 2], y: f32| [c * SHAFT_RADIUS, y, s * SHAFT_RADIUS];
+        let head = |[c, s]: [f32; 2]| [c * HEAD_RADIUS, HEAD_START, s * HEAD_RADIUS];
+        let side = |[c, s]: [f32; 2]| [c, 0.0, s];
+
+        push(shaft(a, 0.0), side(a));
+        push(shaft(b, 0.0), side(b));
+        push(shaft(b, HEAD_START), side(b));
+        push(shaft(a, 0.0), side(a));
+        push(shaft(b, HEAD_START), side(b));
+        push(shaft(a, HEAD_START), side(a));
+
+        push([0.0, HEAD_START, 0.0], [0.0, -1.0, 0.0]);
+        push(head(b), [0.0, -1.0, 0.0]);
+        push(head(a), [0.0, -1.0, 0.0]);
+
+        push(head(a), cone_normal(a));
+        push(head(b), cone_normal(b));
+        push([0.0, 1.0, 0.0], cone_normal(ring(i as f32 + 0.5)));
+    }
+    vertices
+}
+
+/// Three axis-aligned segments of length `2 * size` crossing at `center`, as a line list.
+pub fn generate_marker_vertices(center: [f32; 3], size: f32) -> Vec<f32> {
+    let mut vertices = Vec::with_capacity(18);
+    for axis in 0..3 {
+        for sign in [-1.0, 1.0] {
+            let mut p = center;
+            p[axis] += sign * size;
+            vertices.extend(p);
+        }
+    }
+    vertices
+}
+
+/// Outlines where the planes `normal · x = level` cut the unit cube, as line-list
+/// vertices scaled onto the box `[min, max]`.
+pub fn generate_plane_vertices(normal: [f64; 3], levels: &[f64], min: [f32; 3], max: [f32; 3]) -> Vec<f32> {
+    const CORNERS: [[f64; 3]; 8] = [
+        [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0], [1.0, 1.0, 1.0],
+    ];
+    const EDGES: [(usize, usize); 12] = [
+        (0, 1), (2, 3), (4, 5), (6, 7),
+        (0, 2), (1, 3), (4, 6), (5, 7),
+        (0, 4), (1, 5), (2, 6), (3, 7),
+    ];
+    let dot = |p: [f64; 3]| normal[0] * p[0] + normal[1] * p[1] + normal[2] * p[2];
+
+    // In-plane axes for ordering the polygon corners.
+    let n = glam::DVec3::from(normal).normalize_or_zero();
+    let u = n.any_orthonormal_vector();
+    let v = n.cross(u);
+
+    let mut vertices = Vec::new();
+    for &level in levels {
+        let mut points: Vec<glam::DVec3> = Vec::with_capacity(6);
+        for (a, b) in EDGES {
+            let (pa, pb) = (CORNERS[a], CORNERS[b]);
+            let (da, db) = (dot(pa) - level, dot(pb) - level);
+            if (da > 0.0) == (db > 0.0) || da == db {
+                continue;
+            }
+            let t = da / (da - db);
+            points.push(glam::DVec3::from(pa).lerp(glam::DVec3::from(pb), t));
+        }
+        if points.len() < 3 {
+            continue;
+        }
+
+        let center = points.iter().copied().sum::<glam::DVec3>() / points.len() as f64;
+        points.sort_by(|p, q| {
+            let angle = |x: &glam::DVec3| (*x - center).dot(v).atan2((*x - center).dot(u));
+            angle(p).total_cmp(&angle(q))
+        });
+
+        for i in 0..points.len() {
+            for p in [points[i], points[(i + 1) % points.len()]] {
+                for axis in 0..3 {
+                    vertices.push(min[axis] + p[axis] as f32 * (max[axis] - min[axis]));
+                }
+            }
+        }
+    }
+
+    vertices
+}
+
+/// Heatmap quads for a `cols` x `rows` grid of values in `[0, 1]`, row 0 at the top.
+pub fn generate_cell_vertices(cols: usize, rows: usize, values: &[f32]) -> Vec<f32> {
+    let mut vertices = Vec::with_capacity(values.len() * 6 * 3);
+    if cols == 0 || rows == 0 {
+        return vertices;
+    }
+    let extent = 0.9;
+    let cell_w = 2.0 * extent / cols as f32;
+    let cell_h = 2.0 * extent / rows as f32;
+
+    for (i, &value) in values.iter().enumerate().take(cols * rows) {
+        let x0 = -extent + (i % cols) as f32 * cell_w;
+        let y0 = extent - (i / cols) as f32 * cell_h;
+        let (x1, y1) = (x0 + cell_w, y0 - cell_h);
+        for [x, y] in [[x0, y0], [x1, y0], [x0, y1], [x0, y1], [x1, y0], [x1, y1]] {
+            vertices.extend_from_slice(&[x, y, value]);
+        }
+    }
+
+    vertices
+}
+
 pub fn generate_grid_vertices(size: f32, divisions: u32) -> Vec<f32> {
     let mut vertices = Vec::new();
     let step = size * 2.0 / divisions as f32;
@@ -0,0 +1,324 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::renderer::density::{STATS_SIZE, ToneMap};
+use crate::renderer::point_cloud::Colormap;
+
+pub const MAX_GRID: u32 = 1024;
+/// Points binned per dispatch; larger uploads are split.
+const MAX_BATCH_POINTS: usize = 1 << 20;
+const BIN_WORKGROUP_SIZE: u32 = 256;
+const CELL_WORKGROUP_SIZE: u32 = 16;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct HistogramUniform {
+    /// Min x, min y, max x, max y.
+    bounds: [f32; 4],
+    grid: u32,
+    points: u32,
+    tone_map: u32,
+    colormap: u32,
+}
+
+/// Layouts and pipelines shared by the A and B histograms.
+pub struct HistogramPipelines {
+    bin_layout: wgpu::BindGroupLayout,
+    display_layout: wgpu::BindGroupLayout,
+    pipeline_bin: wgpu::ComputePipeline,
+    pipeline_max: wgpu::ComputePipeline,
+    pipeline_levels: wgpu::ComputePipeline,
+    pipeline_cdf: wgpu::ComputePipeline,
+    pipeline_display: wgpu::RenderPipeline,
+}
+
+impl HistogramPipelines {
+    pub fn new(device: &wgpu::Device, shader: &wgpu::ShaderModule, config: &wgpu::SurfaceConfiguration) -> Self {
+        let entry = |binding, visibility, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let storage = |read_only| wgpu::BufferBindingType::Storage { read_only };
+        let compute = wgpu::ShaderStages::COMPUTE;
+        let fragment = wgpu::ShaderStages::FRAGMENT;
+
+        let bin_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Histogram Bin Bind Group Layout"),
+            entries: &[
+                entry(4, compute, storage(false)),
+                entry(7, compute, storage(false)),
+                entry(8, compute, wgpu::BufferBindingType::Uniform),
+                entry(10, compute, storage(true)),
+            ],
+        });
+
+        let display_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Histogram Display Bind Group Layout"),
+            entries: &[
+                entry(5, fragment, storage(true)),
+                entry(8, fragment, wgpu::BufferBindingType::Uniform),
+                entry(9, fragment, storage(true)),
+            ],
+        });
+
+        let compute_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Histogram Compute Pipeline Layout"),
+            bind_group_layouts: &[&bin_layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&compute_layout),
+                module: shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let pipeline_bin = compute_pipeline("Histogram Bin Pipeline", "cs_histogram_bin");
+        let pipeline_max = compute_pipeline("Histogram Max Pipeline", "cs_histogram_max");
+        let pipeline_levels = compute_pipeline("Histogram Levels Pipeline", "cs_histogram_levels");
+        let pipeline_cdf = compute_pipeline("Histogram CDF Pipeline", "cs_density_cdf");
+
+        let display_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Histogram Display Pipeline Layout"),
+            bind_group_layouts: &[&display_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline_display = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Histogram Display Pipeline"),
+            layout: Some(&display_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_histogram_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_histogram_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            bin_layout,
+            display_layout,
+            pipeline_bin,
+            pipeline_max,
+            pipeline_levels,
+            pipeline_cdf,
+            pipeline_display,
+        }
+    }
+}
+
+/// Sample counts on a `grid` x `grid` lattice over the x/y bounds, kept on the GPU.
+/// Points are binned as they arrive and then dropped, so the count is only limited by u32 cells.
+pub struct Histogram2D {
+    cells_buffer: wgpu::Buffer,
+    stats_buffer: wgpu::Buffer,
+    points_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+    bin_bind_group: wgpu::BindGroup,
+    display_bind_group: wgpu::BindGroup,
+    uniform: HistogramUniform,
+    samples: u64,
+}
+
+impl Histogram2D {
+    pub fn new(device: &wgpu::Device, pipelines: &HistogramPipelines) -> Self {
+        let cells_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Histogram Cells Buffer"),
+            size: (MAX_GRID * MAX_GRID * 4) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let stats_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Histogram Stats Buffer"),
+            size: STATS_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let points_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Histogram Points Buffer"),
+            size: (MAX_BATCH_POINTS * 2 * 4) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Histogram Uniform Buffer"),
+            size: std::mem::size_of::<HistogramUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bin_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Histogram Bin Bind Group"),
+            layout: &pipelines.bin_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: stats_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 7,
+                    resource: cells_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 8,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 10,
+                    resource: points_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let display_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Histogram Display Bind Group"),
+            layout: &pipelines.display_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: stats_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 8,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 9,
+                    resource: cells_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            cells_buffer,
+            stats_buffer,
+            points_buffer,
+            uniform_buffer,
+            bin_bind_group,
+            display_bind_group,
+            uniform: HistogramUniform {
+                bounds: [-1.0, -1.0, 1.0, 1.0],
+                grid: 512,
+                points: 0,
+                tone_map: ToneMap::Log as u32,
+                colormap: Colormap::Viridis as u32,
+            },
+            samples: 0,
+        }
+    }
+
+    /// Samples binned since the last clear.
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn clear(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Histogram Clear Encoder"),
+        });
+        encoder.clear_buffer(&self.cells_buffer, 0, None);
+        queue.submit(std::iter::once(encoder.finish()));
+        self.samples = 0;
+    }
+
+    pub fn set_style(&mut self, queue: &wgpu::Queue, tone_map: ToneMap, colormap: Colormap) {
+        self.uniform.tone_map = tone_map as u32;
+        self.uniform.colormap = colormap as u32;
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+    }
+
+    /// Bins x/y pairs. Changing the grid or the bounds starts a new histogram.
+    pub fn add_points(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pipelines: &HistogramPipelines,
+        points: &[f32],
+        grid: u32,
+        bounds: [f32; 4],
+    ) {
+        let grid = grid.clamp(1, MAX_GRID);
+        if grid != self.uniform.grid || bounds != self.uniform.bounds {
+            self.clear(device, queue);
+            self.uniform.grid = grid;
+            self.uniform.bounds = bounds;
+            queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+        }
+
+        for chunk in points.chunks(MAX_BATCH_POINTS * 2) {
+            let count = (chunk.len() / 2) as u32;
+            if count == 0 {
+                continue;
+            }
+            self.uniform.points = count;
+            queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+            queue.write_buffer(&self.points_buffer, 0, bytemuck::cast_slice(&chunk[..count as usize * 2]));
+
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Histogram Bin Encoder"),
+            });
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Histogram Bin Pass"),
+                    timestamp_writes: None,
+                });
+                pass.set_pipeline(&pipelines.pipeline_bin);
+                pass.set_bind_group(0, &self.bin_bind_group, &[]);
+                pass.dispatch_workgroups(count.div_ceil(BIN_WORKGROUP_SIZE), 1, 1);
+            }
+            queue.submit(std::iter::once(encoder.finish()));
+            self.samples += count as u64;
+        }
+    }
+
+    /// Max, histogram and CDF of the cell counts, for the tone map.
+    pub fn compute_stats(&self, encoder: &mut wgpu::CommandEncoder, pipelines: &HistogramPipelines) {
+        encoder.clear_buffer(&self.stats_buffer, 0, None);
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Histogram Stats Pass"),
+            timestamp_writes: None,
+        });
+        let groups = self.uniform.grid.div_ceil(CELL_WORKGROUP_SIZE);
+        pass.set_bind_group(0, &self.bin_bind_group, &[]);
+        pass.set_pipeline(&pipelines.pipeline_max);
+        pass.dispatch_workgroups(groups, groups, 1);
+        pass.set_pipeline(&pipelines.pipeline_levels);
+        pass.dispatch_workgroups(groups, groups, 1);
+        pass.set_pipeline(&pipelines.pipeline_cdf);
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+
+    /// Tone-mapped cells over the central 90% of the current viewport.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, pipelines: &HistogramPipelines) {
+        render_pass.set_pipeline(&pipelines.pipeline_display);
+        render_pass.set_bind_group(0, &self.display_bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
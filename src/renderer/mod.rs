@@ -1,6 +1,11 @@
 pub mod camera;
+pub mod density;
+pub mod fractal;
 pub mod gpu;
+pub mod histogram;
 pub mod point_cloud;
 
 pub use camera::{Camera, CameraMode};
-pub use gpu::{GpuState, generate_grid_vertices};
+pub use density::ToneMap;
+pub use gpu::{GpuState, generate_cell_vertices, generate_grid_vertices, generate_marker_vertices, generate_plane_vertices, pick_point, project_point};
+pub use point_cloud::Colormap;
@@ -1,142 +1,193 @@
 use bytemuck::{Pod, Zeroable};
 
-const NUM_BUFFERS: usize = 3;
+use crate::renderer::histogram::{Histogram2D, HistogramPipelines};
+
 const MAX_POINTS_PER_BUFFER: usize = 10_000_000;
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct Point3D {
     pub position: [f32; 3],
+    /// Fed through the colormap, in `[0, 1]`.
+    pub value: f32,
+}
+
+/// A generator point. The shader maps `key` onto the colormap through `KeyScale`, so
+/// points never need rewriting when the scale moves.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+pub struct KeyedPoint {
+    pub position: [f32; 3],
+    pub key: u32,
+}
+
+/// Colormaps for per-point attributes. The shader reads the discriminant; 0 colours by position.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    Viridis = 1,
+    Magma = 2,
+    Turbo = 3,
+    /// Wraps around, for attributes taken modulo a period.
+    Cyclic = 4,
+}
+
+/// Maps keys onto `[0, 1]` in wrapping u32 arithmetic: `(key - base) % period / (period - 1)`
+/// when `period` is set, otherwise `(key - base) / span`.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct KeyScale {
+    pub base: u32,
+    pub span: u32,
+    pub period: u32,
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
-pub struct Point2D {
-    pub position: [f32; 2],
-    pub value: f32,
+pub struct PointUniform {
+    pub colormap: u32,
+    pub key_base: u32,
+    pub key_span: u32,
+    pub key_period: u32,
+}
+
+impl PointUniform {
+    pub fn new(colormap: Option<Colormap>, keys: KeyScale) -> Self {
+        Self {
+            colormap: colormap.map_or(0, |c| c as u32),
+            key_base: keys.base,
+            key_span: keys.span,
+            key_period: keys.period,
+        }
+    }
 }
 
 pub struct PointCloudBuffers {
-    buffers_3d: [wgpu::Buffer; NUM_BUFFERS],
-    buffers_2d: [wgpu::Buffer; NUM_BUFFERS],
+    buffer_3d: wgpu::Buffer,
+    /// Colormap and key scale of these points, so A and B each get their own.
+    uniform_buffer: wgpu::Buffer,
+    /// The camera plus `uniform_buffer`, laid out like the shared camera bind group.
+    bind_group: wgpu::BindGroup,
+    pub histogram: Histogram2D,
 
-    current_buffer: usize,
     points_count_3d: usize,
-    points_count_2d: usize,
 }
 
 impl PointCloudBuffers {
-    pub fn new(device: &wgpu::Device) -> Self {
-        let buffers_3d = std::array::from_fn(|_| {
-            device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some("Point Cloud 3D Buffer"),
-                size: (MAX_POINTS_PER_BUFFER * std::mem::size_of::<Point3D>()) as u64,
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-                mapped_at_creation: false,
-            })
+    pub fn new(
+        device: &wgpu::Device,
+        histogram_pipelines: &HistogramPipelines,
+        camera_layout: &wgpu::BindGroupLayout,
+        camera_buffer: &wgpu::Buffer,
+    ) -> Self {
+        let buffer_3d = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Point Cloud 3D Buffer"),
+            size: (MAX_POINTS_PER_BUFFER * std::mem::size_of::<KeyedPoint>()) as u64,
+            // Storage for the density splat on adapters that can't blend float targets.
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Point Cloud Uniform Buffer"),
+            size: std::mem::size_of::<PointUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
 
-        let buffers_2d = std::array::from_fn(|_| {
-            device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some("Point Cloud 2D Buffer"),
-                size: (MAX_POINTS_PER_BUFFER * std::mem::size_of::<Point2D>()) as u64,
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-                mapped_at_creation: false,
-            })
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Point Cloud Bind Group"),
+            layout: camera_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
         });
 
         Self {
-            buffers_3d,
-            buffers_2d,
-            current_buffer: 0,
+            buffer_3d,
+            uniform_buffer,
+            bind_group,
+            histogram: Histogram2D::new(device, histogram_pipelines),
             points_count_3d: 0,
-            points_count_2d: 0,
         }
     }
 
-    pub fn upload_3d(&mut self, queue: &wgpu::Queue, points: &[f32]) {
-        if points.is_empty() {
+    /// Writes consecutive points starting at point `first`.
+    pub fn write_3d(&self, queue: &wgpu::Queue, first: usize, points: &[KeyedPoint]) {
+        let end = (first + points.len()).min(MAX_POINTS_PER_BUFFER);
+        if end <= first {
             return;
         }
 
-        let next_buffer = (self.current_buffer + 1) % NUM_BUFFERS;
-        let point_count = points.len() / 3;
-        let point_count = point_count.min(MAX_POINTS_PER_BUFFER);
-
-        let byte_len = point_count * std::mem::size_of::<Point3D>();
+        let stride = std::mem::size_of::<KeyedPoint>();
         queue.write_buffer(
-            &self.buffers_3d[next_buffer],
-            0,
-            &bytemuck::cast_slice(points)[..byte_len],
+            &self.buffer_3d,
+            (first * stride) as u64,
+            &bytemuck::cast_slice(points)[..(end - first) * stride],
         );
-
-        self.current_buffer = next_buffer;
-        self.points_count_3d = point_count;
     }
 
-    pub fn upload_2d(&mut self, queue: &wgpu::Queue, points: &[f32]) {
-        if points.is_empty() {
-            return;
-        }
-
-        let next_buffer = (self.current_buffer + 1) % NUM_BUFFERS;
-        let point_count = points.len() / 3;
-        let point_count = point_count.min(MAX_POINTS_PER_BUFFER);
-
-        let byte_len = point_count * std::mem::size_of::<Point2D>();
-        queue.write_buffer(
-            &self.buffers_2d[next_buffer],
-            0,
-            &bytemuck::cast_slice(points)[..byte_len],
-        );
+    pub fn set_points_count_3d(&mut self, count: usize) {
+        self.points_count_3d = count.min(MAX_POINTS_PER_BUFFER);
+    }
 
-        self.current_buffer = next_buffer;
-        self.points_count_2d = point_count;
+    pub fn buffer_3d(&self) -> &wgpu::Buffer {
+        &self.buffer_3d
     }
 
-    pub fn current_3d_buffer(&self) -> &wgpu::Buffer {
-        &self.buffers_3d[self.current_buffer]
+    pub fn set_style(&self, queue: &wgpu::Queue, colormap: Option<Colormap>, keys: KeyScale) {
+        let uniform = PointUniform::new(colormap, keys);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
     }
 
-    pub fn current_2d_buffer(&self) -> &wgpu::Buffer {
-        &self.buffers_2d[self.current_buffer]
+    /// Replaces the camera bind group when drawing these points.
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
     }
 
     pub fn points_count_3d(&self) -> u32 {
         self.points_count_3d as u32
     }
-
-    pub fn points_count_2d(&self) -> u32 {
-        self.points_count_2d as u32
-    }
 }
 
 pub fn point_3d_layout() -> wgpu::VertexBufferLayout<'static> {
     wgpu::VertexBufferLayout {
         array_stride: std::mem::size_of::<Point3D>() as wgpu::BufferAddress,
         step_mode: wgpu::VertexStepMode::Vertex,
-        attributes: &[wgpu::VertexAttribute {
-            offset: 0,
-            shader_location: 0,
-            format: wgpu::VertexFormat::Float32x3,
-        }],
+        attributes: &[
+            wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x3,
+            },
+            wgpu::VertexAttribute {
+                offset: 12,
+                shader_location: 1,
+                format: wgpu::VertexFormat::Float32,
+            },
+        ],
     }
 }
 
-pub fn point_2d_layout() -> wgpu::VertexBufferLayout<'static> {
+pub fn keyed_point_layout() -> wgpu::VertexBufferLayout<'static> {
     wgpu::VertexBufferLayout {
-        array_stride: std::mem::size_of::<Point2D>() as wgpu::BufferAddress,
+        array_stride: std::mem::size_of::<KeyedPoint>() as wgpu::BufferAddress,
         step_mode: wgpu::VertexStepMode::Vertex,
         attributes: &[
             wgpu::VertexAttribute {
                 offset: 0,
                 shader_location: 0,
-                format: wgpu::VertexFormat::Float32x2,
+                format: wgpu::VertexFormat::Float32x3,
             },
             wgpu::VertexAttribute {
-                offset: 8,
+                offset: 12,
                 shader_location: 1,
-                format: wgpu::VertexFormat::Float32,
+                format: wgpu::VertexFormat::Uint32,
             },
         ],
     }
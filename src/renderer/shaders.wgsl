@@ -7,8 +7,20 @@ struct CameraUniform {
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
+// Generator points carry integer keys; `KeyScale` on the Rust side.
+struct PointUniform {
+    colormap: u32,
+    key_base: u32,
+    key_span: u32,
+    key_period: u32,
+}
+
+@group(0) @binding(2)
+var<uniform> point_uniform: PointUniform;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
+    @location(1) value: f32,
 }
 
 struct VertexOutput {
@@ -17,65 +29,111 @@ struct VertexOutput {
     @location(1) point_size: f32,
 }
 
+struct KeyedPointInput {
+    @location(0) position: vec3<f32>,
+    @location(1) key: u32,
+}
+
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
+    return point_vertex(in.position, in.value);
+}
+
+@vertex
+fn vs_points(in: KeyedPointInput) -> VertexOutput {
+    let offset = in.key - point_uniform.key_base;
+    let period = point_uniform.key_period;
+    var value: f32;
+    if period > 0u {
+        value = f32(offset % period) / f32(max(period - 1u, 1u));
+    } else {
+        value = f32(offset) / f32(max(point_uniform.key_span, 1u));
+    }
+    return point_vertex(in.position, value);
+}
+
+fn point_vertex(position: vec3<f32>, value: f32) -> VertexOutput {
     var out: VertexOutput;
 
-    let world_pos = vec4<f32>(in.position, 1.0);
+    let world_pos = vec4<f32>(position, 1.0);
     out.clip_position = camera.view_proj * world_pos;
 
-    let dist = distance(camera.camera_pos, in.position);
+    let dist = distance(camera.camera_pos, position);
     out.point_size = clamp(8.0 / (dist * 0.1 + 1.0), 1.0, 8.0);
 
-    let normalized = (in.position + vec3<f32>(1.0)) * 0.5;
-    out.color = vec3<f32>(
-        normalized.x * 0.6 + 0.2,
-        normalized.y * 0.4 + 0.3,
-        normalized.z * 0.8 + 0.2
-    );
+    if point_uniform.colormap == 0u {
+        let normalized = (position + vec3<f32>(1.0)) * 0.5;
+        out.color = vec3<f32>(
+            normalized.x * 0.6 + 0.2,
+            normalized.y * 0.4 + 0.3,
+            normalized.z * 0.8 + 0.2
+        );
+    } else {
+        out.color = apply_colormap(point_uniform.colormap, value);
+    }
 
     return out;
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color, 1.0);
-}
-
-struct Vertex2DInput {
-    @location(0) position: vec2<f32>,
-    @location(1) value: f32,
+// Ids match `Colormap` on the Rust side.
+fn apply_colormap(id: u32, t: f32) -> vec3<f32> {
+    switch id {
+        case 2u: { return magma(t); }
+        case 3u: { return turbo(t); }
+        case 4u: { return cyclic_colormap(t); }
+        default: { return viridis(t); }
+    }
 }
 
-struct Vertex2DOutput {
-    @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec3<f32>,
-}
+fn magma(t: f32) -> vec3<f32> {
+    let c0 = vec3<f32>(0.001, 0.000, 0.014);
+    let c1 = vec3<f32>(0.079, 0.054, 0.212);
+    let c2 = vec3<f32>(0.232, 0.060, 0.438);
+    let c3 = vec3<f32>(0.390, 0.100, 0.502);
+    let c4 = vec3<f32>(0.550, 0.161, 0.506);
+    let c5 = vec3<f32>(0.716, 0.215, 0.475);
+    let c6 = vec3<f32>(0.869, 0.288, 0.409);
+    let c7 = vec3<f32>(0.968, 0.440, 0.360);
+    let c8 = vec3<f32>(0.987, 0.991, 0.750);
 
-@vertex
-fn vs_2d_main(in: Vertex2DInput) -> Vertex2DOutput {
-    var out: Vertex2DOutput;
+    let s = clamp(t, 0.0, 1.0) * 8.0;
+    let i = floor(s);
+    let f = fract(s);
 
-    out.clip_position = vec4<f32>(in.position, 0.0, 1.0);
+    if i < 1.0 { return mix(c0, c1, f); }
+    if i < 2.0 { return mix(c1, c2, f); }
+    if i < 3.0 { return mix(c2, c3, f); }
+    if i < 4.0 { return mix(c3, c4, f); }
+    if i < 5.0 { return mix(c4, c5, f); }
+    if i < 6.0 { return mix(c5, c6, f); }
+    if i < 7.0 { return mix(c6, c7, f); }
+    return mix(c7, c8, f);
+}
 
-    let v = in.value;
-    out.color = vec3<f32>(
-        v * 0.8 + 0.1,
-        v * 0.5 + 0.2,
-        v * 0.9 + 0.1
-    );
+// Polynomial fit of Google's Turbo.
+fn turbo(t: f32) -> vec3<f32> {
+    let x = clamp(t, 0.0, 1.0);
+    let r = 0.13572138 + x * (4.61539260 + x * (-42.66032258 + x * (132.13108234 + x * (-152.94239396 + x * 59.28637943))));
+    let g = 0.09140261 + x * (2.19418839 + x * (4.84296658 + x * (-14.18503333 + x * (4.27729857 + x * 2.82956604))));
+    let b = 0.10667330 + x * (12.64194608 + x * (-60.58204836 + x * (110.36276771 + x * (-89.90310912 + x * 27.34824973))));
+    return clamp(vec3<f32>(r, g, b), vec3<f32>(0.0), vec3<f32>(1.0));
+}
 
-    return out;
+// Hue wheel, so 0 and 1 get the same colour.
+fn cyclic_colormap(t: f32) -> vec3<f32> {
+    let phase = 6.2831853 * (fract(t) + vec3<f32>(0.0, 0.6666667, 0.3333333));
+    return 0.5 + 0.45 * cos(phase);
 }
 
 @fragment
-fn fs_2d_main(in: Vertex2DOutput) -> @location(0) vec4<f32> {
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return vec4<f32>(in.color, 1.0);
 }
 
 struct SurfaceVertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
+    @location(2) color: vec3<f32>,
 }
 
 struct SurfaceVertexOutput {
@@ -83,12 +141,13 @@ struct SurfaceVertexOutput {
     @location(0) world_pos: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) height: f32,
+    @location(3) color: vec3<f32>,
 }
 
 struct SurfaceUniforms {
     z_min: f32,
     z_max: f32,
-    _pad1: f32,
+    vertex_colors: u32,
     _pad2: f32,
 }
 
@@ -128,6 +187,7 @@ fn vs_surface_main(in: SurfaceVertexInput) -> SurfaceVertexOutput {
     out.world_pos = in.position;
     out.normal = normalize(in.normal);
     out.height = in.position.y;
+    out.color = in.color;
 
     return out;
 }
@@ -146,7 +206,10 @@ fn fs_surface_main(in: SurfaceVertexOutput) -> @location(0) vec4<f32> {
 
     let z_range = surface_uniforms.z_max - surface_uniforms.z_min;
     let t = clamp((in.height - surface_uniforms.z_min) / max(z_range, 0.001), 0.0, 1.0);
-    let base_color = viridis(t);
+    var base_color = viridis(t);
+    if surface_uniforms.vertex_colors != 0u {
+        base_color = in.color;
+    }
 
     let lighting = ambient + diffuse;
     let final_color = base_color * lighting + vec3<f32>(specular);
@@ -181,6 +244,50 @@ fn fs_curve_main(in: CurveVertexOutput) -> @location(0) vec4<f32> {
     return vec4<f32>(color, 1.0);
 }
 
+struct ArrowVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) origin: vec3<f32>,
+    @location(3) direction: vec3<f32>,
+    @location(4) length: f32,
+    @location(5) magnitude: f32,
+}
+
+struct ArrowVertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec3<f32>,
+}
+
+// The glyph points along +y; turn it onto the instance's direction and scale it.
+@vertex
+fn vs_arrow_main(in: ArrowVertexInput) -> ArrowVertexOutput {
+    var out: ArrowVertexOutput;
+
+    let axis = normalize(in.direction);
+    var helper = vec3<f32>(1.0, 0.0, 0.0);
+    if abs(axis.x) > 0.9 {
+        helper = vec3<f32>(0.0, 0.0, 1.0);
+    }
+    let side = normalize(cross(axis, helper));
+    let front = cross(side, axis);
+    let basis = mat3x3<f32>(side, axis, front);
+
+    let world_pos = in.origin + basis * in.position * in.length;
+    out.clip_position = camera.view_proj * vec4<f32>(world_pos, 1.0);
+    out.normal = basis * in.normal;
+    out.color = apply_colormap(point_uniform.colormap, in.magnitude);
+
+    return out;
+}
+
+@fragment
+fn fs_arrow_main(in: ArrowVertexOutput) -> @location(0) vec4<f32> {
+    let light_dir = normalize(vec3<f32>(0.5, 1.0, 0.3));
+    let diffuse = abs(dot(normalize(in.normal), light_dir)) * 0.7;
+    return vec4<f32>(in.color * (0.3 + diffuse), 1.0);
+}
+
 struct GridVertexInput {
     @location(0) position: vec3<f32>,
 }
@@ -213,6 +320,14 @@ fn fs_grid_main(in: GridVertexOutput) -> @location(0) vec4<f32> {
     return in.color;
 }
 
+@vertex
+fn vs_overlay_main(in: GridVertexInput) -> GridVertexOutput {
+    var out: GridVertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(in.position, 1.0);
+    out.color = vec4<f32>(1.0, 0.62, 0.2, 0.7);
+    return out;
+}
+
 struct Math2DVertexInput {
     @location(0) position: vec2<f32>,
     @location(1) value: f32,
@@ -231,11 +346,44 @@ fn vs_math_2d_main(in: Math2DVertexInput) -> Math2DVertexOutput {
     return out;
 }
 
+struct Domain2DVertexInput {
+    @location(0) position: vec2<f32>,
+    @location(2) color: vec3<f32>,
+}
+
+@vertex
+fn vs_domain_2d_main(in: Domain2DVertexInput) -> Math2DVertexOutput {
+    var out: Math2DVertexOutput;
+    out.clip_position = vec4<f32>(in.position, 0.0, 1.0);
+    out.color = in.color;
+    return out;
+}
+
 @fragment
 fn fs_math_2d_main(in: Math2DVertexOutput) -> @location(0) vec4<f32> {
     return vec4<f32>(in.color, 1.0);
 }
 
+// Diverging map for probabilities and z-scores: 0.5 is dark, low is blue, high is red.
+fn bias_colormap(t: f32) -> vec3<f32> {
+    let low = vec3<f32>(0.230, 0.480, 0.980);
+    let mid = vec3<f32>(0.080, 0.080, 0.100);
+    let high = vec3<f32>(0.980, 0.300, 0.200);
+    let s = clamp(t, 0.0, 1.0);
+    if s < 0.5 {
+        return mix(low, mid, s * 2.0);
+    }
+    return mix(mid, high, (s - 0.5) * 2.0);
+}
+
+@vertex
+fn vs_bias_main(in: Math2DVertexInput) -> Math2DVertexOutput {
+    var out: Math2DVertexOutput;
+    out.clip_position = vec4<f32>(in.position, 0.0, 1.0);
+    out.color = bias_colormap(in.value);
+    return out;
+}
+
 struct Curve2DVertexInput {
     @location(0) position: vec2<f32>,
 }
@@ -258,3 +406,285 @@ fn fs_curve_2d_main(in: Curve2DVertexOutput) -> @location(0) vec4<f32> {
     let color = viridis(in.t_value);
     return vec4<f32>(color, 1.0);
 }
+
+// Density rendering: points add 1 to an R32Float target, the compute passes
+// gather the max and a histogram of log(1 + hits), and the tone map colours it.
+
+struct DensityUniform {
+    tone_map: u32,
+    colormap: u32,
+    _pad1: u32,
+    _pad2: u32,
+}
+
+struct DensityStats {
+    max_bits: atomic<u32>,
+    covered: atomic<u32>,
+    _pad1: u32,
+    _pad2: u32,
+    histogram: array<atomic<u32>, 256>,
+    cdf: array<f32, 256>,
+}
+
+// Same buffer as `DensityStats`, read by the tone map.
+struct DensityLevels {
+    max_bits: u32,
+    covered: u32,
+    _pad1: u32,
+    _pad2: u32,
+    histogram: array<u32, 256>,
+    cdf: array<f32, 256>,
+}
+
+@group(0) @binding(3)
+var density_texture: texture_2d<f32>;
+
+@group(0) @binding(4)
+var<storage, read_write> density_stats: DensityStats;
+
+@group(0) @binding(5)
+var<storage, read> density_levels: DensityLevels;
+
+@group(0) @binding(6)
+var<uniform> density_uniform: DensityUniform;
+
+@vertex
+fn vs_density_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return camera.view_proj * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_density_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+
+// Without float blending the points are counted in atomic cells instead, then copied
+// into the target, using the same projection as the rasterizer.
+
+struct SplatUniform {
+    viewport: vec4<f32>,
+    size: vec2<u32>,
+    points: u32,
+    _pad: u32,
+}
+
+@group(0) @binding(13)
+var<storage, read> splat_points: array<vec4<f32>>;
+
+@group(0) @binding(14)
+var<storage, read_write> splat_counts: array<atomic<u32>>;
+
+@group(0) @binding(15)
+var<uniform> splat: SplatUniform;
+
+@group(0) @binding(16)
+var splat_target: texture_storage_2d<r32float, write>;
+
+@compute @workgroup_size(256)
+fn cs_density_splat(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= splat.points {
+        return;
+    }
+    let clip = camera.view_proj * vec4<f32>(splat_points[id.x].xyz, 1.0);
+    if clip.w <= 0.0 {
+        return;
+    }
+    let ndc = clip.xyz / clip.w;
+    if any(abs(ndc.xy) > vec2<f32>(1.0)) || ndc.z < 0.0 || ndc.z > 1.0 {
+        return;
+    }
+    let pixel = splat.viewport.xy + vec2<f32>(ndc.x + 1.0, 1.0 - ndc.y) * 0.5 * splat.viewport.zw;
+    let cell = min(vec2<u32>(pixel), splat.size - 1u);
+    atomicAdd(&splat_counts[cell.y * splat.size.x + cell.x], 1u);
+}
+
+@compute @workgroup_size(16, 16)
+fn cs_density_resolve(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= splat.size.x || id.y >= splat.size.y {
+        return;
+    }
+    let hits = atomicLoad(&splat_counts[id.y * splat.size.x + id.x]);
+    textureStore(splat_target, vec2<i32>(id.xy), vec4<f32>(f32(hits), 0.0, 0.0, 0.0));
+}
+
+fn density_level(hits: f32, max_hits: f32) -> f32 {
+    return log(1.0 + hits) / log(1.0 + max(max_hits, 1.0));
+}
+
+fn density_bin(level: f32) -> u32 {
+    return min(u32(level * 256.0), 255u);
+}
+
+fn density_hits(id: vec3<u32>) -> f32 {
+    let size = textureDimensions(density_texture);
+    if id.x >= size.x || id.y >= size.y {
+        return 0.0;
+    }
+    return textureLoad(density_texture, vec2<i32>(id.xy), 0).r;
+}
+
+fn record_max(hits: f32) {
+    if hits > 0.0 {
+        // Positive floats order the same as their bit patterns.
+        atomicMax(&density_stats.max_bits, bitcast<u32>(hits));
+        atomicAdd(&density_stats.covered, 1u);
+    }
+}
+
+fn record_level(hits: f32) {
+    if hits > 0.0 {
+        let max_hits = bitcast<f32>(atomicLoad(&density_stats.max_bits));
+        atomicAdd(&density_stats.histogram[density_bin(density_level(hits, max_hits))], 1u);
+    }
+}
+
+@compute @workgroup_size(16, 16)
+fn cs_density_max(@builtin(global_invocation_id) id: vec3<u32>) {
+    record_max(density_hits(id));
+}
+
+@compute @workgroup_size(16, 16)
+fn cs_density_histogram(@builtin(global_invocation_id) id: vec3<u32>) {
+    record_level(density_hits(id));
+}
+
+@compute @workgroup_size(1)
+fn cs_density_cdf() {
+    let covered = f32(max(atomicLoad(&density_stats.covered), 1u));
+    var total = 0u;
+    for (var i = 0u; i < 256u; i++) {
+        total += atomicLoad(&density_stats.histogram[i]);
+        density_stats.cdf[i] = f32(total) / covered;
+    }
+}
+
+@vertex
+fn vs_fullscreen_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn tone_map_color(hits: f32, tone_map: u32, colormap: u32) -> vec4<f32> {
+    if hits <= 0.0 {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    let level = density_level(hits, bitcast<f32>(density_levels.max_bits));
+    var t = level;
+    if tone_map == 1u {
+        t = density_levels.cdf[density_bin(level)];
+    }
+    return vec4<f32>(apply_colormap(colormap, t), 1.0);
+}
+
+@fragment
+fn fs_tonemap_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let hits = textureLoad(density_texture, vec2<i32>(pos.xy), 0).r;
+    return tone_map_color(hits, density_uniform.tone_map, density_uniform.colormap);
+}
+
+// 2D histogram: incoming x/y pairs are binned into a persistent grid of counts,
+// which shares the density stats and tone map above.
+
+struct HistogramUniform {
+    bounds: vec4<f32>,
+    grid: u32,
+    points: u32,
+    tone_map: u32,
+    colormap: u32,
+}
+
+@group(0) @binding(7)
+var<storage, read_write> histogram_counts: array<atomic<u32>>;
+
+@group(0) @binding(8)
+var<uniform> histogram: HistogramUniform;
+
+// Same buffer as `histogram_counts`, read by the display pass.
+@group(0) @binding(9)
+var<storage, read> histogram_cells: array<u32>;
+
+@group(0) @binding(10)
+var<storage, read> histogram_points: array<vec2<f32>>;
+
+@compute @workgroup_size(256)
+fn cs_histogram_bin(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= histogram.points {
+        return;
+    }
+    let uv = (histogram_points[id.x] - histogram.bounds.xy) / (histogram.bounds.zw - histogram.bounds.xy);
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) {
+        return;
+    }
+    let cell = min(vec2<u32>(uv * f32(histogram.grid)), vec2<u32>(histogram.grid - 1u));
+    atomicAdd(&histogram_counts[cell.y * histogram.grid + cell.x], 1u);
+}
+
+fn histogram_hits(id: vec3<u32>) -> f32 {
+    if id.x >= histogram.grid || id.y >= histogram.grid {
+        return 0.0;
+    }
+    return f32(atomicLoad(&histogram_counts[id.y * histogram.grid + id.x]));
+}
+
+@compute @workgroup_size(16, 16)
+fn cs_histogram_max(@builtin(global_invocation_id) id: vec3<u32>) {
+    record_max(histogram_hits(id));
+}
+
+@compute @workgroup_size(16, 16)
+fn cs_histogram_levels(@builtin(global_invocation_id) id: vec3<u32>) {
+    record_level(histogram_hits(id));
+}
+
+struct HistogramVertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// Two triangles over the central 90% of the viewport, uv (0, 0) at the bottom left.
+@vertex
+fn vs_histogram_main(@builtin(vertex_index) idx: u32) -> HistogramVertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 0.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(0.0, 1.0)
+    );
+    var out: HistogramVertexOutput;
+    out.uv = corners[idx];
+    out.clip_position = vec4<f32>((out.uv * 2.0 - 1.0) * 0.9, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_histogram_main(in: HistogramVertexOutput) -> @location(0) vec4<f32> {
+    let cell = min(vec2<u32>(in.uv * f32(histogram.grid)), vec2<u32>(histogram.grid - 1u));
+    let hits = f32(histogram_cells[cell.y * histogram.grid + cell.x]);
+    return tone_map_color(hits, histogram.tone_map, histogram.colormap);
+}
+
+// Escape-time fractals: smooth iteration counts from the CPU, one texel per pixel,
+// negative inside the set.
+
+struct FractalUniform {
+    size: vec2<f32>,
+    screen: vec2<f32>,
+    colormap: u32,
+    cycle: f32,
+}
+
+@group(0) @binding(11)
+var<uniform> fractal: FractalUniform;
+
+@group(0) @binding(12)
+var fractal_texture: texture_2d<f32>;
+
+// The image is stretched over the screen until a render at the new size replaces it.
+@fragment
+fn fs_fractal_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let texel = min(vec2<u32>(pos.xy * fractal.size / fractal.screen), vec2<u32>(fractal.size) - 1u);
+    let n = textureLoad(fractal_texture, texel, 0).r;
+    if n < 0.0 {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    return vec4<f32>(apply_colormap(fractal.colormap, fract(n / fractal.cycle)), 1.0);
+}
@@ -0,0 +1,167 @@
+use aelys::Value;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::rng::job::BackgroundJob;
+use crate::rng::program::{RngProgram, splitmix64};
+
+const PROGRESS_INTERVAL: u64 = 256;
+
+/// How often flipping input bit `i` of state word 0 flips output bit `j`.
+#[derive(Clone)]
+pub struct AvalancheMatrix {
+    pub bits: u32,
+    pub trials: u64,
+    /// `flips[i * bits + j]`
+    flips: Vec<u64>,
+}
+
+impl AvalancheMatrix {
+    fn new(bits: u32) -> Self {
+        let bits = bits.clamp(1, 64);
+        Self {
+            bits,
+            trials: 0,
+            flips: vec![0; (bits * bits) as usize],
+        }
+    }
+
+    pub fn probability(&self, input: usize, output: usize) -> f64 {
+        if self.trials == 0 {
+            return 0.5;
+        }
+        self.flips[input * self.bits as usize + output] as f64 / self.trials as f64
+    }
+
+    /// Largest |p - 1/2| as `(input bit, output bit, bias)`.
+    pub fn worst_bias(&self) -> (usize, usize, f64) {
+        let bits = self.bits as usize;
+        let mut worst = (0, 0, 0.0);
+        for i in 0..bits {
+            for j in 0..bits {
+                let bias = (self.probability(i, j) - 0.5).abs();
+                if bias > worst.2 {
+                    worst = (i, j, bias);
+                }
+            }
+        }
+        worst
+    }
+
+    pub fn mean_bias(&self) -> f64 {
+        let bits = self.bits as usize;
+        let total: f64 = (0..bits * bits)
+            .map(|k| (self.probability(k / bits, k % bits) - 0.5).abs())
+            .sum();
+        total / (bits * bits) as f64
+    }
+
+    /// Flip probabilities as `(columns, rows, values)`: one row per input bit, one
+    /// column per output bit, MSB first on both axes.
+    pub fn grid(&self) -> (usize, usize, Vec<f32>) {
+        let bits = self.bits as usize;
+        let values = (0..bits)
+            .rev()
+            .flat_map(|i| (0..bits).rev().map(move |j| (i, j)))
+            .map(|(i, j)| self.probability(i, j) as f32)
+            .collect();
+        (bits, bits, values)
+    }
+}
+
+#[derive(Clone, Default)]
+pub enum AvalancheStatus {
+    #[default]
+    Idle,
+    Running(AvalancheMatrix),
+    Done(AvalancheMatrix),
+    Error(String),
+}
+
+impl AvalancheStatus {
+    pub fn matrix(&self) -> Option<&AvalancheMatrix> {
+        match self {
+            AvalancheStatus::Running(m) | AvalancheStatus::Done(m) => Some(m),
+            _ => None,
+        }
+    }
+}
+
+/// Strict avalanche analysis of the state transition.
+pub struct AvalancheAnalyzer {
+    job: BackgroundJob<AvalancheStatus>,
+}
+
+impl AvalancheAnalyzer {
+    pub fn new(status: Arc<Mutex<AvalancheStatus>>) -> Self {
+        Self {
+            job: BackgroundJob::new(status),
+        }
+    }
+
+    /// Flips each of the low `bits` bits of word 0 over `trials` random states.
+    pub fn start(&mut self, code: &str, bits: u32, trials: u64, seed: i64) {
+        let code = code.to_string();
+        let initial = AvalancheStatus::Running(AvalancheMatrix::new(bits));
+        self.job.start(initial, move |cancel, status| {
+            match analyze(&code, bits, trials, seed, cancel, status) {
+                Ok(matrix) => AvalancheStatus::Done(matrix),
+                Err(e) => AvalancheStatus::Error(e),
+            }
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        self.job.cancel();
+    }
+}
+
+fn analyze(
+    code: &str,
+    bits: u32,
+    trials: u64,
+    seed: i64,
+    cancel: &AtomicBool,
+    status: &Mutex<AvalancheStatus>,
+) -> Result<AvalancheMatrix, String> {
+    let mut program = RngProgram::compile(code)?;
+    let mut matrix = AvalancheMatrix::new(bits);
+    let bits = matrix.bits as usize;
+    let mask = if bits >= 64 { u64::MAX } else { (1u64 << bits) - 1 };
+
+    let mut mix = seed as u64;
+    let mut flipped: Vec<Value> = Vec::with_capacity(program.width());
+
+    for trial in 0..trials {
+        // Word 0 is drawn within the output width; other words keep the usual seeding.
+        let mut base = program.seed_state(splitmix64(&mut mix) as i64);
+        let word = splitmix64(&mut mix) & mask;
+        base[0] = Value::int(word as i64);
+        let start = base.clone();
+        let reference = program.step(&mut base)? as u64;
+
+        for i in 0..bits {
+            flipped.clear();
+            flipped.extend_from_slice(&start);
+            flipped[0] = Value::int((word ^ (1u64 << i)) as i64);
+            let output = program.step(&mut flipped)? as u64;
+
+            let mut diff = (output ^ reference) & mask;
+            while diff != 0 {
+                matrix.flips[i * bits + diff.trailing_zeros() as usize] += 1;
+                diff &= diff - 1;
+            }
+        }
+        matrix.trials += 1;
+
+        if (trial + 1).is_multiple_of(PROGRESS_INTERVAL) {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(matrix);
+            }
+            *status.lock() = AvalancheStatus::Running(matrix.clone());
+        }
+    }
+
+    Ok(matrix)
+}
@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+
+pub const BIT_ROWS: usize = 256;
+const MAX_BITS: usize = 64;
+/// z-scores beyond this saturate the colour scale.
+const Z_RANGE: f64 = 4.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BitPlot {
+    /// One row per output, newest at the top.
+    Raster,
+    Frequency,
+    Correlation,
+    /// Flip probabilities from the avalanche analysis, not the live stream.
+    Avalanche,
+}
+
+/// Per-bit counts over every output since the last reset, plus the most recent rows.
+#[derive(Clone)]
+pub struct BitSnapshot {
+    pub bits: u32,
+    /// Newest first, masked to `bits`.
+    pub rows: Vec<u64>,
+    pub samples: u64,
+    ones: [u64; MAX_BITS],
+    /// `pairs[i * 64 + j]` counts outputs where bits i and j are both set.
+    pairs: Vec<u64>,
+    /// Same, with bit i taken from the previous output and bit j from the current one.
+    lagged: Vec<u64>,
+    lagged_samples: u64,
+    lagged_prev_ones: [u64; MAX_BITS],
+    lagged_ones: [u64; MAX_BITS],
+}
+
+impl BitSnapshot {
+    fn new(bits: u32) -> Self {
+        Self {
+            bits: bits.clamp(1, MAX_BITS as u32),
+            rows: Vec::new(),
+            samples: 0,
+            ones: [0; MAX_BITS],
+            pairs: vec![0; MAX_BITS * MAX_BITS],
+            lagged: vec![0; MAX_BITS * MAX_BITS],
+            lagged_samples: 0,
+            lagged_prev_ones: [0; MAX_BITS],
+            lagged_ones: [0; MAX_BITS],
+        }
+    }
+
+    /// Deviation of bit `i` from probability 1/2, in standard deviations.
+    pub fn frequency_z(&self, i: usize) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        let n = self.samples as f64;
+        (self.ones[i] as f64 - n / 2.0) / (n / 4.0).sqrt()
+    }
+
+    /// Phi coefficient between bits `i` and `j` scaled by sqrt(n), so independence
+    /// gives roughly a standard normal. With `lag = 1`, bit `i` is from the previous output.
+    pub fn correlation_z(&self, i: usize, j: usize, lag: usize) -> f64 {
+        let (n, n11, ni, nj) = if lag == 0 {
+            (self.samples, self.pairs[i * MAX_BITS + j], self.ones[i], self.ones[j])
+        } else {
+            (
+                self.lagged_samples,
+                self.lagged[i * MAX_BITS + j],
+                self.lagged_prev_ones[i],
+                self.lagged_ones[j],
+            )
+        };
+        if n == 0 {
+            return 0.0;
+        }
+        let n = n as f64;
+        let (n11, ni, nj) = (n11 as f64, ni as f64, nj as f64);
+        let denom = (ni * (n - ni) * nj * (n - nj)).sqrt();
+        if denom == 0.0 {
+            return 0.0;
+        }
+        (n11 * n - ni * nj) / denom * n.sqrt()
+    }
+
+    /// Cell values in `[0, 1]` as `(columns, rows, values)`, row-major from the top.
+    /// Bits run MSB to LSB left to right; statistics map z = 0 to 0.5.
+    pub fn grid(&self, plot: BitPlot, lag: usize) -> (usize, usize, Vec<f32>) {
+        let bits = self.bits as usize;
+        let z_to_unit = |z: f64| (0.5 + z / (2.0 * Z_RANGE)).clamp(0.0, 1.0) as f32;
+        match plot {
+            BitPlot::Raster => {
+                let values = self
+                    .rows
+                    .iter()
+                    .flat_map(|&row| (0..bits).rev().map(move |i| ((row >> i) & 1) as f32))
+                    .collect();
+                (bits, self.rows.len(), values)
+            }
+            BitPlot::Frequency => {
+                let values = (0..bits).rev().map(|i| z_to_unit(self.frequency_z(i))).collect();
+                (bits, 1, values)
+            }
+            BitPlot::Correlation => {
+                let values = (0..bits)
+                    .rev()
+                    .flat_map(|i| (0..bits).rev().map(move |j| (i, j)))
+                    .map(|(i, j)| {
+                        // A bit is trivially correlated with itself.
+                        if lag == 0 && i == j {
+                            0.5
+                        } else {
+                            z_to_unit(self.correlation_z(i, j, lag))
+                        }
+                    })
+                    .collect();
+                (bits, bits, values)
+            }
+            BitPlot::Avalanche => (0, 0, Vec::new()),
+        }
+    }
+}
+
+/// Accumulates bit statistics on the generator thread while the bit view is open.
+pub struct BitAccumulator {
+    snapshot: BitSnapshot,
+    rows: VecDeque<u64>,
+    prev: Option<u64>,
+}
+
+impl BitAccumulator {
+    pub fn new(bits: u32) -> Self {
+        Self {
+            snapshot: BitSnapshot::new(bits),
+            rows: VecDeque::with_capacity(BIT_ROWS),
+            prev: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.snapshot.bits);
+    }
+
+    pub fn push(&mut self, raw: i64) {
+        let s = &mut self.snapshot;
+        let mask = if s.bits >= 64 { u64::MAX } else { (1u64 << s.bits) - 1 };
+        let v = raw as u64 & mask;
+
+        if self.rows.len() == BIT_ROWS {
+            self.rows.pop_back();
+        }
+        self.rows.push_front(v);
+
+        s.samples += 1;
+        for_each_bit(v, |i| {
+            s.ones[i] += 1;
+            for_each_bit(v, |j| s.pairs[i * MAX_BITS + j] += 1);
+        });
+
+        if let Some(prev) = self.prev {
+            s.lagged_samples += 1;
+            for_each_bit(v, |j| s.lagged_ones[j] += 1);
+            for_each_bit(prev, |i| {
+                s.lagged_prev_ones[i] += 1;
+                for_each_bit(v, |j| s.lagged[i * MAX_BITS + j] += 1);
+            });
+        }
+        self.prev = Some(v);
+    }
+
+    pub fn snapshot(&self) -> BitSnapshot {
+        let mut snapshot = self.snapshot.clone();
+        snapshot.rows = self.rows.iter().copied().collect();
+        snapshot
+    }
+}
+
+#[inline]
+fn for_each_bit(mut v: u64, mut f: impl FnMut(usize)) {
+    while v != 0 {
+        f(v.trailing_zeros() as usize);
+        v &= v - 1;
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Embedding {
+    /// (x_n, x_{n+1}, x_{n+2}) from three fresh outputs per point.
+    Triples,
+    /// Overlapping (x_n, x_{n+k}, x_{n+2k}); every output starts a new point.
+    Lagged,
+    /// Overlapping (x_{n+1} - x_n, x_{n+2} - x_{n+1}, x_{n+3} - x_{n+2}).
+    Differences,
+}
+
+/// Turns the raw output stream into 3D points according to an [`Embedding`].
+pub struct Embedder {
+    mode: Embedding,
+    lag: usize,
+    window: VecDeque<i64>,
+}
+
+impl Embedder {
+    pub fn new(mode: Embedding, lag: usize) -> Self {
+        let lag = lag.max(1);
+        Self {
+            mode,
+            lag,
+            window: VecDeque::with_capacity(2 * lag + 1),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.window.clear();
+    }
+
+    /// Feeds one raw output and returns a point when one is complete.
+    pub fn push(&mut self, value: i64) -> Option<[i64; 3]> {
+        self.window.push_back(value);
+        match self.mode {
+            Embedding::Triples => {
+                if self.window.len() < 3 {
+                    return None;
+                }
+                let point = [self.window[0], self.window[1], self.window[2]];
+                self.window.clear();
+                Some(point)
+            }
+            Embedding::Lagged => {
+                if self.window.len() < 2 * self.lag + 1 {
+                    return None;
+                }
+                let point = [
+                    self.window[0],
+                    self.window[self.lag],
+                    self.window[2 * self.lag],
+                ];
+                self.window.pop_front();
+                Some(point)
+            }
+            Embedding::Differences => {
+                if self.window.len() < 4 {
+                    return None;
+                }
+                let w = &self.window;
+                let point = [
+                    w[1].wrapping_sub(w[0]),
+                    w[2].wrapping_sub(w[1]),
+                    w[3].wrapping_sub(w[2]),
+                ];
+                self.window.pop_front();
+                Some(point)
+            }
+        }
+    }
+}
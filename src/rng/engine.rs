@@ -1,14 +1,28 @@
-use aelys::{CallableFunction, VM, Value, get_function, new_vm, run_with_vm};
+use aelys::Value;
 use crossbeam::channel::{self, Receiver, Sender, TrySendError};
 use parking_lot::Mutex;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
 use std::thread::{self, JoinHandle};
 
+use crate::rng::avalanche::{AvalancheAnalyzer, AvalancheStatus};
+use crate::rng::bits::{BitAccumulator, BitSnapshot};
+use crate::rng::embedding::{Embedder, Embedding};
+use crate::rng::history::{History, HistoryEntry};
+use crate::rng::mapping::ValueMapping;
+use crate::rng::native::{NativeGenerator, NativeRng};
+use crate::rng::period::{PeriodFinder, PeriodStatus};
+use crate::rng::program::RngProgram;
+use crate::rng::stats::{StatsBattery, TestResult};
+use crate::rng::stream::{Pull, StreamSource, StreamSpec};
+
 const TARGET_BATCH_TIME_MS: f32 = 5.0;
 const MIN_BATCH_SIZE: usize = 1_000;
 const MAX_BATCH_SIZE: usize = 500_000;
+const INITIAL_BATCH_SIZE: usize = 10_000;
 const CHANNEL_CAPACITY: usize = 4;
+const DEFAULT_SEED: i64 = 12345;
+pub const MAX_STREAMS: usize = 64;
 
 pub struct AtomicBounds {
     pub min_x: AtomicI64,
@@ -43,12 +57,6 @@ impl AtomicBounds {
     }
 }
 
-#[inline(always)]
-fn normalize_value(value: i64, min: i64, max: i64) -> f32 {
-    let v = value.abs() % (max - min).max(1);
-    (min + v) as f32
-}
-
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
 pub enum Bottleneck {
     CpuRng,
@@ -58,10 +66,19 @@ pub enum Bottleneck {
     Balanced,
 }
 
+#[derive(Clone, Copy, Default)]
+pub struct StreamRate {
+    pub calls_per_sec: u64,
+    pub points_per_sec: u64,
+}
+
 #[derive(Default)]
 pub struct PerformanceStats {
+    /// Totals over every stream.
     pub rng_calls_per_sec: AtomicU64,
     pub points_generated_per_sec: AtomicU64,
+    /// One entry per parallel stream, stream 0 first.
+    pub streams: parking_lot::Mutex<Vec<StreamRate>>,
     pub avg_batch_time_ms: parking_lot::Mutex<f32>,
     pub current_batch_size: AtomicUsize,
     pub dropped_batches: AtomicU64,
@@ -94,58 +111,113 @@ impl PerformanceStats {
     }
 }
 
+/// Where a plotted point came from.
+#[derive(Clone, Copy)]
+pub struct PointOrigin {
+    /// Call index, since the last reseed, of the output that completed the point.
+    pub index: u64,
+    /// Raw outputs behind x, y and z.
+    pub raw: [i64; 3],
+    pub stream: u32,
+}
+
+/// Points from one stream, with one origin per point.
+pub struct PointBatch {
+    pub stream: usize,
+    /// `None` for external streams.
+    pub seed: Option<i64>,
+    /// Call index of the first output pulled into this batch.
+    pub first_index: u64,
+    pub points: Vec<f32>,
+    pub origins: Vec<PointOrigin>,
+}
+
+impl PointBatch {
+    fn new(stream: usize, seed: Option<i64>, first_index: u64, capacity: usize) -> Self {
+        Self {
+            stream,
+            seed,
+            first_index,
+            points: Vec::with_capacity(capacity * 3),
+            origins: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.origins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.origins.is_empty()
+    }
+}
+
 pub enum RngCommand {
     UpdateCode(String),
     Stop,
     Reset,
     SetSeed(i64),
+    SetEmbedding(Embedding, usize),
+    SetMapping(ValueMapping),
+    OpenStream(StreamSpec),
+    UseNative(&'static NativeGenerator),
+    SetBitView(bool),
+    RunAvalanche(u64),
+    SetStreams(usize),
+    SetHistory(bool),
     Pause,
     Resume,
+    /// Runs this many calls of stream 0 while paused.
+    Step(u64),
 }
 
-pub struct RngEngine {
-    tx_cmd: Sender<RngCommand>,
-    rx_points: Receiver<Vec<f32>>,
+/// Handles shared between the engine front-end and its generator thread.
+#[derive(Clone)]
+struct EngineShared {
     stats: Arc<PerformanceStats>,
     bounds: Arc<AtomicBounds>,
     paused: Arc<AtomicBool>,
-    thread_handle: Option<JoinHandle<()>>,
     last_error: Arc<Mutex<Option<String>>>,
+    test_results: Arc<Mutex<Vec<TestResult>>>,
+    period: Arc<Mutex<PeriodStatus>>,
+    bit_stats: Arc<Mutex<BitSnapshot>>,
+    avalanche: Arc<Mutex<AvalancheStatus>>,
+    history: Arc<Mutex<Arc<Vec<HistoryEntry>>>>,
+}
+
+pub struct RngEngine {
+    tx_cmd: Sender<RngCommand>,
+    rx_points: Receiver<PointBatch>,
+    shared: EngineShared,
+    thread_handle: Option<JoinHandle<()>>,
 }
 
 impl RngEngine {
     pub fn new() -> Self {
         let (tx_cmd, rx_cmd) = channel::unbounded::<RngCommand>();
-        let (tx_points, rx_points) = channel::bounded::<Vec<f32>>(CHANNEL_CAPACITY);
-        let stats = Arc::new(PerformanceStats::default());
-        let bounds = Arc::new(AtomicBounds::default());
-        let paused = Arc::new(AtomicBool::new(false));
-        let last_error = Arc::new(Mutex::new(None));
-
-        let stats_clone = Arc::clone(&stats);
-        let bounds_clone = Arc::clone(&bounds);
-        let paused_clone = Arc::clone(&paused);
-        let last_error_clone = Arc::clone(&last_error);
+        let (tx_points, rx_points) = channel::bounded::<PointBatch>(CHANNEL_CAPACITY);
+        let shared = EngineShared {
+            stats: Arc::new(PerformanceStats::default()),
+            bounds: Arc::new(AtomicBounds::default()),
+            paused: Arc::new(AtomicBool::new(false)),
+            last_error: Arc::new(Mutex::new(None)),
+            test_results: Arc::new(Mutex::new(Vec::new())),
+            period: Arc::new(Mutex::new(PeriodStatus::default())),
+            bit_stats: Arc::new(Mutex::new(BitAccumulator::new(32).snapshot())),
+            avalanche: Arc::new(Mutex::new(AvalancheStatus::default())),
+            history: Arc::new(Mutex::new(Arc::new(Vec::new()))),
+        };
 
+        let shared_clone = shared.clone();
         let thread_handle = thread::spawn(move || {
-            rng_thread(
-                rx_cmd,
-                tx_points,
-                stats_clone,
-                bounds_clone,
-                paused_clone,
-                last_error_clone,
-            );
+            rng_thread(rx_cmd, tx_points, shared_clone);
         });
 
         Self {
             tx_cmd,
             rx_points,
-            stats,
-            bounds,
-            paused,
+            shared,
             thread_handle: Some(thread_handle),
-            last_error,
         }
     }
 
@@ -161,38 +233,103 @@ impl RngEngine {
         let _ = self.tx_cmd.send(RngCommand::SetSeed(seed));
     }
 
+    pub fn set_embedding(&self, embedding: Embedding, lag: usize) {
+        let _ = self
+            .tx_cmd
+            .send(RngCommand::SetEmbedding(embedding, lag));
+    }
+
+    pub fn set_mapping(&self, mapping: ValueMapping) {
+        let _ = self.tx_cmd.send(RngCommand::SetMapping(mapping));
+    }
+
+    pub fn open_stream(&self, spec: StreamSpec) {
+        let _ = self.tx_cmd.send(RngCommand::OpenStream(spec));
+    }
+
+    pub fn use_native(&self, generator: &'static NativeGenerator) {
+        let _ = self.tx_cmd.send(RngCommand::UseNative(generator));
+    }
+
+    /// Bit statistics are only gathered while the bit view is showing.
+    pub fn set_bit_view(&self, enabled: bool) {
+        let _ = self.tx_cmd.send(RngCommand::SetBitView(enabled));
+    }
+
+    /// Runs the avalanche analysis on the current Aelys generator over `trials` random states.
+    pub fn run_avalanche(&self, trials: u64) {
+        let _ = self.tx_cmd.send(RngCommand::RunAvalanche(trials));
+    }
+
+    /// Runs `count` independent streams of the current Aelys or native generator, each
+    /// on its own thread. Tests and bit statistics only follow stream 0.
+    pub fn set_streams(&self, count: usize) {
+        let _ = self.tx_cmd.send(RngCommand::SetStreams(count));
+    }
+
     pub fn stop(&self) {
         let _ = self.tx_cmd.send(RngCommand::Stop);
     }
 
     pub fn pause(&self) {
-        self.paused.store(true, Ordering::Relaxed);
+        self.shared.paused.store(true, Ordering::Relaxed);
         let _ = self.tx_cmd.send(RngCommand::Pause);
     }
 
     pub fn resume(&self) {
-        self.paused.store(false, Ordering::Relaxed);
+        self.shared.paused.store(false, Ordering::Relaxed);
         let _ = self.tx_cmd.send(RngCommand::Resume);
     }
 
+    /// Advances stream 0 by `count` calls; only while paused.
+    pub fn step(&self, count: u64) {
+        let _ = self.tx_cmd.send(RngCommand::Step(count));
+    }
+
+    pub fn set_history(&self, enabled: bool) {
+        let _ = self.tx_cmd.send(RngCommand::SetHistory(enabled));
+    }
+
     pub fn is_paused(&self) -> bool {
-        self.paused.load(Ordering::Relaxed)
+        self.shared.paused.load(Ordering::Relaxed)
     }
 
-    pub fn try_recv_batch(&self) -> Option<Vec<f32>> {
+    pub fn try_recv_batch(&self) -> Option<PointBatch> {
         self.rx_points.try_recv().ok()
     }
 
     pub fn stats(&self) -> &Arc<PerformanceStats> {
-        &self.stats
+        &self.shared.stats
     }
 
     pub fn bounds(&self) -> &Arc<AtomicBounds> {
-        &self.bounds
+        &self.shared.bounds
     }
 
     pub fn last_error(&self) -> Option<String> {
-        self.last_error.lock().clone()
+        self.shared.last_error.lock().clone()
+    }
+
+    pub fn test_results(&self) -> Vec<TestResult> {
+        self.shared.test_results.lock().clone()
+    }
+
+    pub fn period(&self) -> PeriodStatus {
+        self.shared.period.lock().clone()
+    }
+
+    pub fn bit_stats(&self) -> BitSnapshot {
+        self.shared.bit_stats.lock().clone()
+    }
+
+    pub fn avalanche(&self) -> AvalancheStatus {
+        self.shared.avalanche.lock().clone()
+    }
+
+    /// Recent calls of stream 0, oldest first. Refreshed every second while running
+    /// and after every step.
+    pub fn history(&self) -> Arc<Vec<HistoryEntry>> {
+        Arc::clone(&self.shared.history.lock())
     }
 }
 
@@ -205,44 +342,430 @@ impl Drop for RngEngine {
     }
 }
 
+/// Where the generator thread pulls raw outputs from.
+enum Source {
+    Aelys {
+        program: RngProgram,
+        state: Vec<Value>,
+    },
+    Native {
+        generator: &'static NativeGenerator,
+        rng: Box<dyn NativeRng>,
+    },
+    Stream {
+        spec: StreamSpec,
+        reader: StreamSource,
+    },
+}
+
+impl Source {
+    fn pull(&mut self) -> Result<Pull, String> {
+        match self {
+            Source::Aelys { program, state } => program.step(state).map(Pull::Value),
+            Source::Native { rng, .. } => Ok(Pull::Value(rng.next_value())),
+            Source::Stream { reader, .. } => reader.pull(),
+        }
+    }
+
+    /// Leading state words for the step debugger; external streams have none.
+    fn write_state(&self, out: &mut [i64]) -> usize {
+        match self {
+            Source::Aelys { state, .. } => out
+                .iter_mut()
+                .zip(state)
+                .map(|(word, value)| *word = value.as_int().unwrap_or(0))
+                .count(),
+            Source::Native { rng, .. } => rng.write_state(out),
+            Source::Stream { .. } => 0,
+        }
+    }
+
+    fn reseed(&mut self, seed: i64) -> Result<(), String> {
+        match self {
+            Source::Aelys { program, state } => *state = program.seed_state(seed),
+            Source::Native { generator, rng } => *rng = (generator.seed)(seed),
+            // Files restart from the top; stdin carries on where it is.
+            Source::Stream { spec, reader } => {
+                if spec.path.is_some() {
+                    *reader = StreamSource::open(spec)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+enum BatchEnd {
+    /// Full, or the source has nothing more right now.
+    Open,
+    Ended,
+    Failed(String),
+}
+
+/// Pulls outputs into `batch` until it holds `batch_size` points, handing every raw output
+/// and the point it completes to `observe`, which can stop the batch early by returning
+/// false. Returns the number of calls and why it stopped.
+fn fill_batch(
+    source: &mut Source,
+    embedder: &mut Embedder,
+    mapping: &ValueMapping,
+    bounds: &AtomicBounds,
+    batch: &mut PointBatch,
+    batch_size: usize,
+    mut observe: impl FnMut(&Source, i64, Option<[f32; 3]>) -> bool,
+) -> (u64, BatchEnd) {
+    let mins = [
+        bounds.min_x.load(Ordering::Relaxed),
+        bounds.min_y.load(Ordering::Relaxed),
+        bounds.min_z.load(Ordering::Relaxed),
+    ];
+    let maxs = [
+        bounds.max_x.load(Ordering::Relaxed),
+        bounds.max_y.load(Ordering::Relaxed),
+        bounds.max_z.load(Ordering::Relaxed),
+    ];
+
+    let mut calls = 0u64;
+
+    while batch.len() < batch_size {
+        let value = match source.pull() {
+            Ok(Pull::Value(v)) => v,
+            Ok(Pull::Pending) => break,
+            Ok(Pull::Ended) => return (calls, BatchEnd::Ended),
+            Err(e) => return (calls, BatchEnd::Failed(e)),
+        };
+        let index = batch.first_index + calls;
+        calls += 1;
+
+        let raw = embedder.push(value);
+        let point = raw.map(|p| std::array::from_fn(|axis| mapping.map(p[axis], mins[axis], maxs[axis])));
+        if let (Some(raw), Some(point)) = (raw, point) {
+            batch.points.extend(point);
+            batch.origins.push(PointOrigin {
+                index,
+                raw,
+                stream: batch.stream as u32,
+            });
+        }
+        if !observe(source, value, point) {
+            break;
+        }
+    }
+
+    (calls, BatchEnd::Open)
+}
+
+/// Grows or shrinks the batch toward `TARGET_BATCH_TIME_MS`.
+fn adapt_batch_size(batch_size: usize, elapsed_ms: f32) -> usize {
+    if elapsed_ms < TARGET_BATCH_TIME_MS * 0.8 {
+        ((batch_size as f32 * 1.2) as usize).min(MAX_BATCH_SIZE)
+    } else if elapsed_ms > TARGET_BATCH_TIME_MS * 1.2 {
+        ((batch_size as f32 * 0.8) as usize).max(MIN_BATCH_SIZE)
+    } else {
+        batch_size
+    }
+}
+
+/// What the extra streams run. External streams can't be split.
+#[derive(Clone)]
+enum Generator {
+    Aelys(String),
+    Native(&'static NativeGenerator),
+}
+
+impl Generator {
+    /// Returns the source and the seed it actually started from.
+    fn open(&self, seed: i64, stream: usize) -> Result<(Source, i64), String> {
+        match self {
+            Generator::Aelys(code) => {
+                let mut program = RngProgram::compile(code)?;
+                let seed = program.stream_seed(seed, stream)?;
+                let state = program.seed_state(seed);
+                Ok((Source::Aelys { program, state }, seed))
+            }
+            Generator::Native(generator) => {
+                let seed = seed.wrapping_add(stream as i64);
+                let rng = (generator.seed)(seed);
+                Ok((Source::Native { generator, rng }, seed))
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct StreamConfig {
+    generator: Generator,
+    seed: i64,
+    embedding: Embedding,
+    lag: usize,
+    mapping: ValueMapping,
+}
+
+/// Streams 1..N; stream 0 is the generator thread itself.
+#[derive(Default)]
+struct StreamWorkers {
+    stop: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl StreamWorkers {
+    fn start(
+        &mut self,
+        count: usize,
+        config: &StreamConfig,
+        tx_points: &Sender<PointBatch>,
+        shared: &EngineShared,
+    ) {
+        self.stop();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        self.stop = Arc::clone(&stop);
+        for stream in 1..count {
+            let config = config.clone();
+            let stop = Arc::clone(&stop);
+            let tx_points = tx_points.clone();
+            let shared = shared.clone();
+            self.handles.push(thread::spawn(move || {
+                stream_thread(stream, config, &stop, tx_points, shared);
+            }));
+        }
+    }
+
+    fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for StreamWorkers {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn stream_thread(
+    stream: usize,
+    config: StreamConfig,
+    stop: &AtomicBool,
+    tx_points: Sender<PointBatch>,
+    shared: EngineShared,
+) {
+    let (mut source, seed) = match config.generator.open(config.seed, stream) {
+        Ok(opened) => opened,
+        Err(e) => {
+            *shared.last_error.lock() = Some(format!("Stream {}: {}", stream, e));
+            return;
+        }
+    };
+    let mut embedder = Embedder::new(config.embedding, config.lag);
+    let mut batch_size = INITIAL_BATCH_SIZE;
+    let mut next_index = 0u64;
+
+    let mut calls_this_sec = 0u64;
+    let mut points_this_sec = 0u64;
+    let mut last_stats_update = std::time::Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        if shared.paused.load(Ordering::Relaxed) {
+            thread::sleep(std::time::Duration::from_millis(10));
+            continue;
+        }
+
+        let batch_start = std::time::Instant::now();
+        let mut batch = PointBatch::new(stream, Some(seed), next_index, batch_size);
+        let (batch_calls, end) = fill_batch(
+            &mut source,
+            &mut embedder,
+            &config.mapping,
+            &shared.bounds,
+            &mut batch,
+            batch_size,
+            |_, _, _| true,
+        );
+        next_index += batch_calls;
+        if let BatchEnd::Failed(e) = end {
+            *shared.last_error.lock() = Some(format!("Stream {}: {}", stream, e));
+            return;
+        }
+
+        batch_size = adapt_batch_size(batch_size, batch_start.elapsed().as_secs_f32() * 1000.0);
+        calls_this_sec += batch_calls;
+        points_this_sec += batch.len() as u64;
+
+        if !batch.is_empty() {
+            shared.stats.total_batches.fetch_add(1, Ordering::Relaxed);
+            match tx_points.try_send(batch) {
+                Ok(_) => {}
+                Err(TrySendError::Full(_)) => {
+                    shared.stats.dropped_batches.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    return;
+                }
+            }
+        }
+
+        if last_stats_update.elapsed().as_secs_f32() >= 1.0 {
+            if let Some(rate) = shared.stats.streams.lock().get_mut(stream) {
+                *rate = StreamRate {
+                    calls_per_sec: calls_this_sec,
+                    points_per_sec: points_this_sec,
+                };
+            }
+            calls_this_sec = 0;
+            points_this_sec = 0;
+            last_stats_update = std::time::Instant::now();
+        }
+    }
+}
+
+/// Everything that watches the outputs of stream 0, cleared whenever it restarts.
+struct Observers {
+    battery: StatsBattery,
+    history: History,
+    bits: Option<BitAccumulator>,
+}
+
+impl Observers {
+    fn new(output_bits: u32) -> Self {
+        Self {
+            battery: StatsBattery::new(output_bits),
+            history: History::default(),
+            bits: None,
+        }
+    }
+
+    fn push(&mut self, source: &Source, value: i64, point: Option<[f32; 3]>) {
+        self.battery.push(value);
+        if let Some(bits) = &mut self.bits {
+            bits.push(value);
+        }
+        self.history.push(value, point, |words| source.write_state(words));
+    }
+
+    /// Also publishes the emptied test results.
+    fn reset(&mut self, test_results: &Mutex<Vec<TestResult>>) {
+        self.battery.reset();
+        self.history.reset();
+        *test_results.lock() = self.battery.results();
+        if let Some(bits) = &mut self.bits {
+            bits.reset();
+        }
+    }
+}
+
 fn rng_thread(
     rx_cmd: Receiver<RngCommand>,
-    tx_points: Sender<Vec<f32>>,
-    stats: Arc<PerformanceStats>,
-    bounds: Arc<AtomicBounds>,
-    paused: Arc<AtomicBool>,
-    last_error: Arc<Mutex<Option<String>>>,
+    tx_points: Sender<PointBatch>,
+    shared: EngineShared,
 ) {
-    let mut vm: Option<VM> = None;
-    let mut rng_func: Option<CallableFunction> = None;
-    let mut current_state = Value::int(12345);
-    let mut batch_size = 10_000usize;
+    let mut workers = StreamWorkers::default();
+    let EngineShared {
+        stats,
+        bounds,
+        paused,
+        last_error,
+        test_results,
+        period,
+        bit_stats,
+        avalanche,
+        history,
+    } = shared.clone();
+
+    let mut source: Option<Source> = None;
+    let mut current_code: Option<String> = None;
+    let mut current_seed = DEFAULT_SEED;
+    let mut batch_size = INITIAL_BATCH_SIZE;
     let mut running = false;
 
     let mut calls_this_sec = 0u64;
     let mut points_this_sec = 0u64;
     let mut last_stats_update = std::time::Instant::now();
     let mut batch_times = Vec::with_capacity(20);
+    let mut mapping = ValueMapping::default();
+    let mut observers = Observers::new(mapping.output_bits);
+    let mut period_finder = PeriodFinder::new(period);
+    let mut avalanche_analyzer = AvalancheAnalyzer::new(Arc::clone(&avalanche));
+    let mut embedding = (Embedding::Triples, 1);
+    let mut embedder = Embedder::new(embedding.0, embedding.1);
+    let mut stream_count = 1usize;
+    let mut restart_streams = false;
+    let mut steps_pending = 0u64;
 
     loop {
         while let Ok(cmd) = rx_cmd.try_recv() {
+            restart_streams |= matches!(
+                cmd,
+                RngCommand::UpdateCode(_)
+                    | RngCommand::UseNative(_)
+                    | RngCommand::OpenStream(_)
+                    | RngCommand::Reset
+                    | RngCommand::SetSeed(_)
+                    | RngCommand::SetEmbedding(..)
+                    | RngCommand::SetMapping(_)
+                    | RngCommand::SetStreams(_)
+            );
             match cmd {
                 RngCommand::UpdateCode(code) => {
                     *last_error.lock() = None;
                     running = false;
-
-                    match compile_rng(&code) {
-                        Ok((new_vm, func)) => {
-                            vm = Some(new_vm);
-                            rng_func = Some(func);
-                            current_state = Value::int(12345);
-                            batch_size = 10_000;
+                    avalanche_analyzer.cancel();
+
+                    match RngProgram::compile(&code) {
+                        Ok(program) => {
+                            let state = program.seed_state(current_seed);
+                            source = Some(Source::Aelys { program, state });
+                            embedder.clear();
+                            batch_size = INITIAL_BATCH_SIZE;
+                            running = true;
+                            observers.reset(&test_results);
+                            period_finder.start(&code, current_seed);
+                            current_code = Some(code);
+                        }
+                        Err(e) => {
+                            *last_error.lock() = Some(e);
+                            source = None;
+                            current_code = None;
+                            period_finder.cancel();
+                        }
+                    }
+                }
+                RngCommand::UseNative(generator) => {
+                    *last_error.lock() = None;
+                    current_code = None;
+                    period_finder.cancel();
+                    avalanche_analyzer.cancel();
+                    embedder.clear();
+                    observers.reset(&test_results);
+                    source = Some(Source::Native {
+                        generator,
+                        rng: (generator.seed)(current_seed),
+                    });
+                    batch_size = INITIAL_BATCH_SIZE;
+                    running = true;
+                }
+                RngCommand::OpenStream(spec) => {
+                    *last_error.lock() = None;
+                    current_code = None;
+                    period_finder.cancel();
+                    avalanche_analyzer.cancel();
+                    embedder.clear();
+                    observers.reset(&test_results);
+
+                    // A stdin source hands its undecoded bytes on when dropped, so let go
+                    // of the old one before opening the new one.
+                    source = None;
+                    match StreamSource::open(&spec) {
+                        Ok(reader) => {
+                            source = Some(Source::Stream { spec, reader });
+                            batch_size = INITIAL_BATCH_SIZE;
                             running = true;
                         }
                         Err(e) => {
                             *last_error.lock() = Some(e);
-                            vm = None;
-                            rng_func = None;
+                            running = false;
                         }
                     }
                 }
@@ -250,107 +773,172 @@ fn rng_thread(
                     return;
                 }
                 RngCommand::Reset => {
-                    current_state = Value::int(12345);
-                    batch_size = 10_000;
+                    current_seed = DEFAULT_SEED;
+                    let reseeded = source.as_mut().map_or(Ok(()), |src| src.reseed(current_seed));
+                    if let Err(e) = reseeded {
+                        *last_error.lock() = Some(e);
+                        source = None;
+                    }
+                    running = source.is_some();
+                    embedder.clear();
+                    batch_size = INITIAL_BATCH_SIZE;
+                    observers.reset(&test_results);
+                    if let Some(code) = &current_code {
+                        period_finder.start(code, current_seed);
+                    }
                 }
                 RngCommand::SetSeed(seed) => {
-                    current_state = Value::int(seed);
+                    current_seed = seed;
+                    if let Some(src @ (Source::Aelys { .. } | Source::Native { .. })) = &mut source {
+                        let _ = src.reseed(current_seed);
+                    }
+                    embedder.clear();
+                    observers.reset(&test_results);
+                    if let Some(code) = &current_code {
+                        period_finder.start(code, current_seed);
+                    }
+                }
+                RngCommand::SetEmbedding(mode, lag) => {
+                    embedding = (mode, lag);
+                    embedder = Embedder::new(mode, lag);
+                }
+                RngCommand::SetMapping(new_mapping) => {
+                    if new_mapping.output_bits != mapping.output_bits {
+                        observers.battery = StatsBattery::new(new_mapping.output_bits);
+                        *test_results.lock() = observers.battery.results();
+                        if observers.bits.is_some() {
+                            observers.bits = Some(BitAccumulator::new(new_mapping.output_bits));
+                        }
+                    }
+                    mapping = new_mapping;
+                }
+                RngCommand::SetBitView(enabled) => {
+                    observers.bits = enabled.then(|| BitAccumulator::new(mapping.output_bits));
+                    if let Some(bits) = &observers.bits {
+                        *bit_stats.lock() = bits.snapshot();
+                    }
                 }
-                RngCommand::Pause => {}
-                RngCommand::Resume => {}
+                RngCommand::RunAvalanche(trials) => match &current_code {
+                    Some(code) => {
+                        avalanche_analyzer.start(code, mapping.output_bits, trials, current_seed)
+                    }
+                    None => {
+                        avalanche_analyzer.cancel();
+                        *avalanche.lock() = AvalancheStatus::Error(
+                            "Avalanche analysis needs an Aelys generator".to_string(),
+                        );
+                    }
+                },
+                RngCommand::SetStreams(count) => {
+                    stream_count = count.clamp(1, MAX_STREAMS);
+                }
+                RngCommand::SetHistory(enabled) => {
+                    observers.history.set_enabled(enabled);
+                    *history.lock() = Arc::new(observers.history.entries());
+                }
+                RngCommand::Pause => {
+                    steps_pending = 0;
+                    *history.lock() = Arc::new(observers.history.entries());
+                }
+                RngCommand::Resume => {
+                    steps_pending = 0;
+                }
+                RngCommand::Step(count) => {
+                    if paused.load(Ordering::Relaxed) {
+                        steps_pending = steps_pending.saturating_add(count);
+                    }
+                }
+            }
+        }
+
+        if restart_streams {
+            restart_streams = false;
+            let generator = match &source {
+                Some(Source::Aelys { .. }) => current_code.clone().map(Generator::Aelys),
+                Some(Source::Native { generator, .. }) => Some(Generator::Native(generator)),
+                _ => None,
+            };
+            *history.lock() = Arc::new(observers.history.entries());
+            workers.stop();
+            let count = if generator.is_some() { stream_count } else { 1 };
+            *stats.streams.lock() = vec![StreamRate::default(); count];
+            if let Some(generator) = generator {
+                let config = StreamConfig {
+                    generator,
+                    seed: current_seed,
+                    embedding: embedding.0,
+                    lag: embedding.1,
+                    mapping,
+                };
+                workers.start(count, &config, &tx_points, &shared);
             }
         }
 
-        if !running || paused.load(Ordering::Relaxed) {
+        let stepping = paused.load(Ordering::Relaxed);
+        if !running || (stepping && steps_pending == 0) {
             thread::sleep(std::time::Duration::from_millis(10));
             continue;
         }
 
-        let (Some(vm_instance), Some(func)) = (&mut vm, &rng_func) else {
+        let Some(source) = &mut source else {
             thread::sleep(std::time::Duration::from_millis(10));
             continue;
         };
 
         let batch_start = std::time::Instant::now();
+        let seed = match source {
+            Source::Stream { .. } => None,
+            _ => Some(current_seed),
+        };
+        let mut batch = PointBatch::new(0, seed, observers.history.next_index(), batch_size);
+        let mut budget = if stepping { steps_pending } else { u64::MAX };
+        let (batch_calls, end) = fill_batch(
+            source,
+            &mut embedder,
+            &mapping,
+            &bounds,
+            &mut batch,
+            batch_size,
+            |source, value, point| {
+                observers.push(source, value, point);
+                budget -= 1;
+                budget > 0
+            },
+        );
+
+        if stepping {
+            steps_pending -= batch_calls.min(steps_pending);
+            *history.lock() = Arc::new(observers.history.entries());
+        }
 
-        let min_x = bounds.min_x.load(Ordering::Relaxed);
-        let max_x = bounds.max_x.load(Ordering::Relaxed);
-        let min_y = bounds.min_y.load(Ordering::Relaxed);
-        let max_y = bounds.max_y.load(Ordering::Relaxed);
-        let min_z = bounds.min_z.load(Ordering::Relaxed);
-        let max_z = bounds.max_z.load(Ordering::Relaxed);
-
-        let mut batch = Vec::with_capacity(batch_size * 3);
-        let mut state = current_state;
-        let mut batch_calls = 0u64;
-        let mut error_occurred = false;
-
-        for _ in 0..batch_size {
-            let x_result = func.call(vm_instance, &[state]);
-            let x_state = match x_result {
-                Ok(v) => v,
-                Err(e) => {
-                    *last_error.lock() = Some(e.to_string());
-                    running = false;
-                    error_occurred = true;
-                    break;
-                }
-            };
-            let x = x_state.as_int().unwrap_or(0);
-            batch.push(normalize_value(x, min_x, max_x));
-            batch_calls += 1;
-
-            let y_result = func.call(vm_instance, &[x_state]);
-            let y_state = match y_result {
-                Ok(v) => v,
-                Err(e) => {
-                    *last_error.lock() = Some(e.to_string());
-                    running = false;
-                    error_occurred = true;
-                    break;
-                }
-            };
-            let y = y_state.as_int().unwrap_or(0);
-            batch.push(normalize_value(y, min_y, max_y));
-            batch_calls += 1;
-
-            let z_result = func.call(vm_instance, &[y_state]);
-            let z_state = match z_result {
-                Ok(v) => v,
-                Err(e) => {
-                    *last_error.lock() = Some(e.to_string());
-                    running = false;
-                    error_occurred = true;
-                    break;
-                }
-            };
-            let z = z_state.as_int().unwrap_or(0);
-            batch.push(normalize_value(z, min_z, max_z));
-            batch_calls += 1;
+        if let Some(bits) = &observers.bits {
+            *bit_stats.lock() = bits.snapshot();
+        }
 
-            state = z_state;
+        match end {
+            BatchEnd::Open => {}
+            BatchEnd::Ended => running = false,
+            BatchEnd::Failed(e) => {
+                *last_error.lock() = Some(e);
+                running = false;
+                workers.stop();
+                continue;
+            }
         }
 
-        if error_occurred {
+        if batch.is_empty() {
             continue;
         }
 
-        current_state = state;
-
         let elapsed_ms = batch_start.elapsed().as_secs_f32() * 1000.0;
         batch_times.push(elapsed_ms);
         if batch_times.len() > 20 {
             batch_times.remove(0);
         }
-
-        if elapsed_ms < TARGET_BATCH_TIME_MS * 0.8 {
-            batch_size = ((batch_size as f32 * 1.2) as usize).min(MAX_BATCH_SIZE);
-        } else if elapsed_ms > TARGET_BATCH_TIME_MS * 1.2 {
-            batch_size = ((batch_size as f32 * 0.8) as usize).max(MIN_BATCH_SIZE);
-        }
+        batch_size = adapt_batch_size(batch_size, elapsed_ms);
 
         calls_this_sec += batch_calls;
-        points_this_sec += (batch.len() / 3) as u64;
+        points_this_sec += batch.len() as u64;
 
         stats.total_batches.fetch_add(1, Ordering::Relaxed);
 
@@ -365,12 +953,19 @@ fn rng_thread(
         }
 
         if last_stats_update.elapsed().as_secs_f32() >= 1.0 {
-            stats
-                .rng_calls_per_sec
-                .store(calls_this_sec, Ordering::Relaxed);
-            stats
-                .points_generated_per_sec
-                .store(points_this_sec, Ordering::Relaxed);
+            {
+                let mut streams = stats.streams.lock();
+                if let Some(rate) = streams.first_mut() {
+                    *rate = StreamRate {
+                        calls_per_sec: calls_this_sec,
+                        points_per_sec: points_this_sec,
+                    };
+                }
+                let calls: u64 = streams.iter().map(|r| r.calls_per_sec).sum();
+                let points: u64 = streams.iter().map(|r| r.points_per_sec).sum();
+                stats.rng_calls_per_sec.store(calls, Ordering::Relaxed);
+                stats.points_generated_per_sec.store(points, Ordering::Relaxed);
+            }
             stats
                 .current_batch_size
                 .store(batch_size, Ordering::Relaxed);
@@ -382,26 +977,12 @@ fn rng_thread(
 
             stats.update_bottleneck();
 
+            *test_results.lock() = observers.battery.results();
+            *history.lock() = Arc::new(observers.history.entries());
+
             calls_this_sec = 0;
             points_this_sec = 0;
             last_stats_update = std::time::Instant::now();
         }
     }
 }
-
-fn compile_rng(code: &str) -> Result<(VM, CallableFunction), String> {
-    let mut vm = new_vm().map_err(|e| format!("VM init error: {}", e))?;
-
-    run_with_vm(&mut vm, code, "rng_def").map_err(|e| format!("{}", e))?;
-
-    let func = get_function(&vm, "rng").map_err(|e| format!("{}", e))?;
-
-    if func.arity() != 1 {
-        return Err(format!(
-            "Function 'rng' must take exactly 1 argument, got {}",
-            func.arity()
-        ));
-    }
-
-    Ok((vm, func))
-}
@@ -1,6 +1,8 @@
 pub struct RngExample {
     pub name: &'static str,
     pub description: &'static str,
+    /// Width of the output range `[0, 2^bits)`, used by the value mapping.
+    pub bits: u32,
     pub code: &'static str,
 }
 
@@ -8,16 +10,58 @@ pub const RNG_EXAMPLES: &[RngExample] = &[
     RngExample {
         name: "Xorshift32",
         description: "Marsaglia's classic, fast and high-quality.",
+        bits: 31,
         code: r#"fn rng(state: int) -> int {
     let x = state ^ (state << 13)
     let y = x ^ (x >> 17)
     let z = y ^ (y << 5)
     z & 0x7FFFFFFF
+}"#,
+    },
+    RngExample {
+        name: "Xorshift128",
+        description: "Four-word shift register; rng returns the newest word.",
+        bits: 32,
+        code: r#"fn rng(w: int, z: int, y: int, x: int) -> int {
+    let t = (x ^ (x << 11)) & 0xFFFFFFFF
+    (w ^ (w >> 19) ^ t ^ (t >> 8)) & 0xFFFFFFFF
+}"#,
+    },
+    RngExample {
+        name: "Xorwow",
+        description: "Xorshift plus a Weyl counter in its own word, mixed by output.",
+        bits: 32,
+        code: r#"fn rng(v: int, w: int, z: int, y: int, x: int, d: int) -> int {
+    let t = x ^ (x >> 2)
+    ((v ^ (v << 4)) ^ (t ^ (t << 1))) & 0xFFFFFFFF
+}
+
+fn rng_5(v: int, w: int, z: int, y: int, x: int, d: int) -> int {
+    (d + 362437) & 0xFFFFFFFF
+}
+
+fn output(v: int, w: int, z: int, y: int, x: int, d: int) -> int {
+    (v + d) & 0xFFFFFFFF
+}"#,
+    },
+    RngExample {
+        name: "PCG16 XSH-RR",
+        description: "32-bit LCG state with a permuted 16-bit output.",
+        bits: 16,
+        code: r#"fn rng(state: int) -> int {
+    (state * 747796405 + 2891336453) & 0xFFFFFFFF
+}
+
+fn output(state: int) -> int {
+    let x = (((state >> 10) ^ state) >> 12) & 0xFFFF
+    let rot = state >> 28
+    ((x >> rot) | (x << ((16 - rot) & 15))) & 0xFFFF
 }"#,
     },
     RngExample {
         name: "LCG MINSTD",
         description: "Park-Miller standard, acceptable for simple uses.",
+        bits: 31,
         code: r#"fn rng(state: int) -> int {
     let next = (state * 48271) % 2147483647
     if next == 0 { 1 } else { next }
@@ -26,6 +70,7 @@ pub const RNG_EXAMPLES: &[RngExample] = &[
     RngExample {
         name: "LCG Numerical Recipes",
         description: "From Numerical Recipes, common but flawed.",
+        bits: 31,
         code: r#"fn rng(state: int) -> int {
     (state * 1103515245 + 12345) & 0x7FFFFFFF
 }"#,
@@ -33,6 +78,7 @@ pub const RNG_EXAMPLES: &[RngExample] = &[
     RngExample {
         name: "RANDU (Bad)",
         description: "IBM 1968. Famous for visible 3D hyperplanes.",
+        bits: 31,
         code: r#"fn rng(state: int) -> int {
     let next = (state * 65539) % 2147483648
     if next == 0 { 1 } else { next }
@@ -41,6 +87,7 @@ pub const RNG_EXAMPLES: &[RngExample] = &[
     RngExample {
         name: "Counter (Worst)",
         description: "Just increments. Perfect diagonal line in 3D.",
+        bits: 31,
         code: r#"fn rng(state: int) -> int {
     state + 1
 }"#,
@@ -48,6 +95,7 @@ pub const RNG_EXAMPLES: &[RngExample] = &[
     RngExample {
         name: "Multiply-3 (Awful)",
         description: "Tiny multiplier, visible patterns.",
+        bits: 31,
         code: r#"fn rng(state: int) -> int {
     (state * 3) % 2147483648
 }"#,
@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use crate::rng::program::MAX_STATE_WORDS;
+
+pub const HISTORY_LEN: usize = 4096;
+
+/// One generator call, as seen by the step debugger.
+#[derive(Clone, Copy)]
+pub struct HistoryEntry {
+    /// Calls since the last reseed, starting at 0.
+    pub index: u64,
+    pub output: i64,
+    /// Point completed by this output, in world coordinates.
+    pub point: Option<[f32; 3]>,
+    width: usize,
+    words: [i64; MAX_STATE_WORDS],
+}
+
+impl HistoryEntry {
+    /// State after the call, cut to the first `MAX_STATE_WORDS`; empty for external
+    /// streams.
+    pub fn state(&self) -> &[i64] {
+        &self.words[..self.width]
+    }
+}
+
+/// The last `HISTORY_LEN` calls of stream 0.
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    next_index: u64,
+    enabled: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::with_capacity(HISTORY_LEN),
+            next_index: 0,
+            enabled: true,
+        }
+    }
+}
+
+impl History {
+    pub fn reset(&mut self) {
+        self.entries.clear();
+        self.next_index = 0;
+    }
+
+    /// Indices keep counting while recording is off.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.entries.clear();
+        }
+    }
+
+    /// Index the next call will get.
+    pub fn next_index(&self) -> u64 {
+        self.next_index
+    }
+
+    /// `state` writes the state words into its buffer and returns how many it wrote;
+    /// it only runs while recording.
+    pub fn push(
+        &mut self,
+        output: i64,
+        point: Option<[f32; 3]>,
+        state: impl FnOnce(&mut [i64; MAX_STATE_WORDS]) -> usize,
+    ) {
+        let index = self.next_index;
+        self.next_index += 1;
+        if !self.enabled {
+            return;
+        }
+
+        let mut words = [0; MAX_STATE_WORDS];
+        let width = state(&mut words).min(MAX_STATE_WORDS);
+
+        if self.entries.len() == HISTORY_LEN {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(HistoryEntry {
+            index,
+            output,
+            point,
+            width,
+            words,
+        });
+    }
+
+    /// Oldest first.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.entries.iter().copied().collect()
+    }
+}
@@ -0,0 +1,87 @@
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+
+/// A cancellable analysis on its own thread (and its own VM), reporting through a
+/// shared status so the render stream keeps going while it runs. The default status
+/// means idle.
+pub struct BackgroundJob<S> {
+    status: Arc<Mutex<S>>,
+    cancel: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<S: Default + Send + 'static> BackgroundJob<S> {
+    pub fn new(status: Arc<Mutex<S>>) -> Self {
+        Self {
+            status,
+            cancel: Arc::new(AtomicBool::new(false)),
+            handle: None,
+        }
+    }
+
+    pub fn status(&self) -> S
+    where
+        S: Clone,
+    {
+        self.status.lock().clone()
+    }
+
+    /// Replaces the status without starting anything, e.g. to report bad input.
+    pub fn set_status(&mut self, status: S) {
+        self.cancel();
+        *self.status.lock() = status;
+    }
+
+    /// Stops the previous run, publishes `initial`, then runs `work` on a new thread.
+    /// `work` polls the flag and may publish progress; its result becomes the final
+    /// status unless the run was cancelled in the meantime.
+    pub fn start<F>(&mut self, initial: S, work: F)
+    where
+        F: FnOnce(&AtomicBool, &Mutex<S>) -> S + Send + 'static,
+    {
+        self.cancel();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel = Arc::clone(&cancel);
+        let status = Arc::clone(&self.status);
+
+        *status.lock() = initial;
+
+        self.handle = Some(thread::spawn(move || {
+            let result = work(&cancel, &status);
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            *status.lock() = result;
+        }));
+    }
+
+    /// Stops the current run, waits for its thread and goes back to idle.
+    pub fn cancel(&mut self) {
+        self.stop();
+        *self.status.lock() = S::default();
+    }
+}
+
+impl<S> BackgroundJob<S> {
+    fn stop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl<S: Default + Send + 'static> Default for BackgroundJob<S> {
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+impl<S> Drop for BackgroundJob<S> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
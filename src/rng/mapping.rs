@@ -0,0 +1,83 @@
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MappingMode {
+    /// `value mod (max - min)`, using the Euclidean remainder so signs don't fold.
+    Modulo,
+    /// Scales the declared output range `[0, 2^bits)` onto the bounds.
+    Scale,
+    /// Keeps only the top `field_bits` bits of the declared width.
+    TopBits,
+    /// Keeps only the bottom `field_bits` bits.
+    LowBits,
+    /// Puts the top 23 bits into the mantissa of a float in `[1, 2)`.
+    Float,
+}
+
+/// How raw generator outputs become coordinates; bounds are applied after this.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ValueMapping {
+    pub mode: MappingMode,
+    pub output_bits: u32,
+    pub field_bits: u32,
+}
+
+impl Default for ValueMapping {
+    fn default() -> Self {
+        Self {
+            mode: MappingMode::Scale,
+            output_bits: 31,
+            field_bits: 8,
+        }
+    }
+}
+
+impl ValueMapping {
+    #[inline]
+    fn masked(&self, value: i64) -> u64 {
+        value as u64 & mask(self.output_bits)
+    }
+
+    /// Maps a raw output into `[0, 1)`.
+    #[inline]
+    pub fn unit(&self, value: i64) -> f64 {
+        let bits = self.output_bits.clamp(1, 64);
+        let v = self.masked(value);
+        match self.mode {
+            MappingMode::Modulo | MappingMode::Scale => v as f64 / pow2(bits),
+            MappingMode::TopBits => {
+                let k = self.field_bits.clamp(1, bits);
+                (v >> (bits - k)) as f64 / pow2(k)
+            }
+            MappingMode::LowBits => {
+                let k = self.field_bits.clamp(1, bits);
+                (v & mask(k)) as f64 / pow2(k)
+            }
+            MappingMode::Float => {
+                let mantissa = if bits >= 23 {
+                    v >> (bits - 23)
+                } else {
+                    v << (23 - bits)
+                };
+                (f32::from_bits(0x3F80_0000 | mantissa as u32) - 1.0) as f64
+            }
+        }
+    }
+
+    #[inline]
+    pub fn map(&self, value: i64, min: i64, max: i64) -> f32 {
+        if self.mode == MappingMode::Modulo {
+            let v = value.rem_euclid((max - min).max(1));
+            return (min + v) as f32;
+        }
+        (min as f64 + self.unit(value) * (max - min) as f64) as f32
+    }
+}
+
+#[inline]
+fn mask(bits: u32) -> u64 {
+    if bits >= 64 { u64::MAX } else { (1u64 << bits) - 1 }
+}
+
+#[inline]
+fn pow2(bits: u32) -> f64 {
+    (bits as f64).exp2()
+}
@@ -1,5 +1,31 @@
+pub mod avalanche;
+pub mod bits;
+pub mod embedding;
 pub mod engine;
 pub mod examples;
+pub mod history;
+pub mod job;
+pub mod mapping;
+pub mod native;
+pub mod period;
+pub mod program;
+pub mod sampler;
+pub mod spectral;
+pub mod stats;
+pub mod stream;
+pub mod sweep;
+pub mod verify;
 
-pub use engine::{Bottleneck, PerformanceStats, RngEngine};
+pub use avalanche::AvalancheStatus;
+pub use bits::BitPlot;
+pub use embedding::Embedding;
+pub use engine::{Bottleneck, MAX_STREAMS, PerformanceStats, PointOrigin, RngEngine};
 pub use examples::RNG_EXAMPLES;
+pub use history::HistoryEntry;
+pub use mapping::{MappingMode, ValueMapping};
+pub use native::NATIVE_GENERATORS;
+pub use period::PeriodStatus;
+pub use stats::{MIN_OUTPUT_BITS, TestResult, Verdict};
+pub use stream::{Endian, StreamFormat, StreamSpec};
+pub use sweep::{SeedSweeper, SweepStatus};
+pub use verify::{Reference, Verifier, VerifyStatus};
@@ -0,0 +1,413 @@
+use crate::rng::program::splitmix64;
+
+/// A generator compiled into the binary. Outputs are raw values in `[0, 2^bits)`.
+pub trait NativeRng: Send {
+    fn next_value(&mut self) -> i64;
+
+    /// Writes as many leading state words as `out` holds and returns how many.
+    fn write_state(&self, out: &mut [i64]) -> usize;
+
+    /// Raw state words, with the buffer index first for block generators.
+    fn state(&self) -> Vec<i64> {
+        let mut words = vec![0; MAX_STATE_LEN];
+        let len = self.write_state(&mut words);
+        words.truncate(len);
+        words
+    }
+}
+
+/// MT19937's buffer index and 624 words, the largest state here.
+const MAX_STATE_LEN: usize = 625;
+
+fn write_words(out: &mut [i64], words: impl IntoIterator<Item = i64>) -> usize {
+    out.iter_mut().zip(words).map(|(slot, word)| *slot = word).count()
+}
+
+/// Builds a generator from raw state words.
+pub type FromState = fn(&[i64]) -> Box<dyn NativeRng>;
+
+pub struct NativeGenerator {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub bits: u32,
+    pub seed: fn(i64) -> Box<dyn NativeRng>,
+    /// Starts from raw words laid out as `NativeRng::state` reports them. `None` when
+    /// the state is too large for an Aelys program to hold.
+    pub from_state: Option<FromState>,
+}
+
+pub const NATIVE_GENERATORS: &[NativeGenerator] = &[
+    NativeGenerator {
+        name: "PCG32",
+        description: "pcg32_srandom_r(seed, 54), XSH-RR 32-bit output.",
+        bits: 32,
+        seed: |s| Box::new(Pcg32::new(s as u64, 54)),
+        from_state: Some(|w| Box::new(Pcg32 { state: w[0] as u64, inc: w[1] as u64 })),
+    },
+    NativeGenerator {
+        name: "xoshiro256**",
+        description: "State filled from SplitMix64(seed), as recommended by the authors.",
+        bits: 64,
+        seed: |s| Box::new(Xoshiro256StarStar::new(s as u64)),
+        from_state: Some(|w| Box::new(Xoshiro256StarStar(std::array::from_fn(|i| w[i] as u64)))),
+    },
+    NativeGenerator {
+        name: "SplitMix64",
+        description: "Weyl sequence with a 64-bit finalizer; state starts at the seed.",
+        bits: 64,
+        seed: |s| Box::new(SplitMix64(s as u64)),
+        from_state: Some(|w| Box::new(SplitMix64(w[0] as u64))),
+    },
+    NativeGenerator {
+        name: "MT19937",
+        description: "32-bit Mersenne Twister, init_genrand(seed).",
+        bits: 32,
+        seed: |s| Box::new(Mt19937::new(s as u32)),
+        from_state: None,
+    },
+    NativeGenerator {
+        name: "ChaCha8",
+        description: "8-round ChaCha, key = seed (LE) padded with zeros, 64-bit counter.",
+        bits: 32,
+        seed: |s| Box::new(ChaCha8::new(s as u64)),
+        from_state: None,
+    },
+    NativeGenerator {
+        name: "Xorshift32 (preset)",
+        description: "Same 64-bit arithmetic and 31-bit mask as the Aelys preset.",
+        bits: 31,
+        seed: |s| Box::new(Lcg::new(s, xorshift32)),
+        from_state: Some(|w| Box::new(Lcg::new(w[0], xorshift32))),
+    },
+    NativeGenerator {
+        name: "LCG MINSTD",
+        description: "Park-Miller, bit-exact with the Aelys preset.",
+        bits: 31,
+        seed: |s| Box::new(Lcg::new(s, minstd)),
+        from_state: Some(|w| Box::new(Lcg::new(w[0], minstd))),
+    },
+    NativeGenerator {
+        name: "LCG Numerical Recipes",
+        description: "Bit-exact with the Aelys preset.",
+        bits: 31,
+        seed: |s| Box::new(Lcg::new(s, numerical_recipes)),
+        from_state: Some(|w| Box::new(Lcg::new(w[0], numerical_recipes))),
+    },
+    NativeGenerator {
+        name: "RANDU (Bad)",
+        description: "Bit-exact with the Aelys preset.",
+        bits: 31,
+        seed: |s| Box::new(Lcg::new(s, randu)),
+        from_state: Some(|w| Box::new(Lcg::new(w[0], randu))),
+    },
+];
+
+fn xorshift32(x: i64) -> i64 {
+    let x = x ^ x.wrapping_shl(13);
+    let y = x ^ (x >> 17);
+    let z = y ^ y.wrapping_shl(5);
+    z & 0x7FFF_FFFF
+}
+
+fn minstd(x: i64) -> i64 {
+    let next = x.wrapping_mul(48271) % 2_147_483_647;
+    if next == 0 { 1 } else { next }
+}
+
+fn numerical_recipes(x: i64) -> i64 {
+    x.wrapping_mul(1_103_515_245).wrapping_add(12345) & 0x7FFF_FFFF
+}
+
+fn randu(x: i64) -> i64 {
+    let next = x.wrapping_mul(65539) % 2_147_483_648;
+    if next == 0 { 1 } else { next }
+}
+
+/// Any single-word recurrence whose output is the new state.
+struct Lcg {
+    state: i64,
+    step: fn(i64) -> i64,
+}
+
+impl Lcg {
+    fn new(seed: i64, step: fn(i64) -> i64) -> Self {
+        Self { state: seed, step }
+    }
+}
+
+impl NativeRng for Lcg {
+    fn next_value(&mut self) -> i64 {
+        self.state = (self.step)(self.state);
+        self.state
+    }
+
+    fn write_state(&self, out: &mut [i64]) -> usize {
+        write_words(out, [self.state])
+    }
+}
+
+struct Pcg32 {
+    state: u64,
+    inc: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    fn new(init_state: u64, init_seq: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            inc: (init_seq << 1) | 1,
+        };
+        rng.step();
+        rng.state = rng.state.wrapping_add(init_state);
+        rng.step();
+        rng
+    }
+
+    fn step(&mut self) -> u64 {
+        let old = self.state;
+        self.state = old.wrapping_mul(Self::MULTIPLIER).wrapping_add(self.inc);
+        old
+    }
+}
+
+impl NativeRng for Pcg32 {
+    fn next_value(&mut self) -> i64 {
+        let old = self.step();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot) as i64
+    }
+
+    fn write_state(&self, out: &mut [i64]) -> usize {
+        write_words(out, [self.state as i64, self.inc as i64])
+    }
+}
+
+struct Xoshiro256StarStar([u64; 4]);
+
+impl Xoshiro256StarStar {
+    fn new(seed: u64) -> Self {
+        let mut mix = seed;
+        Self(std::array::from_fn(|_| splitmix64(&mut mix)))
+    }
+}
+
+impl NativeRng for Xoshiro256StarStar {
+    fn next_value(&mut self) -> i64 {
+        let s = &mut self.0;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result as i64
+    }
+
+    fn write_state(&self, out: &mut [i64]) -> usize {
+        write_words(out, self.0.map(|w| w as i64))
+    }
+}
+
+struct SplitMix64(u64);
+
+impl NativeRng for SplitMix64 {
+    fn next_value(&mut self) -> i64 {
+        splitmix64(&mut self.0) as i64
+    }
+
+    fn write_state(&self, out: &mut [i64]) -> usize {
+        write_words(out, [self.0 as i64])
+    }
+}
+
+struct Mt19937 {
+    mt: [u32; 624],
+    index: usize,
+}
+
+impl Mt19937 {
+    fn new(seed: u32) -> Self {
+        let mut mt = [0u32; 624];
+        mt[0] = seed;
+        for i in 1..624 {
+            mt[i] = 1812433253u32
+                .wrapping_mul(mt[i - 1] ^ (mt[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+        Self { mt, index: 624 }
+    }
+
+    fn twist(&mut self) {
+        for i in 0..624 {
+            let y = (self.mt[i] & 0x8000_0000) | (self.mt[(i + 1) % 624] & 0x7FFF_FFFF);
+            let mut next = self.mt[(i + 397) % 624] ^ (y >> 1);
+            if y & 1 != 0 {
+                next ^= 0x9908_B0DF;
+            }
+            self.mt[i] = next;
+        }
+        self.index = 0;
+    }
+}
+
+impl NativeRng for Mt19937 {
+    fn next_value(&mut self) -> i64 {
+        if self.index >= 624 {
+            self.twist();
+        }
+        let mut y = self.mt[self.index];
+        self.index += 1;
+        y ^= y >> 11;
+        y ^= (y << 7) & 0x9D2C_5680;
+        y ^= (y << 15) & 0xEFC6_0000;
+        y ^= y >> 18;
+        y as i64
+    }
+
+    fn write_state(&self, out: &mut [i64]) -> usize {
+        let words = self.mt.iter().map(|&w| w as i64);
+        write_words(out, std::iter::once(self.index as i64).chain(words))
+    }
+}
+
+struct ChaCha8 {
+    input: [u32; 16],
+    block: [u32; 16],
+    index: usize,
+}
+
+impl ChaCha8 {
+    fn new(seed: u64) -> Self {
+        let mut input = [0u32; 16];
+        input[..4].copy_from_slice(&[0x6170_7865, 0x3320_646E, 0x7962_2D32, 0x6B20_6574]);
+        input[4] = seed as u32;
+        input[5] = (seed >> 32) as u32;
+        Self {
+            input,
+            block: [0; 16],
+            index: 16,
+        }
+    }
+
+    fn refill(&mut self) {
+        let mut x = self.input;
+        for _ in 0..4 {
+            quarter_round(&mut x, 0, 4, 8, 12);
+            quarter_round(&mut x, 1, 5, 9, 13);
+            quarter_round(&mut x, 2, 6, 10, 14);
+            quarter_round(&mut x, 3, 7, 11, 15);
+            quarter_round(&mut x, 0, 5, 10, 15);
+            quarter_round(&mut x, 1, 6, 11, 12);
+            quarter_round(&mut x, 2, 7, 8, 13);
+            quarter_round(&mut x, 3, 4, 9, 14);
+        }
+        for (out, (a, b)) in self.block.iter_mut().zip(x.iter().zip(&self.input)) {
+            *out = a.wrapping_add(*b);
+        }
+
+        let counter = ((self.input[13] as u64) << 32 | self.input[12] as u64).wrapping_add(1);
+        self.input[12] = counter as u32;
+        self.input[13] = (counter >> 32) as u32;
+        self.index = 0;
+    }
+}
+
+#[inline]
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+impl NativeRng for ChaCha8 {
+    fn next_value(&mut self) -> i64 {
+        if self.index >= 16 {
+            self.refill();
+        }
+        let value = self.block[self.index];
+        self.index += 1;
+        value as i64
+    }
+
+    fn write_state(&self, out: &mut [i64]) -> usize {
+        let words = self.input.iter().map(|&w| w as i64);
+        write_words(out, std::iter::once(self.index as i64).chain(words))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outputs(rng: &mut dyn NativeRng, n: usize) -> Vec<u64> {
+        (0..n).map(|_| rng.next_value() as u64).collect()
+    }
+
+    #[test]
+    fn pcg32_matches_reference_demo() {
+        // pcg32-demo: pcg32_srandom_r(&rng, 42, 54).
+        let mut rng = Pcg32::new(42, 54);
+        assert_eq!(outputs(&mut rng, 6), [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]);
+    }
+
+    #[test]
+    fn xoshiro256starstar_matches_reference() {
+        let mut rng = Xoshiro256StarStar([1, 2, 3, 4]);
+        assert_eq!(
+            outputs(&mut rng, 10),
+            [
+                11520,
+                0,
+                1509978240,
+                1215971899390074240,
+                1216172134540287360,
+                607988272756665600,
+                16172922978634559625,
+                8476171486693032832,
+                10595114339597558777,
+                2904607092377533576,
+            ]
+        );
+    }
+
+    #[test]
+    fn splitmix64_matches_reference() {
+        let mut rng = SplitMix64(0);
+        assert_eq!(outputs(&mut rng, 3), [0xe220a8397b1dcdaf, 0x6e789e6aa1b965f4, 0x06c45d188009454f]);
+    }
+
+    #[test]
+    fn mt19937_matches_reference() {
+        let mut rng = Mt19937::new(5489);
+        assert_eq!(rng.next_value(), 3499211612);
+        // The C++ standard pins the 10000th output of the default seed.
+        for _ in 1..9999 {
+            rng.next_value();
+        }
+        assert_eq!(rng.next_value(), 4123659995);
+    }
+
+    #[test]
+    fn chacha8_matches_reference_keystream() {
+        // draft-strombergson-chacha-test-vectors TC1: 256-bit zero key, zero IV, 8 rounds.
+        let expected: [u8; 64] = [
+            0x3e, 0x00, 0xef, 0x2f, 0x89, 0x5f, 0x40, 0xd6, 0x7f, 0x5b, 0xb8, 0xe8, 0x1f, 0x09, 0xa5, 0xa1,
+            0x2c, 0x84, 0x0e, 0xc3, 0xce, 0x9a, 0x7f, 0x3b, 0x18, 0x1b, 0xe1, 0x88, 0xef, 0x71, 0x1a, 0x1e,
+            0x98, 0x4c, 0xe1, 0x72, 0xb9, 0x21, 0x6f, 0x41, 0x9f, 0x44, 0x53, 0x67, 0x45, 0x6d, 0x56, 0x19,
+            0x31, 0x4a, 0x42, 0xa3, 0xda, 0x86, 0xb0, 0x01, 0x38, 0x7b, 0xfd, 0xb8, 0x0e, 0x0c, 0xfe, 0x42,
+        ];
+        let mut rng = ChaCha8::new(0);
+        let keystream: Vec<u8> = (0..16).flat_map(|_| (rng.next_value() as u32).to_le_bytes()).collect();
+        assert_eq!(keystream, expected);
+    }
+}
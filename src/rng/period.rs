@@ -0,0 +1,111 @@
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::rng::job::BackgroundJob;
+use crate::rng::program::{RngProgram, states_equal};
+
+const PERIOD_BUDGET: u64 = 1 << 26;
+const PROGRESS_INTERVAL: u64 = 1 << 16;
+
+#[derive(Clone, Default)]
+pub enum PeriodStatus {
+    #[default]
+    Idle,
+    Searching {
+        steps: u64,
+    },
+    Found {
+        tail: u64,
+        cycle: u64,
+    },
+    /// Budget ran out; tail + cycle is at least `min`.
+    LowerBound {
+        min: u64,
+    },
+    Error(String),
+}
+
+/// Runs Brent's cycle detection in the background.
+pub struct PeriodFinder {
+    job: BackgroundJob<PeriodStatus>,
+}
+
+impl PeriodFinder {
+    pub fn new(status: Arc<Mutex<PeriodStatus>>) -> Self {
+        Self {
+            job: BackgroundJob::new(status),
+        }
+    }
+
+    pub fn start(&mut self, code: &str, seed: i64) {
+        let code = code.to_string();
+        self.job.start(PeriodStatus::Searching { steps: 0 }, move |cancel, status| {
+            find_period(&code, seed, cancel, status).unwrap_or_else(PeriodStatus::Error)
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        self.job.cancel();
+    }
+}
+
+fn find_period(
+    code: &str,
+    seed: i64,
+    cancel: &AtomicBool,
+    status: &Mutex<PeriodStatus>,
+) -> Result<PeriodStatus, String> {
+    let mut program = RngProgram::compile(code)?;
+
+    let mut steps = 1u64;
+    let mut power = 1u64;
+    let mut completed_power = 0u64;
+    let mut cycle = 1u64;
+    let mut tortoise = program.seed_state(seed);
+    let mut hare = tortoise.clone();
+    program.step(&mut hare)?;
+
+    while !states_equal(&tortoise, &hare) {
+        if power == cycle {
+            tortoise.copy_from_slice(&hare);
+            completed_power = power;
+            power *= 2;
+            cycle = 0;
+        }
+        program.step(&mut hare)?;
+        cycle += 1;
+        steps += 1;
+
+        if steps.is_multiple_of(PROGRESS_INTERVAL) {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(PeriodStatus::Idle);
+            }
+            *status.lock() = PeriodStatus::Searching { steps };
+        }
+        if steps >= PERIOD_BUDGET {
+            return Ok(PeriodStatus::LowerBound {
+                min: completed_power,
+            });
+        }
+    }
+
+    let mut tortoise = program.seed_state(seed);
+    let mut hare = tortoise.clone();
+    for _ in 0..cycle {
+        program.step(&mut hare)?;
+    }
+
+    let mut tail = 0u64;
+    while !states_equal(&tortoise, &hare) {
+        program.step(&mut tortoise)?;
+        program.step(&mut hare)?;
+        tail += 1;
+
+        if tail.is_multiple_of(PROGRESS_INTERVAL) && cancel.load(Ordering::Relaxed) {
+            return Ok(PeriodStatus::Idle);
+        }
+    }
+
+    Ok(PeriodStatus::Found { tail, cycle })
+}
@@ -0,0 +1,140 @@
+use aelys::{CallableFunction, VM, Value, get_function, new_vm, run_with_vm};
+
+pub const MAX_STATE_WORDS: usize = 8;
+
+/// A compiled Aelys generator.
+///
+/// `rng(s0, ..., sN)` fixes the state width through its arity and returns the new
+/// word 0. Every other word `i` takes the previous value of word `i - 1` (a shift
+/// register, as in xorshift128) unless `rng_<i>` is defined to compute it. All words
+/// are computed from the previous state. The optional `output(s0, ..., sN)` maps
+/// the new state to the sample; without it the sample is word 0. The optional
+/// `stream_seed(seed, stream)` gives the seed of each parallel stream.
+pub struct RngProgram {
+    vm: VM,
+    transitions: Vec<Option<CallableFunction>>,
+    output: Option<CallableFunction>,
+    stream_seed: Option<CallableFunction>,
+    next: Vec<Value>,
+}
+
+impl RngProgram {
+    pub fn compile(code: &str) -> Result<Self, String> {
+        let mut vm = new_vm().map_err(|e| format!("VM init error: {}", e))?;
+
+        run_with_vm(&mut vm, code, "rng_def").map_err(|e| format!("{}", e))?;
+
+        let func = get_function(&vm, "rng").map_err(|e| format!("{}", e))?;
+
+        let width = func.arity();
+        if width == 0 || width > MAX_STATE_WORDS {
+            return Err(format!(
+                "Function 'rng' must take between 1 and {} state words, got {}",
+                MAX_STATE_WORDS, width
+            ));
+        }
+
+        let mut transitions = vec![Some(func)];
+        for i in 1..width {
+            let name = format!("rng_{}", i);
+            let word = get_function(&vm, &name).ok();
+            if let Some(f) = &word {
+                check_arity(&name, f, width)?;
+            }
+            transitions.push(word);
+        }
+
+        let output = get_function(&vm, "output").ok();
+        if let Some(f) = &output {
+            check_arity("output", f, width)?;
+        }
+
+        let stream_seed = get_function(&vm, "stream_seed").ok();
+        if let Some(arity) = stream_seed.as_ref().map(|f| f.arity()).filter(|&a| a != 2) {
+            return Err(format!(
+                "Function 'stream_seed' must take (seed, stream), got {} arguments",
+                arity
+            ));
+        }
+
+        Ok(Self {
+            vm,
+            transitions,
+            output,
+            stream_seed,
+            next: vec![Value::int(0); width],
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Word 0 is the seed itself; extra words are filled with 32-bit SplitMix64 outputs.
+    pub fn seed_state(&self, seed: i64) -> Vec<Value> {
+        let mut mix = seed as u64;
+        (0..self.width())
+            .map(|i| {
+                if i == 0 {
+                    Value::int(seed)
+                } else {
+                    Value::int((splitmix64(&mut mix) >> 32) as i64)
+                }
+            })
+            .collect()
+    }
+
+    /// Seed for parallel stream `stream`: `stream_seed(seed, stream)` when defined,
+    /// otherwise consecutive seeds. Stream 0 always keeps `seed`.
+    pub fn stream_seed(&mut self, seed: i64, stream: usize) -> Result<i64, String> {
+        match &self.stream_seed {
+            Some(f) if stream > 0 => {
+                let args = [Value::int(seed), Value::int(stream as i64)];
+                let value = f.call(&mut self.vm, &args).map_err(|e| e.to_string())?;
+                Ok(value.as_int().unwrap_or(0))
+            }
+            _ => Ok(seed.wrapping_add(stream as i64)),
+        }
+    }
+
+    /// Advances `state` in place and returns the raw output of the new state.
+    pub fn step(&mut self, state: &mut [Value]) -> Result<i64, String> {
+        for i in 0..self.transitions.len() {
+            self.next[i] = match &self.transitions[i] {
+                Some(f) => f.call(&mut self.vm, state).map_err(|e| e.to_string())?,
+                None => state[i - 1],
+            };
+        }
+        state.copy_from_slice(&self.next);
+
+        let sample = match &self.output {
+            Some(f) => f.call(&mut self.vm, state).map_err(|e| e.to_string())?,
+            None => state[0],
+        };
+        Ok(sample.as_int().unwrap_or(0))
+    }
+}
+
+pub fn states_equal(a: &[Value], b: &[Value]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x.as_int() == y.as_int())
+}
+
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn check_arity(name: &str, func: &CallableFunction, width: usize) -> Result<(), String> {
+    if func.arity() != width {
+        return Err(format!(
+            "Function '{}' must take the same {} state words as 'rng', got {}",
+            name,
+            width,
+            func.arity()
+        ));
+    }
+    Ok(())
+}
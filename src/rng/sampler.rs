@@ -0,0 +1,53 @@
+use aelys::Value;
+
+use crate::rng::native::{NativeGenerator, NativeRng};
+use crate::rng::program::RngProgram;
+
+/// A seeded generator outside the engine, either compiled Aelys or native.
+pub enum Sampler {
+    Aelys(RngProgram, Vec<Value>),
+    Native(Box<dyn NativeRng>),
+}
+
+impl Sampler {
+    pub fn aelys(code: &str, seed: i64) -> Result<Self, String> {
+        let program = RngProgram::compile(code)?;
+        let state = program.seed_state(seed);
+        Ok(Sampler::Aelys(program, state))
+    }
+
+    pub fn native(generator: &NativeGenerator, seed: i64) -> Self {
+        Sampler::Native((generator.seed)(seed))
+    }
+
+    /// Starts `generator` from the same words a program of that width would hold.
+    pub fn native_from_state(generator: &NativeGenerator, state: &[i64]) -> Result<Self, String> {
+        let from_state = generator
+            .from_state
+            .ok_or_else(|| format!("{} has more state than an Aelys program can hold", generator.name))?;
+        let width = (generator.seed)(0).state().len();
+        if state.len() != width {
+            return Err(format!(
+                "{} has {} state words, the program has {}",
+                generator.name,
+                width,
+                state.len()
+            ));
+        }
+        Ok(Sampler::Native(from_state(state)))
+    }
+
+    pub fn next_value(&mut self) -> Result<i64, String> {
+        match self {
+            Sampler::Aelys(program, state) => program.step(state),
+            Sampler::Native(rng) => Ok(rng.next_value()),
+        }
+    }
+
+    pub fn state(&self) -> Vec<i64> {
+        match self {
+            Sampler::Aelys(_, state) => state.iter().map(|v| v.as_int().unwrap_or(0)).collect(),
+            Sampler::Native(rng) => rng.state(),
+        }
+    }
+}
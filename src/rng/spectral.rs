@@ -0,0 +1,478 @@
+use aelys::Value;
+
+use crate::rng::program::{RngProgram, splitmix64};
+
+pub const MIN_DIMENSION: usize = 2;
+pub const MAX_DIMENSION: usize = 8;
+const DETECT_PROBES: usize = 32;
+const VERIFY_OUTPUTS: usize = 32;
+const LLL_DELTA: f64 = 0.99;
+const LLL_MAX_ITERATIONS: usize = 100_000;
+
+/// Hermite constants γ_t for t = 2..=8; the shortest dual vector is at most sqrt(γ_t) m^(1/t).
+const HERMITE: [f64; 7] = [
+    1.154_700_538_379_251_5, // 2 / sqrt(3)
+    1.259_921_049_894_873_2, // 2^(1/3)
+    std::f64::consts::SQRT_2,
+    1.515_716_566_510_398,   // 8^(1/5)
+    1.665_366_211_022_859_5, // (64/3)^(1/6)
+    1.811_447_328_527_813_3, // 64^(1/7)
+    2.0,
+];
+
+/// `x' = (a x + c) mod m`, with `m` up to 2^64.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Lcg {
+    pub a: u128,
+    pub c: u128,
+    pub m: u128,
+}
+
+impl Lcg {
+    pub fn new(a: u128, c: u128, m: u128) -> Result<Self, String> {
+        if !(2..=1 << 64).contains(&m) {
+            return Err(format!("Modulus must be between 2 and 2^64, got {}", m));
+        }
+        Ok(Self { a: a % m, c: c % m, m })
+    }
+
+    fn step(&self, x: u128) -> u128 {
+        (mul_mod(self.a, x, self.m) + self.c) % self.m
+    }
+
+    /// The generator that takes `lag` steps at once, as seen by a lagged embedding.
+    pub fn leap(&self, lag: usize) -> Self {
+        let (mut a, mut c) = (1u128, 0u128);
+        for _ in 0..lag {
+            a = mul_mod(a, self.a, self.m);
+            c = (mul_mod(c, self.a, self.m) + self.c) % self.m;
+        }
+        Self { a, c, m: self.m }
+    }
+}
+
+/// Result of the spectral test in one dimension.
+#[derive(Clone)]
+pub struct Dimension {
+    pub t: usize,
+    /// Shortest nonzero vector of the dual lattice: the normal of the widest-spaced
+    /// family of hyperplanes covering all overlapping t-tuples.
+    pub shortest: Vec<i128>,
+    /// Euclidean length ν_t of `shortest`.
+    pub nu: f64,
+    /// Distance between adjacent hyperplanes in the unit cube, 1 / ν_t.
+    pub spacing: f64,
+    /// How many of those hyperplanes cross the unit cube.
+    pub planes: u128,
+    /// ν_t relative to the best any lattice with this modulus can do, in (0, 1].
+    pub merit: f64,
+}
+
+#[derive(Clone)]
+pub struct SpectralReport {
+    pub lcg: Lcg,
+    pub dimensions: Vec<Dimension>,
+}
+
+/// Hyperplanes `normal · (x / m) = level` containing every 3D point of an LCG.
+pub struct Hyperplanes {
+    pub normal: [f64; 3],
+    pub levels: Vec<f64>,
+}
+
+pub fn spectral_test(lcg: &Lcg) -> SpectralReport {
+    let dimensions = (MIN_DIMENSION..=MAX_DIMENSION)
+        .map(|t| {
+            let shortest = shortest_dual_vector(lcg, t);
+            let nu = norm(&shortest);
+            let offset = plane_offset(lcg, &shortest);
+            let bound = HERMITE[t - MIN_DIMENSION].sqrt() * (lcg.m as f64).powf(1.0 / t as f64);
+            Dimension {
+                t,
+                planes: plane_count(&shortest, offset),
+                spacing: 1.0 / nu,
+                merit: (nu / bound).min(1.0),
+                nu,
+                shortest,
+            }
+        })
+        .collect();
+
+    SpectralReport {
+        lcg: *lcg,
+        dimensions,
+    }
+}
+
+/// The planes of points `(x_n, x_{n+lag}, x_{n+2 lag})`, or `None` if there are more than `max_planes`.
+pub fn hyperplanes_3d(lcg: &Lcg, lag: usize, max_planes: usize) -> Option<Hyperplanes> {
+    let lcg = lcg.leap(lag.max(1));
+    let u = shortest_dual_vector(&lcg, 3);
+    let offset = plane_offset(&lcg, &u);
+    if plane_count(&u, offset) > max_planes as u128 {
+        return None;
+    }
+
+    let (low, high) = level_range(&u);
+    let levels = (low..=high)
+        .map(|k| k as f64 + offset)
+        .filter(|&level| level > low as f64 && level < high as f64)
+        .collect();
+    Some(Hyperplanes {
+        normal: [u[0] as f64, u[1] as f64, u[2] as f64],
+        levels,
+    })
+}
+
+/// Recognizes a single-word generator of the form `(a * x + c) mod m`, trying
+/// power-of-two moduli and the usual primes just below them. The first fit is then
+/// run from the bottom and the top of its range and must predict every output.
+pub fn detect_lcg(code: &str) -> Result<Lcg, String> {
+    let mut program = RngProgram::compile(code)?;
+    if program.width() != 1 {
+        return Err("The spectral test needs a single-word generator".to_string());
+    }
+    fit_lcg(|x| {
+        let mut state = [Value::int(*x)];
+        let y = program.step(&mut state)?;
+        *x = state[0].as_int().unwrap_or(0);
+        Ok(y)
+    })
+}
+
+/// `detect_lcg` for any single-word `step` that advances its state and returns the output.
+fn fit_lcg(mut step: impl FnMut(&mut i64) -> Result<i64, String>) -> Result<Lcg, String> {
+    let mut f = |mut x: i64| step(&mut x);
+
+    let mut mix = 0x5EED_u64;
+    let probes: Vec<u64> = (0..DETECT_PROBES).map(|_| splitmix64(&mut mix)).collect();
+    let y1 = f(1)?;
+    let y2 = f(2)?;
+
+    let candidates = (2..=64u32).flat_map(|k| {
+        let p = 1u128 << k;
+        [Some(p), p.checked_sub(1), p.checked_sub(5)]
+            .into_iter()
+            .flatten()
+            .filter(|&m| m >= 2)
+    });
+
+    let mut fitted = None;
+    for m in candidates {
+        let reduce = |y: i64| {
+            if m == 1 << 64 {
+                y as u64 as u128
+            } else {
+                (y as i128).rem_euclid(m as i128) as u128
+            }
+        };
+        let a = (reduce(y2) + m - reduce(y1)) % m;
+        let c = (reduce(y1) + m - a) % m;
+        let lcg = Lcg { a, c, m };
+
+        let fits = |y: i64| m == 1 << 64 || (y >= 0 && (y as u128) < m);
+        let mut matched = fits(y1) && fits(y2);
+        for &probe in &probes {
+            if !matched {
+                break;
+            }
+            let x = (probe as u128 % (m.min(1 << 63) - 1)) + 1;
+            let y = f(x as i64)?;
+            matched = fits(y) && reduce(y) == lcg.step(x);
+        }
+        if matched {
+            fitted = Some(lcg);
+            break;
+        }
+    }
+
+    let not_lcg = || "Not recognized as (a * x + c) mod m; enter the parameters by hand".to_string();
+    let lcg = fitted.ok_or_else(not_lcg)?;
+    let m = lcg.m;
+    for seed in [1, (m - 1) as u64 as i64] {
+        let mut state = seed;
+        let mut x = seed as u64 as u128 % m;
+        for _ in 0..VERIFY_OUTPUTS {
+            let y = step(&mut state)?;
+            let expected = lcg.step(x);
+            if (y < 0 && m < 1 << 64) || y as u64 as u128 != expected {
+                return Err(not_lcg());
+            }
+            x = expected;
+        }
+    }
+    Ok(lcg)
+}
+
+/// Parses a decimal or `0x` hex integer, or `2^k` optionally followed by `+ d` / `- d`.
+pub fn parse_param(text: &str) -> Result<u128, String> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace() && *c != '_').collect();
+    let invalid = || format!("Invalid number '{}'", text);
+    let plain = |t: &str| match t.strip_prefix("0x").or_else(|| t.strip_prefix("0X")) {
+        Some(hex) => u128::from_str_radix(hex, 16).map_err(|_| invalid()),
+        None => t.parse::<u128>().map_err(|_| invalid()),
+    };
+
+    let Some(rest) = text.strip_prefix("2^") else {
+        return plain(&text);
+    };
+    let split = rest.find(['+', '-']).unwrap_or(rest.len());
+    let k: u32 = rest[..split].parse().map_err(|_| invalid())?;
+    if k > 64 {
+        return Err(format!("Exponent too large in '{}'", text));
+    }
+    let base = 1u128 << k;
+    match rest[split..].split_at_checked(1) {
+        Some(("+", d)) => Ok(base + plain(d)?),
+        Some(("-", d)) => base.checked_sub(plain(d)?).ok_or_else(invalid),
+        _ => Ok(base),
+    }
+}
+
+/// Writes moduli near a power of two as `2^k`, `2^k-d`; everything else in decimal.
+pub fn format_param(value: u128) -> String {
+    let k = 128 - value.leading_zeros();
+    for (k, sign) in [(k.saturating_sub(1), '+'), (k, '-')] {
+        let base = 1u128 << k.min(127);
+        let d = value.abs_diff(base);
+        if k >= 8 && d < 256 {
+            return match d {
+                0 => format!("2^{}", k),
+                _ => format!("2^{}{}{}", k, sign, d),
+            };
+        }
+    }
+    value.to_string()
+}
+
+/// Dual lattice of t-tuples: integer u with u_1 + u_2 a + ... + u_t a^(t-1) ≡ 0 (mod m).
+fn shortest_dual_vector(lcg: &Lcg, t: usize) -> Vec<i128> {
+    let m = lcg.m as i128;
+    let mut basis = vec![vec![0i128; t]; t];
+    basis[0][0] = m;
+    let mut power = 1u128;
+    for (i, row) in basis.iter_mut().enumerate().skip(1) {
+        power = mul_mod(power, lcg.a, lcg.m);
+        row[0] = (m - power as i128) % m;
+        row[i] = 1;
+    }
+
+    lll_reduce(&mut basis);
+    shortest_vector(&basis)
+}
+
+/// `u · x` over the unit cube lies strictly between these.
+fn level_range(u: &[i128]) -> (i128, i128) {
+    let low = u.iter().filter(|&&x| x < 0).sum();
+    let high = u.iter().filter(|&&x| x > 0).sum();
+    (low, high)
+}
+
+/// Fractional part of `u · x / m` shared by every tuple; zero when c = 0.
+fn plane_offset(lcg: &Lcg, u: &[i128]) -> f64 {
+    // x_{n+j} = a^j x_n + c (1 + a + ... + a^(j-1)); the a^j x_n terms cancel mod m.
+    let m = lcg.m;
+    let mut sum = 0u128;
+    let mut partial = 0u128;
+    for &coeff in u {
+        let term = mul_mod(coeff.rem_euclid(m as i128) as u128, partial, m);
+        sum = (sum + term) % m;
+        partial = (mul_mod(partial, lcg.a, m) + lcg.c) % m;
+    }
+    sum as f64 / m as f64
+}
+
+fn plane_count(u: &[i128], offset: f64) -> u128 {
+    let span: u128 = u.iter().map(|x| x.unsigned_abs()).sum();
+    if offset == 0.0 { span.saturating_sub(1) } else { span }
+}
+
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    // Both operands are below 2^64, so the product fits.
+    (a % m) * (b % m) % m
+}
+
+fn norm(v: &[i128]) -> f64 {
+    v.iter().map(|&x| (x as f64) * (x as f64)).sum::<f64>().sqrt()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Gram-Schmidt coefficients and squared lengths of the orthogonalized basis.
+fn gram_schmidt(basis: &[Vec<i128>]) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let n = basis.len();
+    let mut ortho: Vec<Vec<f64>> = Vec::with_capacity(n);
+    let mut mu = vec![vec![0.0; n]; n];
+    let mut lengths = vec![0.0; n];
+    for i in 0..n {
+        let b: Vec<f64> = basis[i].iter().map(|&x| x as f64).collect();
+        let mut v = b.clone();
+        for j in 0..i {
+            mu[i][j] = if lengths[j] > 0.0 { dot(&b, &ortho[j]) / lengths[j] } else { 0.0 };
+            for (vk, ok) in v.iter_mut().zip(&ortho[j]) {
+                *vk -= mu[i][j] * ok;
+            }
+        }
+        lengths[i] = dot(&v, &v);
+        ortho.push(v);
+    }
+    (mu, lengths)
+}
+
+fn lll_reduce(basis: &mut [Vec<i128>]) {
+    let n = basis.len();
+    let mut k = 1;
+    let mut iterations = 0;
+    while k < n && iterations < LLL_MAX_ITERATIONS {
+        iterations += 1;
+        for j in (0..k).rev() {
+            let (mu, _) = gram_schmidt(basis);
+            let q = mu[k][j].round();
+            if q != 0.0 {
+                let q = q as i128;
+                let (head, tail) = basis.split_at_mut(k);
+                for (x, y) in tail[0].iter_mut().zip(&head[j]) {
+                    *x -= q * y;
+                }
+            }
+        }
+        let (mu, lengths) = gram_schmidt(basis);
+        if lengths[k] >= (LLL_DELTA - mu[k][k - 1] * mu[k][k - 1]) * lengths[k - 1] {
+            k += 1;
+        } else {
+            basis.swap(k, k - 1);
+            k = (k - 1).max(1);
+        }
+    }
+}
+
+/// Exhaustive Fincke-Pohst enumeration; cheap on an LLL-reduced basis of dimension <= 8.
+fn shortest_vector(basis: &[Vec<i128>]) -> Vec<i128> {
+    let (mu, lengths) = gram_schmidt(basis);
+    let mut best = basis
+        .iter()
+        .min_by(|a, b| norm(a).total_cmp(&norm(b)))
+        .cloned()
+        .unwrap_or_default();
+
+    let mut search = Enumeration {
+        basis,
+        mu: &mu,
+        lengths: &lengths,
+        coeffs: vec![0; basis.len()],
+        radius2: norm(&best).powi(2),
+        best: &mut best,
+    };
+    search.descend(basis.len() - 1, 0.0);
+    best
+}
+
+struct Enumeration<'a> {
+    basis: &'a [Vec<i128>],
+    mu: &'a [Vec<f64>],
+    lengths: &'a [f64],
+    coeffs: Vec<i128>,
+    radius2: f64,
+    best: &'a mut Vec<i128>,
+}
+
+impl Enumeration<'_> {
+    fn descend(&mut self, k: usize, partial: f64) {
+        if self.lengths[k] <= 0.0 {
+            return;
+        }
+        let center: f64 = -(k + 1..self.basis.len())
+            .map(|j| self.coeffs[j] as f64 * self.mu[j][k])
+            .sum::<f64>();
+        let reach = ((self.radius2 - partial).max(0.0) / self.lengths[k]).sqrt();
+
+        for x in (center - reach).ceil() as i128..=(center + reach).floor() as i128 {
+            let d = x as f64 - center;
+            let length = partial + d * d * self.lengths[k];
+            if length > self.radius2 * (1.0 + 1e-9) {
+                continue;
+            }
+            self.coeffs[k] = x;
+            if k > 0 {
+                self.descend(k - 1, length);
+            } else if self.coeffs.iter().any(|&c| c != 0) {
+                let mut v = vec![0i128; self.basis.len()];
+                for (c, row) in self.coeffs.iter().zip(self.basis) {
+                    for (vi, r) in v.iter_mut().zip(row) {
+                        *vi += c * r;
+                    }
+                }
+                let n2 = norm(&v).powi(2);
+                if n2 > 0.0 && n2 < norm(self.best).powi(2) {
+                    self.radius2 = n2;
+                    *self.best = v;
+                }
+            }
+        }
+        self.coeffs[k] = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn randu() -> Lcg {
+        Lcg::new(65539, 0, 1 << 31).unwrap()
+    }
+
+    #[test]
+    fn randu_lies_on_15_planes() {
+        let report = spectral_test(&randu());
+        let t3 = &report.dimensions[3 - MIN_DIMENSION];
+        assert_eq!(t3.planes, 15);
+        // 9 x_n - 6 x_{n+1} + x_{n+2} ≡ 0 (mod 2^31), Knuth's example.
+        assert!(t3.shortest == [9, -6, 1] || t3.shortest == [-9, 6, -1]);
+        assert!((t3.nu - 118f64.sqrt()).abs() < 1e-9);
+
+        let planes = hyperplanes_3d(&randu(), 1, 100).unwrap();
+        assert_eq!(planes.levels.len(), 15);
+    }
+
+    #[test]
+    fn lagged_embedding_uses_the_leaped_generator() {
+        let lcg = Lcg::new(48271, 0, (1 << 31) - 1).unwrap();
+        let mut x = 1;
+        for _ in 0..5 {
+            x = lcg.step(x);
+        }
+        assert_eq!(lcg.leap(5).step(1), x);
+    }
+
+    #[test]
+    fn detects_minstd_from_its_outputs() {
+        let m = (1i64 << 31) - 1;
+        let minstd = fit_lcg(|x| {
+            *x = x.wrapping_mul(48271) % m;
+            Ok(*x)
+        })
+        .unwrap();
+        assert!(minstd == Lcg { a: 48271, c: 0, m: m as u128 });
+    }
+
+    #[test]
+    fn rejects_xorshift() {
+        let xorshift = fit_lcg(|x| {
+            *x ^= *x << 13 & 0xFFFF_FFFF;
+            *x ^= *x >> 17;
+            *x ^= *x << 5 & 0xFFFF_FFFF;
+            Ok(*x)
+        });
+        assert!(xorshift.is_err());
+    }
+
+    #[test]
+    fn params_round_trip_through_their_text_form() {
+        for value in [(1u128 << 31) - 1, 1 << 32, (1 << 64) - 59, 65539] {
+            assert_eq!(parse_param(&format_param(value)).unwrap(), value);
+        }
+        assert_eq!(format_param((1 << 31) - 1), "2^31-1");
+    }
+}
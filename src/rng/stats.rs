@@ -0,0 +1,475 @@
+const CHI_SQUARE_BUCKETS: usize = 256;
+const GAP_MAX: usize = 10;
+const POKER_HAND: usize = 5;
+const POKER_CATEGORIES: usize = 4;
+const BIRTHDAY_DAY_BITS: u32 = 24;
+/// Expected collision count the sample size is tuned for; 512 birthdays in 2^24 days.
+const BIRTHDAY_LAMBDA: f64 = 2.0;
+const BIRTHDAY_MAX_COLLISIONS: usize = 6;
+
+const FAIL_THRESHOLD: f64 = 1e-3;
+
+/// Narrowest output the battery bins correctly: chi-square and poker read whole bytes and nibbles.
+pub const MIN_OUTPUT_BITS: u32 = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pending,
+    Pass,
+    Fail,
+}
+
+#[derive(Clone)]
+pub struct TestResult {
+    pub name: &'static str,
+    pub samples: u64,
+    pub statistic: f64,
+    pub p_value: Option<f64>,
+}
+
+impl TestResult {
+    fn pending(name: &'static str, samples: u64) -> Self {
+        Self {
+            name,
+            samples,
+            statistic: 0.0,
+            p_value: None,
+        }
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        match self.p_value {
+            None => Verdict::Pending,
+            Some(p) if (FAIL_THRESHOLD..=1.0 - FAIL_THRESHOLD).contains(&p) => Verdict::Pass,
+            Some(_) => Verdict::Fail,
+        }
+    }
+}
+
+/// Incremental test battery fed with raw generator outputs, before any
+/// coordinate mapping. Outputs are interpreted as `bits`-wide unsigned integers.
+pub struct StatsBattery {
+    bits: u32,
+    total: u64,
+
+    buckets: [u64; CHI_SQUARE_BUCKETS],
+
+    sum: f64,
+    sum_sq: f64,
+    sum_lag: f64,
+    first: Option<f64>,
+    prev: Option<f64>,
+
+    above: u64,
+    below: u64,
+    runs: u64,
+    prev_above: Option<bool>,
+
+    gap_len: usize,
+    gap_counts: [u64; GAP_MAX + 1],
+    gap_total: u64,
+
+    hand: [u8; POKER_HAND],
+    hand_len: usize,
+    poker_counts: [u64; POKER_CATEGORIES],
+    hands: u64,
+
+    birthday_day_bits: u32,
+    birthday_count: usize,
+    birthday_lambda: f64,
+    birthdays: Vec<u64>,
+    birthday_counts: [u64; BIRTHDAY_MAX_COLLISIONS + 1],
+    birthday_samples: u64,
+}
+
+impl StatsBattery {
+    pub fn new(bits: u32) -> Self {
+        let bits = bits.clamp(MIN_OUTPUT_BITS, 64);
+        // Narrow streams get a smaller year, with the birthday count scaled down to
+        // keep λ = m³ / 4n close to the target.
+        let day_bits = bits.min(BIRTHDAY_DAY_BITS);
+        let days = (1u64 << day_bits) as f64;
+        let count = (4.0 * BIRTHDAY_LAMBDA * days).cbrt().round() as usize;
+        Self {
+            bits,
+            total: 0,
+            buckets: [0; CHI_SQUARE_BUCKETS],
+            sum: 0.0,
+            sum_sq: 0.0,
+            sum_lag: 0.0,
+            first: None,
+            prev: None,
+            above: 0,
+            below: 0,
+            runs: 0,
+            prev_above: None,
+            gap_len: 0,
+            gap_counts: [0; GAP_MAX + 1],
+            gap_total: 0,
+            hand: [0; POKER_HAND],
+            hand_len: 0,
+            poker_counts: [0; POKER_CATEGORIES],
+            hands: 0,
+            birthday_day_bits: day_bits,
+            birthday_count: count,
+            birthday_lambda: (count as f64).powi(3) / (4.0 * days),
+            birthdays: Vec::with_capacity(count),
+            birthday_counts: [0; BIRTHDAY_MAX_COLLISIONS + 1],
+            birthday_samples: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.bits);
+    }
+
+    /// Top `k` bits of the output; `k` never exceeds the stream width.
+    #[inline]
+    fn top_bits(&self, raw: i64, k: u32) -> u64 {
+        debug_assert!(k <= self.bits);
+        let v = raw as u64;
+        if self.bits >= 64 {
+            v >> (64 - k)
+        } else {
+            (v & ((1u64 << self.bits) - 1)) >> (self.bits - k)
+        }
+    }
+
+    #[inline]
+    fn unit(&self, raw: i64) -> f64 {
+        let v = raw as u64;
+        if self.bits >= 64 {
+            (v >> 11) as f64 / (1u64 << 53) as f64
+        } else {
+            (v & ((1u64 << self.bits) - 1)) as f64 / (1u64 << self.bits) as f64
+        }
+    }
+
+    pub fn push(&mut self, raw: i64) {
+        self.total += 1;
+
+        self.buckets[self.top_bits(raw, 8) as usize] += 1;
+
+        let u = self.unit(raw);
+        self.sum += u;
+        self.sum_sq += u * u;
+        if let Some(p) = self.prev {
+            self.sum_lag += p * u;
+        } else {
+            self.first = Some(u);
+        }
+        self.prev = Some(u);
+
+        let is_above = u >= 0.5;
+        if is_above {
+            self.above += 1;
+        } else {
+            self.below += 1;
+        }
+        if self.prev_above != Some(is_above) {
+            self.runs += 1;
+        }
+        self.prev_above = Some(is_above);
+
+        if u < 0.5 {
+            self.gap_counts[self.gap_len.min(GAP_MAX)] += 1;
+            self.gap_total += 1;
+            self.gap_len = 0;
+        } else {
+            self.gap_len += 1;
+        }
+
+        self.hand[self.hand_len] = self.top_bits(raw, 4) as u8;
+        self.hand_len += 1;
+        if self.hand_len == POKER_HAND {
+            let mut seen = 0u16;
+            for &card in &self.hand {
+                seen |= 1 << card;
+            }
+            let distinct = seen.count_ones() as usize;
+            self.poker_counts[distinct.saturating_sub(2)] += 1;
+            self.hands += 1;
+            self.hand_len = 0;
+        }
+
+        self.birthdays.push(self.top_bits(raw, self.birthday_day_bits));
+        if self.birthdays.len() == self.birthday_count {
+            self.birthdays.sort_unstable();
+            let mut spacings: Vec<u64> = self
+                .birthdays
+                .iter()
+                .scan(0u64, |last, &day| {
+                    let s = day - *last;
+                    *last = day;
+                    Some(s)
+                })
+                .collect();
+            spacings.sort_unstable();
+            let collisions = spacings.windows(2).filter(|w| w[0] == w[1]).count();
+            self.birthday_counts[collisions.min(BIRTHDAY_MAX_COLLISIONS)] += 1;
+            self.birthday_samples += 1;
+            self.birthdays.clear();
+        }
+    }
+
+    pub fn results(&self) -> Vec<TestResult> {
+        vec![
+            self.chi_square_result(),
+            self.serial_correlation_result(),
+            self.runs_result(),
+            self.gap_result(),
+            self.poker_result(),
+            self.birthday_result(),
+        ]
+    }
+
+    fn chi_square_result(&self) -> TestResult {
+        let name = "Chi-square";
+        let n = self.total;
+        let expected = n as f64 / CHI_SQUARE_BUCKETS as f64;
+        if expected < 5.0 {
+            return TestResult::pending(name, n);
+        }
+        let stat = self
+            .buckets
+            .iter()
+            .map(|&c| (c as f64 - expected).powi(2) / expected)
+            .sum::<f64>();
+        TestResult {
+            name,
+            samples: n,
+            statistic: stat,
+            p_value: Some(chi_square_p(stat, (CHI_SQUARE_BUCKETS - 1) as f64)),
+        }
+    }
+
+    fn serial_correlation_result(&self) -> TestResult {
+        let name = "Serial corr.";
+        let n = self.total;
+        let (Some(first), Some(last)) = (self.first, self.prev) else {
+            return TestResult::pending(name, n);
+        };
+        if n < 100 {
+            return TestResult::pending(name, n);
+        }
+
+        let nf = n as f64;
+        let lag = self.sum_lag + last * first;
+        let denom = nf * self.sum_sq - self.sum * self.sum;
+        if denom <= 0.0 {
+            return TestResult {
+                name,
+                samples: n,
+                statistic: 1.0,
+                p_value: Some(0.0),
+            };
+        }
+        let c = (nf * lag - self.sum * self.sum) / denom;
+
+        let mu = -1.0 / (nf - 1.0);
+        let sigma = (nf * (nf - 3.0) / (nf + 1.0)).sqrt() / (nf - 1.0);
+        TestResult {
+            name,
+            samples: n,
+            statistic: c,
+            p_value: Some(normal_two_sided_p((c - mu) / sigma)),
+        }
+    }
+
+    fn runs_result(&self) -> TestResult {
+        let name = "Runs";
+        let n = self.total;
+        if self.above < 10 || self.below < 10 {
+            return TestResult::pending(name, n);
+        }
+
+        let n1 = self.above as f64;
+        let n2 = self.below as f64;
+        let nf = n1 + n2;
+        let mu = 2.0 * n1 * n2 / nf + 1.0;
+        let var = (mu - 1.0) * (mu - 2.0) / (nf - 1.0);
+        let z = (self.runs as f64 - mu) / var.sqrt();
+        TestResult {
+            name,
+            samples: n,
+            statistic: z,
+            p_value: Some(normal_two_sided_p(z)),
+        }
+    }
+
+    fn gap_result(&self) -> TestResult {
+        let name = "Gap";
+        let n = self.gap_total;
+        let tail = 0.5f64.powi(GAP_MAX as i32);
+        if (n as f64) * tail < 5.0 {
+            return TestResult::pending(name, self.total);
+        }
+
+        let nf = n as f64;
+        let stat = self
+            .gap_counts
+            .iter()
+            .enumerate()
+            .map(|(r, &c)| {
+                let p = if r < GAP_MAX {
+                    0.5 * 0.5f64.powi(r as i32)
+                } else {
+                    tail
+                };
+                let e = nf * p;
+                (c as f64 - e).powi(2) / e
+            })
+            .sum::<f64>();
+        TestResult {
+            name,
+            samples: self.total,
+            statistic: stat,
+            p_value: Some(chi_square_p(stat, GAP_MAX as f64)),
+        }
+    }
+
+    fn poker_result(&self) -> TestResult {
+        let name = "Poker";
+        // P(r distinct values among 5 draws from 16), with r = 1 and r = 2 merged.
+        const PROBS: [f64; POKER_CATEGORIES] = [
+            (16.0 * 1.0 + 16.0 * 15.0 * 15.0) / 1_048_576.0,
+            16.0 * 15.0 * 14.0 * 25.0 / 1_048_576.0,
+            16.0 * 15.0 * 14.0 * 13.0 * 10.0 / 1_048_576.0,
+            16.0 * 15.0 * 14.0 * 13.0 * 12.0 / 1_048_576.0,
+        ];
+
+        let n = self.hands as f64;
+        if n * PROBS[0] < 5.0 {
+            return TestResult::pending(name, self.total);
+        }
+
+        let stat = self
+            .poker_counts
+            .iter()
+            .zip(PROBS)
+            .map(|(&c, p)| (c as f64 - n * p).powi(2) / (n * p))
+            .sum::<f64>();
+        TestResult {
+            name,
+            samples: self.total,
+            statistic: stat,
+            p_value: Some(chi_square_p(stat, (POKER_CATEGORIES - 1) as f64)),
+        }
+    }
+
+    fn birthday_result(&self) -> TestResult {
+        let name = "Birthday sp.";
+        let mut probs = [0.0f64; BIRTHDAY_MAX_COLLISIONS + 1];
+        let lambda = self.birthday_lambda;
+        let mut term = (-lambda).exp();
+        let mut head = 0.0;
+        for (k, p) in probs.iter_mut().take(BIRTHDAY_MAX_COLLISIONS).enumerate() {
+            if k > 0 {
+                term *= lambda / k as f64;
+            }
+            *p = term;
+            head += term;
+        }
+        probs[BIRTHDAY_MAX_COLLISIONS] = 1.0 - head;
+
+        let n = self.birthday_samples as f64;
+        if n * probs[BIRTHDAY_MAX_COLLISIONS] < 5.0 {
+            return TestResult::pending(name, self.total);
+        }
+
+        let stat = self
+            .birthday_counts
+            .iter()
+            .zip(probs)
+            .map(|(&c, p)| (c as f64 - n * p).powi(2) / (n * p))
+            .sum::<f64>();
+        TestResult {
+            name,
+            samples: self.total,
+            statistic: stat,
+            p_value: Some(chi_square_p(stat, BIRTHDAY_MAX_COLLISIONS as f64)),
+        }
+    }
+}
+
+pub fn chi_square_p(stat: f64, df: f64) -> f64 {
+    gamma_q(df / 2.0, stat / 2.0)
+}
+
+pub fn normal_two_sided_p(z: f64) -> f64 {
+    gamma_q(0.5, z * z / 2.0)
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    let x = x - 1.0;
+    let mut a = COEFFS[0];
+    for (i, c) in COEFFS.iter().enumerate().skip(1) {
+        a += c / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x).
+fn gamma_q(a: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-14;
+    const FPMIN: f64 = 1e-300;
+    const MAX_ITER: usize = 1000;
+
+    if x <= 0.0 {
+        return 1.0;
+    }
+
+    let prefix = (-x + a * x.ln() - ln_gamma(a)).exp();
+
+    if x < a + 1.0 {
+        let mut ap = a;
+        let mut del = 1.0 / a;
+        let mut sum = del;
+        for _ in 0..MAX_ITER {
+            ap += 1.0;
+            del *= x / ap;
+            sum += del;
+            if del.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        (1.0 - sum * prefix).clamp(0.0, 1.0)
+    } else {
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / FPMIN;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < FPMIN {
+                d = FPMIN;
+            }
+            c = b + an / c;
+            if c.abs() < FPMIN {
+                c = FPMIN;
+            }
+            d = 1.0 / d;
+            let del = d * c;
+            h *= del;
+            if (del - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        (prefix * h).clamp(0.0, 1.0)
+    }
+}
@@ -0,0 +1,199 @@
+use crossbeam::channel::{self, Receiver, RecvTimeoutError};
+use parking_lot::Mutex;
+use std::fs::File;
+use std::io::{self, Read};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+const READ_SIZE: usize = 1 << 16;
+const CHUNK_QUEUE: usize = 16;
+const WAIT_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// Raw bytes as read; an empty chunk marks the end of input.
+type Chunk = Result<Vec<u8>, String>;
+
+/// Stdin has one reader thread for the whole process, since a second one would race the
+/// first for the same pipe. Sources opened on it share its channel and leave the bytes
+/// they didn't decode to the next one.
+static STDIN: OnceLock<Receiver<Chunk>> = OnceLock::new();
+static STDIN_LEFTOVER: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    U8,
+    U16,
+    U32,
+    U64,
+    /// One integer per line, decimal or `0x` hex; blank lines and `#` comments are skipped.
+    Text,
+}
+
+impl StreamFormat {
+    pub fn bits(self) -> Option<u32> {
+        match self {
+            StreamFormat::U8 => Some(8),
+            StreamFormat::U16 => Some(16),
+            StreamFormat::U32 => Some(32),
+            StreamFormat::U64 => Some(64),
+            StreamFormat::Text => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+#[derive(Clone)]
+pub struct StreamSpec {
+    /// `None` reads stdin.
+    pub path: Option<String>,
+    pub format: StreamFormat,
+    pub endian: Endian,
+}
+
+pub enum Pull {
+    Value(i64),
+    /// Nothing buffered yet; the reader is still waiting on its input.
+    Pending,
+    Ended,
+}
+
+/// Reads an external number stream on its own thread so a slow pipe never blocks the engine,
+/// decoding values as they are pulled.
+pub struct StreamSource {
+    rx: Receiver<Chunk>,
+    format: StreamFormat,
+    endian: Endian,
+    stdin: bool,
+    /// Bytes received but not decoded yet, from `pos` on.
+    bytes: Vec<u8>,
+    pos: usize,
+    /// No more bytes will arrive.
+    eof: bool,
+}
+
+impl StreamSource {
+    pub fn open(spec: &StreamSpec) -> Result<Self, String> {
+        let (rx, bytes) = match &spec.path {
+            Some(path) => {
+                let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+                (spawn_reader(file), Vec::new())
+            }
+            None => (
+                STDIN.get_or_init(|| spawn_reader(io::stdin())).clone(),
+                std::mem::take(&mut *STDIN_LEFTOVER.lock()),
+            ),
+        };
+
+        Ok(Self {
+            rx,
+            format: spec.format,
+            endian: spec.endian,
+            stdin: spec.path.is_none(),
+            bytes,
+            pos: 0,
+            eof: false,
+        })
+    }
+
+    pub fn pull(&mut self) -> Result<Pull, String> {
+        loop {
+            if let Some(value) = self.decode() {
+                return value.map(Pull::Value);
+            }
+            if self.eof {
+                return Ok(Pull::Ended);
+            }
+
+            self.bytes.drain(..self.pos);
+            self.pos = 0;
+            match self.rx.recv_timeout(WAIT_TIMEOUT) {
+                Ok(chunk) => {
+                    let chunk = chunk?;
+                    self.eof = chunk.is_empty();
+                    self.bytes.extend_from_slice(&chunk);
+                }
+                Err(RecvTimeoutError::Timeout) => return Ok(Pull::Pending),
+                Err(RecvTimeoutError::Disconnected) => self.eof = true,
+            }
+        }
+    }
+
+    /// The next complete value in `bytes`, or `None` until more arrive. At the end of
+    /// input a last line without a newline still counts; a partial binary word doesn't.
+    fn decode(&mut self) -> Option<Result<i64, String>> {
+        let rest = &self.bytes[self.pos..];
+
+        let Some(bits) = self.format.bits() else {
+            let mut rest = rest;
+            while !rest.is_empty() {
+                let (line, used) = match rest.iter().position(|&b| b == b'\n') {
+                    Some(i) => (&rest[..i], i + 1),
+                    None if self.eof => (rest, rest.len()),
+                    None => return None,
+                };
+                rest = &rest[used..];
+                self.pos += used;
+
+                let text = String::from_utf8_lossy(line);
+                let text = text.trim();
+                if !text.is_empty() && !text.starts_with('#') {
+                    return Some(parse_int(text));
+                }
+            }
+            return None;
+        };
+
+        let width = bits as usize / 8;
+        let word = rest.get(..width)?;
+        let mut buf = [0u8; 8];
+        buf[..width].copy_from_slice(word);
+        if self.endian == Endian::Big {
+            buf[..width].reverse();
+        }
+        self.pos += width;
+        Some(Ok(u64::from_le_bytes(buf) as i64))
+    }
+}
+
+impl Drop for StreamSource {
+    fn drop(&mut self) {
+        if self.stdin {
+            *STDIN_LEFTOVER.lock() = self.bytes.split_off(self.pos);
+        }
+    }
+}
+
+fn spawn_reader(mut input: impl Read + Send + 'static) -> Receiver<Chunk> {
+    let (tx, rx) = channel::bounded(CHUNK_QUEUE);
+    thread::spawn(move || loop {
+        let mut buf = vec![0u8; READ_SIZE];
+        let chunk = match input.read(&mut buf) {
+            Ok(n) => {
+                buf.truncate(n);
+                Ok(buf)
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => Err(format!("Stream read error: {}", e)),
+        };
+        let done = !matches!(&chunk, Ok(bytes) if !bytes.is_empty());
+        if tx.send(chunk).is_err() || done {
+            return;
+        }
+    });
+    rx
+}
+
+fn parse_int(text: &str) -> Result<i64, String> {
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).map(|v| v as i64),
+        None => text
+            .parse::<i64>()
+            .or_else(|_| text.parse::<u64>().map(|v| v as i64)),
+    };
+    parsed.map_err(|_| format!("Invalid number in stream: '{}'", text))
+}
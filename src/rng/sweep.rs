@@ -0,0 +1,216 @@
+use aelys::Value;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::rng::job::BackgroundJob;
+use crate::rng::program::RngProgram;
+use crate::rng::stats::chi_square_p;
+
+const MAX_SEEDS: usize = 1 << 20;
+const MAX_REPORTED: usize = 1000;
+const PROGRESS_INTERVAL: u64 = 64;
+const CHI_SQUARE_BITS: u32 = 4;
+/// Chance that a sweep flags any unbiased seed at all, split over the seeds (Bonferroni).
+const BIAS_ALPHA: f64 = 1e-3;
+
+#[derive(Clone, Copy)]
+pub enum SeedProblem {
+    /// The state stopped changing after `after` steps.
+    Stuck { after: u64 },
+    ShortCycle { tail: u64, cycle: u64 },
+    /// Chi-square on the top bits failed at either tail, after correcting for the
+    /// number of seeds swept.
+    Biased { p_value: f64 },
+}
+
+#[derive(Clone, Copy)]
+pub struct BadSeed {
+    pub seed: i64,
+    pub problem: SeedProblem,
+}
+
+#[derive(Clone, Default)]
+pub struct SweepProgress {
+    pub checked: u64,
+    pub total: u64,
+    /// Bad seeds found so far, at most the first `MAX_REPORTED`.
+    pub bad: Vec<BadSeed>,
+    pub bad_count: u64,
+    /// How many of `bad_count` are `Biased` rather than stuck or cycling.
+    pub biased_count: u64,
+}
+
+#[derive(Clone, Default)]
+pub enum SweepStatus {
+    #[default]
+    Idle,
+    Running(SweepProgress),
+    Done(SweepProgress),
+    Error(String),
+}
+
+/// Runs a short stream from each seed of a list on a background thread and keeps the bad ones.
+#[derive(Default)]
+pub struct SeedSweeper {
+    job: BackgroundJob<SweepStatus>,
+}
+
+impl SeedSweeper {
+    pub fn status(&self) -> SweepStatus {
+        self.job.status()
+    }
+
+    /// `seeds` is a list such as `0..1000, 42, 0xdead`; each seed gets `length` outputs.
+    pub fn start(&mut self, code: &str, seeds: &str, length: u64, bits: u32) {
+        let seeds = match parse_seeds(seeds) {
+            Ok(seeds) => seeds,
+            Err(e) => {
+                self.job.set_status(SweepStatus::Error(e));
+                return;
+            }
+        };
+
+        let code = code.to_string();
+        let initial = SweepStatus::Running(SweepProgress {
+            total: seeds.len() as u64,
+            ..SweepProgress::default()
+        });
+        self.job.start(initial, move |cancel, status| {
+            match sweep(&code, &seeds, length, bits, cancel, status) {
+                Ok(progress) => SweepStatus::Done(progress),
+                Err(e) => SweepStatus::Error(e),
+            }
+        });
+    }
+}
+
+/// Comma or space separated seeds and ranges (`a..b`, `a..=b`), decimal or `0x` hex.
+pub fn parse_seeds(text: &str) -> Result<Vec<i64>, String> {
+    let mut seeds = Vec::new();
+    for token in text.split([',', ' ', '\n', '\t']).filter(|t| !t.is_empty()) {
+        let range = match token.split_once("..") {
+            Some((start, end)) => match end.strip_prefix('=') {
+                Some(end) => parse_seed(start)?..=parse_seed(end)?,
+                None => parse_seed(start)?..=parse_seed(end)?.saturating_sub(1),
+            },
+            None => {
+                let seed = parse_seed(token)?;
+                seed..=seed
+            }
+        };
+        let count = (*range.end() as i128 - *range.start() as i128 + 1).max(0);
+        if seeds.len() as i128 + count > MAX_SEEDS as i128 {
+            return Err(format!("At most {} seeds per sweep", MAX_SEEDS));
+        }
+        seeds.extend(range);
+    }
+    if seeds.is_empty() {
+        return Err("No seeds to sweep".to_string());
+    }
+    Ok(seeds)
+}
+
+fn parse_seed(text: &str) -> Result<i64, String> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let parsed = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).map(|v| v as i64),
+        None => digits.parse::<i64>(),
+    };
+    let value = parsed.map_err(|_| format!("Invalid seed '{}'", text))?;
+    Ok(if negative { value.wrapping_neg() } else { value })
+}
+
+fn sweep(
+    code: &str,
+    seeds: &[i64],
+    length: u64,
+    bits: u32,
+    cancel: &AtomicBool,
+    status: &Mutex<SweepStatus>,
+) -> Result<SweepProgress, String> {
+    let mut program = RngProgram::compile(code)?;
+    let mut progress = SweepProgress {
+        total: seeds.len() as u64,
+        ..SweepProgress::default()
+    };
+    let mut seen: HashMap<Vec<i64>, u64> = HashMap::with_capacity(length as usize + 1);
+    let threshold = BIAS_ALPHA / seeds.len() as f64;
+
+    for &seed in seeds {
+        if let Some(problem) = check_seed(&mut program, seed, length, bits, threshold, &mut seen)? {
+            progress.bad_count += 1;
+            if let SeedProblem::Biased { .. } = problem {
+                progress.biased_count += 1;
+            }
+            if progress.bad.len() < MAX_REPORTED {
+                progress.bad.push(BadSeed { seed, problem });
+            }
+        }
+        progress.checked += 1;
+
+        if progress.checked.is_multiple_of(PROGRESS_INTERVAL) {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(progress);
+            }
+            *status.lock() = SweepStatus::Running(progress.clone());
+        }
+    }
+
+    Ok(progress)
+}
+
+fn check_seed(
+    program: &mut RngProgram,
+    seed: i64,
+    length: u64,
+    bits: u32,
+    threshold: f64,
+    seen: &mut HashMap<Vec<i64>, u64>,
+) -> Result<Option<SeedProblem>, String> {
+    let bits = bits.clamp(CHI_SQUARE_BITS, 64);
+    let mut buckets = [0u64; 1 << CHI_SQUARE_BITS];
+
+    let mut state = program.seed_state(seed);
+    let words = |state: &[Value]| {
+        state
+            .iter()
+            .map(|v| v.as_int().unwrap_or(0))
+            .collect::<Vec<_>>()
+    };
+    seen.clear();
+    seen.insert(words(&state), 0);
+
+    for step in 1..=length {
+        let output = program.step(&mut state)? as u64;
+        buckets[((output << (64 - bits)) >> (64 - CHI_SQUARE_BITS)) as usize] += 1;
+
+        if let Some(first) = seen.insert(words(&state), step) {
+            let cycle = step - first;
+            return Ok(Some(if cycle == 1 {
+                SeedProblem::Stuck { after: first }
+            } else {
+                SeedProblem::ShortCycle { tail: first, cycle }
+            }));
+        }
+    }
+
+    let expected = length as f64 / buckets.len() as f64;
+    if expected < 5.0 {
+        return Ok(None);
+    }
+    let stat: f64 = buckets
+        .iter()
+        .map(|&c| (c as f64 - expected).powi(2) / expected)
+        .sum();
+    let p_value = chi_square_p(stat, (buckets.len() - 1) as f64);
+    // Two-sided, so each tail gets half of the threshold.
+    if (threshold / 2.0..=1.0 - threshold / 2.0).contains(&p_value) {
+        Ok(None)
+    } else {
+        Ok(Some(SeedProblem::Biased { p_value }))
+    }
+}
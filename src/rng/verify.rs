@@ -0,0 +1,104 @@
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::rng::job::BackgroundJob;
+use crate::rng::native::NativeGenerator;
+use crate::rng::sampler::Sampler;
+
+const PROGRESS_INTERVAL: u64 = 1 << 16;
+
+#[derive(Clone)]
+pub enum Reference {
+    Native(&'static NativeGenerator),
+    Aelys(String),
+}
+
+#[derive(Clone)]
+pub struct Divergence {
+    pub index: u64,
+    pub user_output: i64,
+    pub reference_output: i64,
+    pub user_state: Vec<i64>,
+    pub reference_state: Vec<i64>,
+}
+
+#[derive(Clone, Default)]
+pub enum VerifyStatus {
+    #[default]
+    Idle,
+    Running {
+        checked: u64,
+    },
+    Match {
+        checked: u64,
+    },
+    Diverged(Divergence),
+    Error(String),
+}
+
+/// Steps a user generator and a reference in lockstep on a background thread.
+#[derive(Default)]
+pub struct Verifier {
+    job: BackgroundJob<VerifyStatus>,
+}
+
+impl Verifier {
+    pub fn status(&self) -> VerifyStatus {
+        self.job.status()
+    }
+
+    /// Outputs are compared on their low `bits` bits, the declared output width.
+    pub fn start(&mut self, code: &str, reference: Reference, seed: i64, count: u64, bits: u32) {
+        let code = code.to_string();
+        self.job.start(VerifyStatus::Running { checked: 0 }, move |cancel, status| {
+            verify(&code, &reference, seed, count, bits, cancel, status).unwrap_or_else(VerifyStatus::Error)
+        });
+    }
+}
+
+fn verify(
+    code: &str,
+    reference: &Reference,
+    seed: i64,
+    count: u64,
+    bits: u32,
+    cancel: &AtomicBool,
+    status: &Mutex<VerifyStatus>,
+) -> Result<VerifyStatus, String> {
+    let mut user = Sampler::aelys(code, seed)?;
+    let mut reference = match reference {
+        // Seeding differs between the two sides, so start from the program's own words.
+        Reference::Native(generator) => Sampler::native_from_state(generator, &user.state())?,
+        Reference::Aelys(ref_code) => {
+            Sampler::aelys(ref_code, seed).map_err(|e| format!("Reference: {}", e))?
+        }
+    };
+
+    let mask = if bits >= 64 { u64::MAX } else { (1u64 << bits) - 1 };
+
+    for index in 0..count {
+        let user_output = user.next_value()?;
+        let reference_output = reference
+            .next_value()
+            .map_err(|e| format!("Reference: {}", e))?;
+
+        if (user_output as u64 ^ reference_output as u64) & mask != 0 {
+            return Ok(VerifyStatus::Diverged(Divergence {
+                index,
+                user_output,
+                reference_output,
+                user_state: user.state(),
+                reference_state: reference.state(),
+            }));
+        }
+
+        if (index + 1).is_multiple_of(PROGRESS_INTERVAL) {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(VerifyStatus::Idle);
+            }
+            *status.lock() = VerifyStatus::Running { checked: index + 1 };
+        }
+    }
+
+    Ok(VerifyStatus::Match { checked: count })
+}
@@ -2,6 +2,6 @@ pub mod panels;
 pub mod state;
 pub mod theme;
 
-pub use panels::{UiActions, draw_help_overlay, draw_side_panel};
+pub use panels::{EngineReadout, UiActions, draw_compare_overlay, draw_help_overlay, draw_pick_tooltip, draw_side_panel};
 pub use state::UiState;
 pub use theme::apply_theme;
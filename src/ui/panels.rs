@@ -1,18 +1,66 @@
 use egui::{Color32, Context, RichText, ScrollArea, TextEdit, Ui};
+use std::sync::Arc;
 use std::sync::atomic::Ordering;
 
 use crate::math::MATH_EXAMPLES;
 use crate::math::examples::MathFunctionKind;
-use crate::renderer::CameraMode;
-use crate::rng::{Bottleneck, PerformanceStats, RNG_EXAMPLES};
-use crate::ui::state::{AppMode, MathViewMode, UiState, ViewMode};
+use crate::math::ode::OdeMethod;
+use crate::renderer::{CameraMode, Colormap, ToneMap};
+use crate::rng::{
+    AvalancheStatus, BitPlot, Bottleneck, Embedding, Endian, HistoryEntry, MAX_STREAMS, MIN_OUTPUT_BITS, MappingMode, NATIVE_GENERATORS, PerformanceStats, PeriodStatus, PointOrigin, RNG_EXAMPLES, RngEngine,
+    StreamFormat, SweepStatus, TestResult, Verdict, VerifyStatus,
+};
+use crate::rng::spectral::format_param;
+use crate::rng::sweep::SeedProblem;
+use crate::ui::state::{AppMode, MathViewMode, PointColor, UiState, VerifyAgainst, ViewMode};
 use crate::ui::theme::*;
 
+const ENGINE_LABELS: [&str; 2] = ["A", "B"];
+
+/// Per-frame copy of what the panel shows for one generator.
+pub struct EngineReadout {
+    pub stats: Arc<PerformanceStats>,
+    pub test_results: Vec<TestResult>,
+    pub period: PeriodStatus,
+    pub avalanche: AvalancheStatus,
+    pub history: Arc<Vec<HistoryEntry>>,
+    pub last_error: Option<String>,
+}
+
+impl EngineReadout {
+    pub fn from_engine(engine: &RngEngine) -> Self {
+        Self {
+            stats: Arc::clone(engine.stats()),
+            test_results: engine.test_results(),
+            period: engine.period(),
+            avalanche: engine.avalanche(),
+            history: engine.history(),
+            last_error: engine.last_error(),
+        }
+    }
+}
+
 pub struct UiActions {
     pub compile_code: bool,
+    pub compile_code_b: bool,
     pub reset_rng: bool,
     pub set_seed: Option<i64>,
+    pub set_seed_b: Option<i64>,
+    pub set_compare: bool,
+    pub open_stream: bool,
+    /// Engine slot (0 = A, 1 = B) to switch to the selected native generator.
+    pub run_native: Option<usize>,
+    pub start_verify: bool,
+    pub start_sweep: bool,
+    pub run_avalanche: bool,
+    pub detect_lcg: bool,
+    pub run_spectral: bool,
+    pub set_embedding: bool,
+    pub set_mapping: bool,
+    pub set_streams: bool,
     pub toggle_pause: bool,
+    pub step: Option<u64>,
+    pub set_history: bool,
     pub clear_points: bool,
     pub compile_math: bool,
 }
@@ -21,9 +69,24 @@ impl Default for UiActions {
     fn default() -> Self {
         Self {
             compile_code: false,
+            compile_code_b: false,
             reset_rng: false,
             set_seed: None,
+            set_seed_b: None,
+            set_compare: false,
+            open_stream: false,
+            run_native: None,
+            start_verify: false,
+            start_sweep: false,
+            run_avalanche: false,
+            detect_lcg: false,
+            run_spectral: false,
+            set_embedding: false,
+            set_mapping: false,
+            set_streams: false,
             toggle_pause: false,
+            step: None,
+            set_history: false,
             clear_points: false,
             compile_math: false,
         }
@@ -33,8 +96,10 @@ impl Default for UiActions {
 pub fn draw_side_panel(
     ctx: &Context,
     state: &mut UiState,
-    stats: &PerformanceStats,
-    last_error: &Option<String>,
+    primary: &EngineReadout,
+    secondary: Option<&EngineReadout>,
+    verify: &VerifyStatus,
+    sweep: &SweepStatus,
     is_paused: bool,
 ) -> UiActions {
     let mut actions = UiActions::default();
@@ -108,6 +173,8 @@ pub fn draw_side_panel(
                                         state.selected_example = i;
                                         state.code = ex.code.to_string();
                                         state.code_needs_compile = true;
+                                        state.mapping.output_bits = ex.bits;
+                                        actions.set_mapping = true;
                                     }
                                 }
                             });
@@ -119,7 +186,10 @@ pub fn draw_side_panel(
                         ui.add_space(16.0);
 
                         section_header(ui, "AELYS CODE");
-                        code_editor(ui, &mut state.code, last_error);
+                        ui.label(RichText::new("Define: fn rng(s0, ..) -> int, optional fn output(s0, ..) and fn rng_<i>(s0, ..)")
+                            .color(TEXT_MUTED).size(10.0).italics());
+                        ui.add_space(4.0);
+                        code_editor(ui, "rng_code", &mut state.code, &primary.last_error);
                         ui.add_space(8.0);
                         let btn_text = if state.code_needs_compile { "Compile & Run" } else { "Running..." };
                         let btn_color = if state.code_needs_compile { ACCENT_GREEN } else { BG_WIDGET };
@@ -145,15 +215,54 @@ pub fn draw_side_panel(
                             if ui.selectable_label(state.view_mode == ViewMode::Mode2D, "2D").clicked() {
                                 state.view_mode = ViewMode::Mode2D;
                             }
+                            if ui.selectable_label(state.view_mode == ViewMode::Bits, "Bits").clicked() {
+                                state.view_mode = ViewMode::Bits;
+                            }
                         });
-                        if state.view_mode == ViewMode::Mode3D {
-                            camera_controls(ui, &mut state.camera_mode);
-                        } else {
+                        ui.horizontal(|ui| {
+                            ui.label("Embed:");
+                            for (mode, label) in [
+                                (Embedding::Triples, "Triples"),
+                                (Embedding::Lagged, "Lagged"),
+                                (Embedding::Differences, "Diffs"),
+                            ] {
+                                if ui.selectable_label(state.embedding == mode, label).clicked() && state.embedding != mode {
+                                    state.embedding = mode;
+                                    actions.set_embedding = true;
+                                    actions.clear_points = true;
+                                }
+                            }
+                        });
+                        if state.embedding == Embedding::Lagged {
                             ui.horizontal(|ui| {
-                                ui.label("Grid:");
-                                ui.add(egui::Slider::new(&mut state.grid_size, 128..=1024).suffix("px"));
+                                ui.label("Lag:");
+                                if ui.add(egui::Slider::new(&mut state.embedding_lag, 1..=256)).changed() {
+                                    actions.set_embedding = true;
+                                    actions.clear_points = true;
+                                }
                             });
                         }
+                        match state.view_mode {
+                            ViewMode::Mode3D => {
+                                camera_controls(ui, &mut state.camera_mode);
+                                density_controls(ui, state);
+                                if !state.density {
+                                    point_color_controls(ui, state);
+                                }
+                            }
+                            ViewMode::Mode2D => {
+                                ui.horizontal(|ui| {
+                                    ui.label("Grid:");
+                                    ui.add(egui::Slider::new(&mut state.grid_size, 128..=1024).suffix("px"));
+                                });
+                                tone_map_controls(ui, state);
+                            }
+                            ViewMode::Bits => bit_plot_controls(ui, state),
+                        }
+                        ui.add_space(16.0);
+
+                        section_header(ui, "MAPPING");
+                        mapping_controls(ui, state, &mut actions);
                         ui.add_space(16.0);
 
                         section_header(ui, "BOUNDS");
@@ -183,19 +292,47 @@ pub fn draw_side_panel(
                         });
                         ui.add_space(16.0);
 
+                        debugger_controls(ui, state, primary, is_paused, &mut actions);
+                        ui.add_space(16.0);
+
+                        native_controls(ui, state, &mut actions);
+                        ui.add_space(16.0);
+
+                        verify_controls(ui, state, verify, &mut actions);
+                        ui.add_space(16.0);
+
+                        sweep_controls(ui, state, sweep, &mut actions);
+                        ui.add_space(16.0);
+
+                        let engines: Vec<&EngineReadout> = std::iter::once(primary).chain(secondary).collect();
+                        avalanche_controls(ui, state, &engines, &mut actions);
+                        ui.add_space(16.0);
+
+                        spectral_controls(ui, state, &mut actions);
+                        ui.add_space(16.0);
+
+                        stream_controls(ui, state, &mut actions);
+                        ui.add_space(16.0);
+
+                        compare_controls(ui, state, secondary, &mut actions);
+                        ui.add_space(16.0);
+
                         perf_controls(ui, state);
+                        stream_count_controls(ui, state, &mut actions);
                         ui.add_space(16.0);
 
                         ui.separator();
                         ui.add_space(12.0);
 
                         if state.show_stats {
-                            stats_panel(ui, stats, is_paused);
+                            stats_panel(ui, &engines, is_paused);
+                            ui.add_space(16.0);
+                            tests_panel(ui, &engines);
                         }
                     }
                     AppMode::Math => {
                         section_header(ui, "FUNCTION TYPE");
-                        ui.horizontal(|ui| {
+                        ui.horizontal_wrapped(|ui| {
                             if ui.selectable_label(state.math_function_type == MathFunctionKind::Surface, "Surface z=f(x,y)").clicked() {
                                 state.math_function_type = MathFunctionKind::Surface;
                                 state.math_needs_compile = true;
@@ -208,6 +345,27 @@ pub fn draw_side_panel(
                                 state.math_function_type = MathFunctionKind::ParametricSurface;
                                 state.math_needs_compile = true;
                             }
+                            if ui.selectable_label(state.math_function_type == MathFunctionKind::Implicit, "Implicit f(x,y,z)=c").clicked() {
+                                state.math_function_type = MathFunctionKind::Implicit;
+                                state.math_needs_compile = true;
+                            }
+                            if ui.selectable_label(state.math_function_type == MathFunctionKind::Ode, "ODE Attractor").clicked() {
+                                state.math_function_type = MathFunctionKind::Ode;
+                                state.math_needs_compile = true;
+                            }
+                            if ui.selectable_label(state.math_function_type == MathFunctionKind::VectorField, "Vector Field").clicked() {
+                                state.math_function_type = MathFunctionKind::VectorField;
+                                state.math_needs_compile = true;
+                            }
+                            if ui.selectable_label(state.math_function_type == MathFunctionKind::Complex, "Complex f(x+iy)").clicked() {
+                                state.math_function_type = MathFunctionKind::Complex;
+                                state.math_needs_compile = true;
+                            }
+                            if ui.selectable_label(state.math_function_type == MathFunctionKind::Fractal, "Escape-time Fractal").clicked() {
+                                state.math_function_type = MathFunctionKind::Fractal;
+                                state.math_view_mode = MathViewMode::Mode2D;
+                                state.math_needs_compile = true;
+                            }
                         });
                         ui.add_space(12.0);
 
@@ -229,11 +387,18 @@ pub fn draw_side_panel(
                                             state.math_code = ex.code.to_string();
                                             state.math_x_range = (ex.x_range.0 as f32, ex.x_range.1 as f32);
                                             state.math_y_range = (ex.y_range.0 as f32, ex.y_range.1 as f32);
+                                            state.math_z_range = (ex.z_range.0 as f32, ex.z_range.1 as f32);
+                                            state.math_iso_level = 0.0;
+                                            state.math_initial = ex.initial.map(|v| v as f32);
+                                            state.math_ode_step = ex.step as f32;
                                             state.math_t_range = (ex.t_range.0 as f32, ex.t_range.1 as f32);
                                             state.math_u_range = (ex.u_range.0 as f32, ex.u_range.1 as f32);
                                             state.math_v_range = (ex.v_range.0 as f32, ex.v_range.1 as f32);
                                             state.math_u_samples = ex.u_samples as u32;
                                             state.math_v_samples = ex.v_samples as u32;
+                                            if ex.function_type == MathFunctionKind::Fractal {
+                                                state.reset_fractal_view();
+                                            }
                                             state.math_needs_compile = true;
                                             actions.compile_math = true;
                                         }
@@ -253,10 +418,15 @@ pub fn draw_side_panel(
                             MathFunctionKind::Surface => "Define: fn f(x: float, y: float) -> float",
                             MathFunctionKind::ParametricCurve => "Define: fn fx(t), fy(t), fz(t) -> float",
                             MathFunctionKind::ParametricSurface => "Define: fn fx(u, v), fy(u, v), fz(u, v) -> float",
+                            MathFunctionKind::Implicit => "Define: fn f(x: float, y: float, z: float) -> float",
+                            MathFunctionKind::Ode => "Define: fn dx(x, y, z, t), dy(x, y, z, t), dz(x, y, z, t) -> float",
+                            MathFunctionKind::VectorField => "Define: fn vx(x, y, z), vy(x, y, z), vz(x, y, z) -> float",
+                            MathFunctionKind::Complex => "Define: fn re(x, y), im(x, y) -> float, for f(x + iy)",
+                            MathFunctionKind::Fractal => "Define: fn next_re(zr, zi, cr, ci), next_im(zr, zi, cr, ci) -> float",
                         };
                         ui.label(RichText::new(hint).color(TEXT_MUTED).size(10.0).italics());
                         ui.add_space(4.0);
-                        code_editor(ui, &mut state.math_code, last_error);
+                        code_editor(ui, "math_code", &mut state.math_code, &primary.last_error);
                         ui.add_space(8.0);
 
                         let (btn_text, btn_color, text_color) = if state.math_needs_compile {
@@ -278,7 +448,7 @@ pub fn draw_side_panel(
                         section_header(ui, "PARAMETERS");
                         let mut changed = false;
                         match state.math_function_type {
-                            MathFunctionKind::Surface => {
+                            MathFunctionKind::Surface | MathFunctionKind::Complex => {
                                 changed |= range_controls(ui, "X", &mut state.math_x_range);
                                 changed |= range_controls(ui, "Y", &mut state.math_y_range);
                                 ui.add_space(8.0);
@@ -310,6 +480,123 @@ pub fn draw_side_panel(
                                 ui.label("V Samples:");
                                 changed |= ui.add(egui::Slider::new(&mut state.math_v_samples, 10..=200)).changed();
                             }
+                            MathFunctionKind::Implicit => {
+                                changed |= range_controls(ui, "X", &mut state.math_x_range);
+                                changed |= range_controls(ui, "Y", &mut state.math_y_range);
+                                changed |= range_controls(ui, "Z", &mut state.math_z_range);
+                                ui.add_space(8.0);
+                                ui.horizontal(|ui| {
+                                    ui.label("Resolution:");
+                                    changed |= ui.add(egui::Slider::new(&mut state.math_implicit_resolution, 16..=128)).changed();
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Iso-level:");
+                                    changed |= ui.add(egui::DragValue::new(&mut state.math_iso_level).speed(0.01)).changed();
+                                });
+                                ui.label(RichText::new("Moving the iso-level reuses the sampled grid")
+                                    .color(TEXT_MUTED).size(10.0).italics());
+                            }
+                            MathFunctionKind::Ode => {
+                                ui.horizontal(|ui| {
+                                    ui.label("t min:");
+                                    changed |= ui.add(egui::DragValue::new(&mut state.math_t_range.0).speed(0.1)).changed();
+                                    ui.label("max:");
+                                    changed |= ui.add(egui::DragValue::new(&mut state.math_t_range.1).speed(0.1)).changed();
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Start:");
+                                    for v in &mut state.math_initial {
+                                        changed |= ui.add(egui::DragValue::new(v).speed(0.01)).changed();
+                                    }
+                                });
+                                ui.add_space(8.0);
+                                ui.horizontal(|ui| {
+                                    ui.label("Method:");
+                                    changed |= ui.selectable_value(&mut state.math_ode_method, OdeMethod::Rk4, "RK4").changed();
+                                    changed |= ui.selectable_value(&mut state.math_ode_method, OdeMethod::Rk45, "RK45").changed();
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(if state.math_ode_method == OdeMethod::Rk4 { "Step:" } else { "Max step:" });
+                                    changed |= ui.add(egui::DragValue::new(&mut state.math_ode_step)
+                                        .speed(0.001).range(0.0001..=1.0)).changed();
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Render:");
+                                    ui.selectable_value(&mut state.math_ode_points, false, "Line");
+                                    ui.selectable_value(&mut state.math_ode_points, true, "Points");
+                                });
+                                if state.math_ode_points {
+                                    colormap_controls(ui, &mut state.colormap);
+                                } else {
+                                    ui.label(RichText::new("Long runs are thinned to 10k vertices; Points draws them all")
+                                        .color(TEXT_MUTED).size(10.0).italics());
+                                }
+                            }
+                            MathFunctionKind::VectorField => {
+                                changed |= range_controls(ui, "X", &mut state.math_x_range);
+                                changed |= range_controls(ui, "Y", &mut state.math_y_range);
+                                changed |= range_controls(ui, "Z", &mut state.math_z_range);
+                                ui.add_space(8.0);
+                                ui.horizontal(|ui| {
+                                    ui.label("Arrows:");
+                                    changed |= ui.add(egui::Slider::new(&mut state.math_field_resolution, 4..=24).suffix(" per axis")).changed();
+                                });
+                                colormap_controls(ui, &mut state.colormap);
+                                ui.add_space(8.0);
+                                changed |= ui.checkbox(&mut state.math_streamlines, "Streamlines").changed();
+                                if state.math_streamlines {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Seed plane:");
+                                        for (axis, label) in ["X", "Y", "Z"].into_iter().enumerate() {
+                                            changed |= ui.selectable_value(&mut state.math_stream_axis, axis, label).changed();
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("At:");
+                                        changed |= ui.add(egui::Slider::new(&mut state.math_stream_offset, 0.0..=1.0)).changed();
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("Seeds:");
+                                        changed |= ui.add(egui::Slider::new(&mut state.math_stream_seeds, 1..=12).suffix(" per side")).changed();
+                                    });
+                                }
+                            }
+                            MathFunctionKind::Fractal => {
+                                ui.horizontal(|ui| {
+                                    ui.label("Set:");
+                                    changed |= ui.selectable_value(&mut state.fractal_julia, false, "Over c").changed();
+                                    changed |= ui.selectable_value(&mut state.fractal_julia, true, "Julia").changed();
+                                });
+                                if state.fractal_julia {
+                                    ui.horizontal(|ui| {
+                                        ui.label("c:");
+                                        changed |= ui.add(egui::DragValue::new(&mut state.fractal_c[0]).speed(0.001)).changed();
+                                        changed |= ui.add(egui::DragValue::new(&mut state.fractal_c[1]).speed(0.001).suffix("i")).changed();
+                                    });
+                                }
+                                ui.horizontal(|ui| {
+                                    ui.label("Iterations:");
+                                    changed |= ui.add(egui::Slider::new(&mut state.fractal_max_iterations, 16..=4096).logarithmic(true)).changed();
+                                });
+                                ui.add_space(8.0);
+                                colormap_controls(ui, &mut state.colormap);
+                                ui.horizontal(|ui| {
+                                    ui.label("Cycle:");
+                                    ui.add(egui::Slider::new(&mut state.fractal_cycle, 4.0..=256.0).logarithmic(true).suffix(" iterations"));
+                                });
+                                ui.add_space(8.0);
+                                ui.horizontal(|ui| {
+                                    let [re, im] = state.fractal_center;
+                                    ui.label(RichText::new(format!("{:.6} {:+.6}i, ±{:.2e}", re, im, state.fractal_scale))
+                                        .color(TEXT_MUTED).size(11.0));
+                                    if ui.button("Reset view").clicked() {
+                                        state.reset_fractal_view();
+                                        changed = true;
+                                    }
+                                });
+                                ui.label(RichText::new("In the 2D view, drag to pan and scroll to zoom")
+                                    .color(TEXT_MUTED).size(10.0).italics());
+                            }
                         }
                         if changed {
                             state.math_needs_compile = true;
@@ -347,7 +634,7 @@ fn section_header(ui: &mut Ui, text: &str) {
     ui.add_space(4.0);
 }
 
-fn code_editor(ui: &mut Ui, code: &mut String, error: &Option<String>) {
+fn code_editor(ui: &mut Ui, id: &str, code: &mut String, error: &Option<String>) {
     let frame = egui::Frame::default()
         .fill(BG_PURE_BLACK)
         .stroke(egui::Stroke::new(1.0, BORDER_SUBTLE))
@@ -355,7 +642,7 @@ fn code_editor(ui: &mut Ui, code: &mut String, error: &Option<String>) {
         .inner_margin(8.0);
 
     frame.show(ui, |ui| {
-        ScrollArea::vertical().max_height(180.0).show(ui, |ui| {
+        ScrollArea::vertical().id_salt(id).max_height(180.0).show(ui, |ui| {
             ui.horizontal_top(|ui| {
                 let lines = code.lines().count().max(1);
                 let line_nums: String = (1..=lines).map(|n| format!("{:3}\n", n)).collect();
@@ -404,6 +691,546 @@ fn camera_controls(ui: &mut Ui, mode: &mut CameraMode) {
     });
 }
 
+fn point_color_controls(ui: &mut Ui, state: &mut UiState) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Color:");
+        for (color, label) in [
+            (PointColor::Position, "Position"),
+            (PointColor::Age, "Age"),
+            (PointColor::Index, "Index"),
+            (PointColor::Batch, "Batch"),
+            (PointColor::Stream, "Stream"),
+        ] {
+            if ui.selectable_label(state.point_color == color, label).clicked() {
+                state.point_color = color;
+            }
+        }
+    });
+    if state.point_color == PointColor::Position {
+        return;
+    }
+
+    colormap_controls(ui, &mut state.colormap);
+    if state.point_color != PointColor::Stream {
+        ui.horizontal(|ui| {
+            ui.label("Period:");
+            ui.add(egui::DragValue::new(&mut state.color_period).range(0..=1_000_000).speed(1.0));
+        });
+    }
+    let hint = match state.point_color {
+        PointColor::Position => "",
+        PointColor::Age => "Oldest point on screen to newest.",
+        PointColor::Index => "Call index since the last reseed; a period shows index mod N.",
+        PointColor::Batch => "Batch the point arrived in; stripes mean the pattern changes over time.",
+        PointColor::Stream => "One colour per generator stream.",
+    };
+    ui.label(RichText::new(hint).color(TEXT_MUTED).size(10.0).italics());
+}
+
+fn density_controls(ui: &mut Ui, state: &mut UiState) {
+    ui.horizontal(|ui| {
+        ui.label("Render:");
+        ui.selectable_value(&mut state.density, false, "Points");
+        ui.selectable_value(&mut state.density, true, "Density");
+    });
+    if state.density {
+        tone_map_controls(ui, state);
+    }
+}
+
+fn tone_map_controls(ui: &mut Ui, state: &mut UiState) {
+    ui.horizontal(|ui| {
+        ui.label("Tone:");
+        ui.selectable_value(&mut state.tone_map, ToneMap::Log, "Log");
+        ui.selectable_value(&mut state.tone_map, ToneMap::Equalize, "Equalize");
+    });
+    colormap_controls(ui, &mut state.colormap);
+    let hint = match state.tone_map {
+        ToneMap::Log => "Hits per pixel or cell on a log scale, up to the densest one.",
+        ToneMap::Equalize => "Each colour covers the same area; shows faint structure.",
+    };
+    ui.label(RichText::new(hint).color(TEXT_MUTED).size(10.0).italics());
+}
+
+fn colormap_controls(ui: &mut Ui, colormap: &mut Colormap) {
+    ui.horizontal(|ui| {
+        ui.label("Map:");
+        for (map, label) in [
+            (Colormap::Viridis, "Viridis"),
+            (Colormap::Magma, "Magma"),
+            (Colormap::Turbo, "Turbo"),
+            (Colormap::Cyclic, "Cyclic"),
+        ] {
+            if ui.selectable_label(*colormap == map, label).clicked() {
+                *colormap = map;
+            }
+        }
+    });
+}
+
+fn bit_plot_controls(ui: &mut Ui, state: &mut UiState) {
+    ui.horizontal(|ui| {
+        ui.label("Plot:");
+        for (plot, label) in [
+            (BitPlot::Raster, "Raster"),
+            (BitPlot::Frequency, "Freq"),
+            (BitPlot::Correlation, "Corr"),
+            (BitPlot::Avalanche, "Avalanche"),
+        ] {
+            if ui.selectable_label(state.bit_plot == plot, label).clicked() {
+                state.bit_plot = plot;
+            }
+        }
+    });
+    if state.bit_plot == BitPlot::Correlation {
+        ui.horizontal(|ui| {
+            ui.label("Lag:");
+            ui.selectable_value(&mut state.bit_lag, 0, "Same output");
+            ui.selectable_value(&mut state.bit_lag, 1, "Previous");
+        });
+    }
+    let hint = match state.bit_plot {
+        BitPlot::Raster => "Last 256 outputs, newest on top, MSB left.",
+        BitPlot::Frequency => "Ones per bit; mid colour is 1/2, ends are 4 sigma.",
+        BitPlot::Correlation => "Bit i (row) against bit j (column); mid colour is independent.",
+        BitPlot::Avalanche => "Input bit (row) against output bit (column); dark is a 1/2 flip rate.",
+    };
+    ui.label(RichText::new(hint).color(TEXT_MUTED).size(10.0).italics());
+}
+
+fn mapping_controls(ui: &mut Ui, state: &mut UiState, actions: &mut UiActions) {
+    let mut changed = false;
+    let mapping = &mut state.mapping;
+    ui.horizontal(|ui| {
+        ui.label("Mode:");
+        for (mode, label) in [
+            (MappingMode::Scale, "Scale"),
+            (MappingMode::Modulo, "Mod"),
+            (MappingMode::TopBits, "Top"),
+            (MappingMode::LowBits, "Low"),
+            (MappingMode::Float, "Float"),
+        ] {
+            if ui.selectable_label(mapping.mode == mode, label).clicked() && mapping.mode != mode {
+                mapping.mode = mode;
+                changed = true;
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Output bits:");
+        changed |= ui.add(egui::Slider::new(&mut mapping.output_bits, MIN_OUTPUT_BITS..=64)).changed();
+    });
+    if matches!(mapping.mode, MappingMode::TopBits | MappingMode::LowBits) {
+        mapping.field_bits = mapping.field_bits.min(mapping.output_bits);
+        ui.horizontal(|ui| {
+            ui.label("Field bits:");
+            changed |= ui.add(egui::Slider::new(&mut mapping.field_bits, 1..=mapping.output_bits)).changed();
+        });
+    }
+    if changed {
+        actions.set_mapping = true;
+        actions.clear_points = true;
+    }
+}
+
+fn native_controls(ui: &mut Ui, state: &mut UiState, actions: &mut UiActions) {
+    section_header(ui, "NATIVE REFERENCE");
+    egui::ComboBox::from_id_salt("native_generators")
+        .selected_text(NATIVE_GENERATORS[state.selected_native].name)
+        .width(ui.available_width())
+        .show_ui(ui, |ui| {
+            for (i, generator) in NATIVE_GENERATORS.iter().enumerate() {
+                ui.selectable_value(&mut state.selected_native, i, generator.name);
+            }
+        });
+    let generator = &NATIVE_GENERATORS[state.selected_native];
+    ui.add_space(4.0);
+    ui.label(RichText::new(generator.description).color(TEXT_MUTED).size(11.0).italics());
+    ui.horizontal(|ui| {
+        if ui.button("Run as A").clicked() {
+            state.mapping.output_bits = generator.bits;
+            state.code_needs_compile = true;
+            actions.run_native = Some(0);
+        }
+        if ui.add_enabled(state.compare_enabled, egui::Button::new("Run as B")).clicked() {
+            state.output_bits_b = generator.bits;
+            state.code_b_needs_compile = true;
+            actions.run_native = Some(1);
+        }
+    });
+    if actions.run_native.is_some() {
+        actions.set_mapping = true;
+        actions.clear_points = true;
+    }
+}
+
+fn debugger_controls(ui: &mut Ui, state: &mut UiState, engine: &EngineReadout, is_paused: bool, actions: &mut UiActions) {
+    section_header(ui, "DEBUGGER");
+    ui.horizontal(|ui| {
+        if ui.add_enabled(is_paused, egui::Button::new("Step")).clicked() {
+            actions.step = Some(1);
+        }
+        if ui.add_enabled(is_paused, egui::Button::new("Step N")).clicked() {
+            actions.step = Some(state.step_count);
+        }
+        ui.add(egui::DragValue::new(&mut state.step_count).range(1..=1_000_000).speed(10.0));
+        if ui.checkbox(&mut state.record_history, "Record").changed() {
+            actions.set_history = true;
+        }
+    });
+    if !is_paused {
+        ui.label(RichText::new("Pause to step; the table refreshes every second while running.")
+            .color(TEXT_MUTED).size(10.0).italics());
+    }
+
+    let history = &engine.history;
+    if history.is_empty() {
+        return;
+    }
+    let mono = |text: String, color: Color32| RichText::new(text).color(color).family(egui::FontFamily::Monospace).size(11.0);
+    ui.add_space(4.0);
+    ui.label(mono(format!("{:>8}  {:<18}  {:<24}  state", "index", "output", "point"), TEXT_MUTED));
+    let row_height = ui.text_style_height(&egui::TextStyle::Body);
+    ScrollArea::both().id_salt("debugger_history").max_height(220.0).show_rows(ui, row_height, history.len(), |ui, rows| {
+        // Newest first.
+        for entry in rows.map(|row| &history[history.len() - 1 - row]) {
+            let point = match entry.point {
+                Some([x, y, z]) => format!("({:.1}, {:.1}, {:.1})", x, y, z),
+                None => "-".to_string(),
+            };
+            let words = if entry.state().is_empty() {
+                "n/a".to_string()
+            } else {
+                entry.state().iter().map(|w| format!("{:#x}", w)).collect::<Vec<_>>().join(", ")
+            };
+            let text = format!("{:>8}  {:<#18x}  {:<24}  [{}]", entry.index, entry.output, point, words);
+            let selected = state.selected_call == Some(entry.index);
+            if ui.selectable_label(selected, mono(text, TEXT_PRIMARY)).clicked() {
+                state.selected_call = if selected { None } else { Some(entry.index) };
+            }
+        }
+    });
+}
+
+fn verify_controls(ui: &mut Ui, state: &mut UiState, status: &VerifyStatus, actions: &mut UiActions) {
+    section_header(ui, "VERIFY");
+    ui.horizontal(|ui| {
+        ui.label("Against:");
+        let native = &NATIVE_GENERATORS[state.selected_native];
+        if native.from_state.is_none() && state.verify_against == VerifyAgainst::Native {
+            state.verify_against = VerifyAgainst::Aelys;
+        }
+        ui.add_enabled_ui(native.from_state.is_some(), |ui| {
+            ui.selectable_value(&mut state.verify_against, VerifyAgainst::Native, native.name)
+                .on_disabled_hover_text("State too large for an Aelys program; compare it in the B view instead.");
+        });
+        ui.selectable_value(&mut state.verify_against, VerifyAgainst::Aelys, "Aelys code");
+    });
+    if state.verify_against == VerifyAgainst::Aelys {
+        ui.add_space(4.0);
+        code_editor(ui, "verify_code", &mut state.verify_code, &None);
+    }
+    ui.horizontal(|ui| {
+        ui.label("Outputs:");
+        ui.add(egui::DragValue::new(&mut state.verify_count).range(1..=1_000_000_000).speed(1000.0));
+        if ui.button("Verify").clicked() {
+            actions.start_verify = true;
+        }
+    });
+
+    let mono = |text: String, color: Color32| RichText::new(text).color(color).family(egui::FontFamily::Monospace).size(11.0);
+    match status {
+        VerifyStatus::Idle => {}
+        VerifyStatus::Running { checked } => {
+            ui.label(mono(format!("checking... {}", fmt_num(*checked as usize)), TEXT_MUTED));
+        }
+        VerifyStatus::Match { checked } => {
+            ui.label(mono(format!("{} outputs match", fmt_num(*checked as usize)), ACCENT_GREEN));
+        }
+        VerifyStatus::Diverged(d) => {
+            ui.label(mono(format!("Diverged at index {}", d.index), ACCENT_RED));
+            let fmt_state = |state: &[i64]| {
+                state.iter().map(|w| format!("{:#x}", w)).collect::<Vec<_>>().join(", ")
+            };
+            ui.label(mono(format!("user  out {:#x}  state [{}]", d.user_output, fmt_state(&d.user_state)), TEXT_PRIMARY));
+            ui.label(mono(format!("ref   out {:#x}  state [{}]", d.reference_output, fmt_state(&d.reference_state)), TEXT_PRIMARY));
+        }
+        VerifyStatus::Error(e) => {
+            ui.label(mono(e.clone(), ACCENT_RED));
+        }
+    }
+}
+
+fn sweep_controls(ui: &mut Ui, state: &mut UiState, status: &SweepStatus, actions: &mut UiActions) {
+    section_header(ui, "SEED SWEEP");
+    ui.horizontal(|ui| {
+        ui.label("Seeds:");
+        ui.add(TextEdit::singleline(&mut state.sweep_seeds).hint_text("0..1000, 0xdead").desired_width(180.0));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Outputs:");
+        ui.add(egui::DragValue::new(&mut state.sweep_length).range(16..=1_000_000).speed(64.0));
+        if ui.button("Sweep").clicked() {
+            actions.start_sweep = true;
+        }
+    });
+
+    let mono = |text: String, color: Color32| RichText::new(text).color(color).family(egui::FontFamily::Monospace).size(11.0);
+    let progress = match status {
+        SweepStatus::Idle => return,
+        SweepStatus::Error(e) => {
+            ui.label(mono(e.clone(), ACCENT_RED));
+            return;
+        }
+        SweepStatus::Running(p) => {
+            ui.label(mono(format!("checking... {}/{}", fmt_num(p.checked as usize), fmt_num(p.total as usize)), TEXT_MUTED));
+            p
+        }
+        SweepStatus::Done(p) => {
+            let color = if p.bad_count == 0 { ACCENT_GREEN } else { ACCENT_ORANGE };
+            let stuck = p.bad_count - p.biased_count;
+            ui.label(mono(format!(
+                "{} stuck or cycling, {} biased of {} seeds",
+                fmt_num(stuck as usize), fmt_num(p.biased_count as usize), fmt_num(p.total as usize)
+            ), color));
+            p
+        }
+    };
+
+    if progress.bad.is_empty() {
+        return;
+    }
+    ScrollArea::vertical().id_salt("sweep_results").max_height(140.0).show(ui, |ui| {
+        for bad in &progress.bad {
+            let problem = match bad.problem {
+                SeedProblem::Stuck { after } => format!("stuck after {}", after),
+                SeedProblem::ShortCycle { tail, cycle } => format!("cycle {} after {}", cycle, tail),
+                SeedProblem::Biased { p_value } => format!("chi-square p = {:.1e}", p_value),
+            };
+            let label = mono(format!("{:>20}  {}", bad.seed, problem), TEXT_PRIMARY);
+            if ui.selectable_label(state.seed == bad.seed, label).on_hover_text("Load this seed").clicked() {
+                state.seed = bad.seed;
+                actions.set_seed = Some(bad.seed);
+                actions.clear_points = true;
+            }
+        }
+    });
+    if progress.bad_count > progress.bad.len() as u64 {
+        ui.label(mono(format!("first {} shown", progress.bad.len()), TEXT_MUTED));
+    }
+}
+
+fn avalanche_controls(ui: &mut Ui, state: &mut UiState, engines: &[&EngineReadout], actions: &mut UiActions) {
+    section_header(ui, "AVALANCHE");
+    ui.horizontal(|ui| {
+        ui.label("States:");
+        ui.add(egui::DragValue::new(&mut state.avalanche_trials).range(64..=1_000_000).speed(64.0));
+        if ui.button("Analyze").clicked() {
+            actions.run_avalanche = true;
+            state.view_mode = ViewMode::Bits;
+            state.bit_plot = BitPlot::Avalanche;
+        }
+    });
+    ui.label(RichText::new("Flips each bit of state word 0 and counts which output bits change.")
+        .color(TEXT_MUTED).size(10.0).italics());
+
+    let mono = |text: String, color: Color32| RichText::new(text).color(color).family(egui::FontFamily::Monospace).size(11.0);
+    for (engine, label) in engines.iter().zip(ENGINE_LABELS) {
+        let prefix = if engines.len() > 1 { format!("{}: ", label) } else { String::new() };
+        let matrix = match &engine.avalanche {
+            AvalancheStatus::Idle => continue,
+            AvalancheStatus::Error(e) => {
+                ui.label(mono(format!("{}{}", prefix, e), ACCENT_RED));
+                continue;
+            }
+            AvalancheStatus::Running(m) | AvalancheStatus::Done(m) => m,
+        };
+        let (input, output, worst) = matrix.worst_bias();
+        // One sigma of a flip rate is 0.5 / sqrt(states); across up to 4096 cells the
+        // worst one lands around 4 sigma by chance alone.
+        let sigma = 0.5 / (matrix.trials.max(1) as f64).sqrt();
+        let color = if worst > 6.0 * sigma { ACCENT_RED } else if worst > 4.5 * sigma { ACCENT_ORANGE } else { ACCENT_GREEN };
+        let progress = match &engine.avalanche {
+            AvalancheStatus::Running(_) => "running, ",
+            _ => "",
+        };
+        ui.label(mono(format!("{}{}{} states", prefix, progress, fmt_num(matrix.trials as usize)), TEXT_MUTED));
+        ui.label(mono(format!("worst bias {:.4} (in {} -> out {})", worst, input, output), color));
+        ui.label(mono(format!("mean bias  {:.4}", matrix.mean_bias()), TEXT_PRIMARY));
+    }
+}
+
+fn spectral_controls(ui: &mut Ui, state: &mut UiState, actions: &mut UiActions) {
+    section_header(ui, "SPECTRAL TEST");
+    egui::Grid::new("lcg_params").num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+        for (label, text) in [
+            ("a", &mut state.spectral_a),
+            ("c", &mut state.spectral_c),
+            ("m", &mut state.spectral_m),
+        ] {
+            ui.label(label);
+            ui.add(TextEdit::singleline(text).desired_width(ui.available_width()));
+            ui.end_row();
+        }
+    });
+    ui.horizontal(|ui| {
+        if ui.button("Detect from code").clicked() {
+            actions.detect_lcg = true;
+        }
+        if ui.button("Run").clicked() {
+            actions.run_spectral = true;
+        }
+        ui.checkbox(&mut state.show_planes, "Planes in 3D");
+    });
+
+    if let Some(e) = &state.spectral_error {
+        ui.label(RichText::new(e).color(ACCENT_RED).size(11.0));
+    }
+    let Some(report) = &state.spectral_report else {
+        return;
+    };
+    let lcg = report.lcg;
+    ui.label(RichText::new(format!("a = {}  c = {}  m = {}", format_param(lcg.a), format_param(lcg.c), format_param(lcg.m)))
+        .color(TEXT_MUTED).family(egui::FontFamily::Monospace).size(11.0));
+
+    egui::Frame::default()
+        .fill(BG_WIDGET)
+        .stroke(egui::Stroke::new(1.0, BORDER_SUBTLE))
+        .rounding(6.0)
+        .inner_margin(12.0)
+        .show(ui, |ui| {
+            ui.style_mut().override_font_id = Some(egui::FontId::new(11.0, egui::FontFamily::Monospace));
+            egui::Grid::new("spectral").num_columns(5).spacing([14.0, 4.0]).show(ui, |ui| {
+                for header in ["t", "nu", "spacing", "planes", "S_t"] {
+                    ui.label(RichText::new(header).color(TEXT_MUTED).strong());
+                }
+                ui.end_row();
+                for d in &report.dimensions {
+                    let color = if d.merit >= 0.7 { ACCENT_GREEN } else if d.merit >= 0.3 { ACCENT_ORANGE } else { ACCENT_RED };
+                    ui.label(d.t.to_string()).on_hover_text(format!("shortest dual vector {:?}", d.shortest));
+                    ui.label(format!("{:.1}", d.nu));
+                    ui.label(format!("{:.2e}", d.spacing));
+                    ui.label(fmt_num(d.planes.min(usize::MAX as u128) as usize));
+                    ui.label(RichText::new(format!("{:.3}", d.merit)).color(color));
+                    ui.end_row();
+                }
+            });
+        });
+    if state.show_planes && (state.embedding == Embedding::Differences || state.mapping.mode != MappingMode::Scale) {
+        ui.label(RichText::new("Planes are drawn for the Triples and Lagged embeddings with Scale mapping.")
+            .color(TEXT_MUTED).size(10.0).italics());
+    }
+}
+
+fn stream_controls(ui: &mut Ui, state: &mut UiState, actions: &mut UiActions) {
+    section_header(ui, "EXTERNAL STREAM");
+    ui.horizontal(|ui| {
+        ui.label("File:");
+        ui.add(TextEdit::singleline(&mut state.stream_path)
+            .hint_text("stdin")
+            .desired_width(ui.available_width()));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Format:");
+        for (format, label) in [
+            (StreamFormat::U8, "u8"),
+            (StreamFormat::U16, "u16"),
+            (StreamFormat::U32, "u32"),
+            (StreamFormat::U64, "u64"),
+            (StreamFormat::Text, "text"),
+        ] {
+            if ui.selectable_label(state.stream_format == format, label).clicked() {
+                state.stream_format = format;
+            }
+        }
+    });
+    if !matches!(state.stream_format, StreamFormat::U8 | StreamFormat::Text) {
+        ui.horizontal(|ui| {
+            ui.label("Endian:");
+            if ui.selectable_label(state.stream_endian == Endian::Little, "Little").clicked() {
+                state.stream_endian = Endian::Little;
+            }
+            if ui.selectable_label(state.stream_endian == Endian::Big, "Big").clicked() {
+                state.stream_endian = Endian::Big;
+            }
+        });
+    }
+    if ui.button("Open Stream").clicked() {
+        if let Some(bits) = state.stream_format.bits() {
+            state.mapping.output_bits = bits;
+            actions.set_mapping = true;
+        }
+        actions.open_stream = true;
+        actions.clear_points = true;
+        // Lets "Compile & Run" switch back to the Aelys generator.
+        state.code_needs_compile = true;
+    }
+}
+
+fn compare_controls(ui: &mut Ui, state: &mut UiState, secondary: Option<&EngineReadout>, actions: &mut UiActions) {
+    section_header(ui, "COMPARE");
+    if ui.checkbox(&mut state.compare_enabled, "Second generator (B)").changed() {
+        actions.set_compare = true;
+        actions.clear_points = true;
+    }
+    if !state.compare_enabled {
+        return;
+    }
+
+    ui.add_space(4.0);
+    egui::ComboBox::from_id_salt("rng_examples_b")
+        .selected_text(RNG_EXAMPLES[state.selected_example_b].name)
+        .width(ui.available_width())
+        .show_ui(ui, |ui| {
+            for (i, ex) in RNG_EXAMPLES.iter().enumerate() {
+                if ui.selectable_label(state.selected_example_b == i, ex.name).clicked() {
+                    state.selected_example_b = i;
+                    state.code_b = ex.code.to_string();
+                    state.code_b_needs_compile = true;
+                    state.output_bits_b = ex.bits;
+                    actions.set_mapping = true;
+                }
+            }
+        });
+    ui.add_space(4.0);
+
+    let no_error = None;
+    code_editor(ui, "rng_code_b", &mut state.code_b, secondary.map_or(&no_error, |s| &s.last_error));
+    ui.add_space(8.0);
+    let btn_text = if state.code_b_needs_compile { "Compile & Run B" } else { "Running..." };
+    let btn_color = if state.code_b_needs_compile { ACCENT_GREEN } else { BG_WIDGET };
+    let text_color = if state.code_b_needs_compile { BG_PURE_BLACK } else { ACCENT_GREEN };
+    if ui.add(egui::Button::new(RichText::new(btn_text).color(text_color))
+        .fill(btn_color).min_size(egui::vec2(ui.available_width(), 32.0))).clicked()
+        && state.code_b_needs_compile {
+        actions.compile_code_b = true;
+        actions.clear_points = true;
+        state.code_b_needs_compile = false;
+    }
+    ui.add_space(8.0);
+
+    ui.horizontal(|ui| {
+        ui.label("Output bits:");
+        if ui.add(egui::Slider::new(&mut state.output_bits_b, MIN_OUTPUT_BITS..=64)).changed() {
+            actions.set_mapping = true;
+            actions.clear_points = true;
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Seed:");
+        ui.add(egui::DragValue::new(&mut state.seed_b).speed(1.0));
+        if ui.button("Apply").clicked() {
+            actions.set_seed_b = Some(state.seed_b);
+            actions.clear_points = true;
+        }
+        if ui.button("Random").clicked() {
+            state.seed_b = rand_seed();
+            actions.set_seed_b = Some(state.seed_b);
+            actions.clear_points = true;
+        }
+    });
+}
+
 fn bounds_grid(ui: &mut Ui, mins: &mut [f32; 3], maxs: &mut [f32; 3]) {
     egui::Grid::new("bounds").num_columns(3).spacing([8.0, 4.0]).show(ui, |ui| {
         ui.label("");
@@ -454,7 +1281,17 @@ fn perf_controls(ui: &mut Ui, state: &mut UiState) {
     });
 }
 
-fn stats_panel(ui: &mut Ui, stats: &PerformanceStats, paused: bool) {
+fn stream_count_controls(ui: &mut Ui, state: &mut UiState, actions: &mut UiActions) {
+    ui.horizontal(|ui| {
+        ui.label("Streams:");
+        if ui.add(egui::DragValue::new(&mut state.streams).range(1..=MAX_STREAMS)).changed() {
+            actions.set_streams = true;
+            actions.clear_points = true;
+        }
+    }).response.on_hover_text("Generator threads per engine. Stream k starts from stream_seed(seed, k) when defined, seed + k otherwise");
+}
+
+fn stats_panel(ui: &mut Ui, engines: &[&EngineReadout], paused: bool) {
     section_header(ui, "STATISTICS");
     egui::Frame::default()
         .fill(BG_WIDGET)
@@ -464,56 +1301,173 @@ fn stats_panel(ui: &mut Ui, stats: &PerformanceStats, paused: bool) {
         .show(ui, |ui| {
             ui.style_mut().override_font_id = Some(egui::FontId::new(11.0, egui::FontFamily::Monospace));
 
-            let fps = *stats.fps.lock();
+            let fps = *engines[0].stats.fps.lock();
             let fps_color = if fps >= 60.0 { ACCENT_GREEN } else if fps >= 30.0 { ACCENT_ORANGE } else { ACCENT_RED };
 
-            egui::Grid::new("stats").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+            egui::Grid::new("stats").num_columns(1 + engines.len()).spacing([20.0, 4.0]).show(ui, |ui| {
+                if engines.len() > 1 {
+                    ui.label("");
+                    for label in ENGINE_LABELS.iter().take(engines.len()) {
+                        ui.label(RichText::new(*label).color(TEXT_MUTED).strong());
+                    }
+                    ui.end_row();
+                }
+
                 ui.label(RichText::new("FPS").color(TEXT_MUTED));
                 ui.label(RichText::new(format!("{:.0}", fps)).color(fps_color));
+                // One frame rate for the whole window; B's column stays empty.
+                for _ in 1..engines.len() {
+                    ui.label("");
+                }
                 ui.end_row();
 
-                ui.label(RichText::new("RNG/s").color(TEXT_MUTED));
-                ui.label(RichText::new(fmt_num(stats.rng_calls_per_sec.load(Ordering::Relaxed) as usize)).color(ACCENT_BLUE));
-                ui.end_row();
+                stats_row(ui, "RNG/s", engines, |e| {
+                    RichText::new(fmt_num(e.stats.rng_calls_per_sec.load(Ordering::Relaxed) as usize)).color(ACCENT_BLUE)
+                });
+                let streams = engines.iter().map(|e| e.stats.streams.lock().len()).max().unwrap_or(1);
+                if streams > 1 {
+                    for k in 0..streams {
+                        stats_row(ui, &format!("  #{}", k), engines, |e| {
+                            let text = match e.stats.streams.lock().get(k) {
+                                Some(rate) => fmt_num(rate.calls_per_sec as usize),
+                                None => "-".to_string(),
+                            };
+                            RichText::new(text).color(TEXT_MUTED)
+                        });
+                    }
+                }
+                stats_row(ui, "Points/s", engines, |e| {
+                    RichText::new(fmt_num(e.stats.points_generated_per_sec.load(Ordering::Relaxed) as usize)).color(ACCENT_PURPLE)
+                });
+                stats_row(ui, "Rendered", engines, |e| {
+                    RichText::new(fmt_num(e.stats.points_rendered.load(Ordering::Relaxed))).color(TEXT_PRIMARY)
+                });
+                stats_row(ui, "Batch", engines, |e| {
+                    RichText::new(fmt_num(e.stats.current_batch_size.load(Ordering::Relaxed))).color(TEXT_PRIMARY)
+                });
+                stats_row(ui, "Batch ms", engines, |e| {
+                    RichText::new(format!("{:.1}", *e.stats.avg_batch_time_ms.lock())).color(TEXT_PRIMARY)
+                });
+                stats_row(ui, "Period", engines, |e| {
+                    let (text, color) = match &e.period {
+                        PeriodStatus::Idle => ("-".to_string(), TEXT_MUTED),
+                        PeriodStatus::Searching { steps } => (format!("searching {}", fmt_num(*steps as usize)), TEXT_MUTED),
+                        PeriodStatus::Found { tail, cycle } => {
+                            (format!("{} (tail {})", fmt_num(*cycle as usize), fmt_num(*tail as usize)), ACCENT_ORANGE)
+                        }
+                        PeriodStatus::LowerBound { min } => (format!(">= {}", fmt_num(*min as usize)), ACCENT_GREEN),
+                        PeriodStatus::Error(e) => (format!("error: {}", e), ACCENT_RED),
+                    };
+                    RichText::new(text).color(color)
+                });
+            });
 
-                ui.label(RichText::new("Points/s").color(TEXT_MUTED));
-                ui.label(RichText::new(fmt_num(stats.points_generated_per_sec.load(Ordering::Relaxed) as usize)).color(ACCENT_PURPLE));
-                ui.end_row();
+            ui.add_space(8.0);
 
-                ui.label(RichText::new("Rendered").color(TEXT_MUTED));
-                ui.label(RichText::new(fmt_num(stats.points_rendered.load(Ordering::Relaxed))).color(TEXT_PRIMARY));
-                ui.end_row();
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Status:").color(TEXT_MUTED));
+                if paused {
+                    ui.label(RichText::new("PAUSED").color(ACCENT_ORANGE).strong());
+                    return;
+                }
+                for engine in engines {
+                    let (text, color) = match *engine.stats.bottleneck.lock() {
+                        Bottleneck::CpuRng => ("CPU Limited", ACCENT_ORANGE),
+                        Bottleneck::GpuUpload => ("GPU Upload", ACCENT_RED),
+                        Bottleneck::GpuRender => ("GPU Render", ACCENT_RED),
+                        Bottleneck::Balanced => ("Balanced", ACCENT_GREEN),
+                    };
+                    ui.label(RichText::new(text).color(color));
+                }
+            });
+        });
+}
 
-                ui.label(RichText::new("Batch").color(TEXT_MUTED));
-                ui.label(RichText::new(fmt_num(stats.current_batch_size.load(Ordering::Relaxed))).color(TEXT_PRIMARY));
-                ui.end_row();
+fn stats_row(ui: &mut Ui, label: &str, engines: &[&EngineReadout], value: impl Fn(&EngineReadout) -> RichText) {
+    ui.label(RichText::new(label).color(TEXT_MUTED));
+    for engine in engines {
+        ui.label(value(engine));
+    }
+    ui.end_row();
+}
 
-                ui.label(RichText::new("Batch ms").color(TEXT_MUTED));
-                ui.label(RichText::new(format!("{:.1}", *stats.avg_batch_time_ms.lock())).color(TEXT_PRIMARY));
-                ui.end_row();
-            });
+fn tests_panel(ui: &mut Ui, engines: &[&EngineReadout]) {
+    section_header(ui, "RANDOMNESS TESTS");
+    egui::Frame::default()
+        .fill(BG_WIDGET)
+        .stroke(egui::Stroke::new(1.0, BORDER_SUBTLE))
+        .rounding(6.0)
+        .inner_margin(12.0)
+        .show(ui, |ui| {
+            ui.style_mut().override_font_id = Some(egui::FontId::new(11.0, egui::FontFamily::Monospace));
 
-            ui.add_space(8.0);
+            let samples: Vec<String> = engines
+                .iter()
+                .map(|e| fmt_num(e.test_results.iter().map(|r| r.samples).max().unwrap_or(0) as usize))
+                .collect();
+            ui.label(RichText::new(format!("Samples: {}", samples.join(" | "))).color(TEXT_MUTED));
+            ui.add_space(4.0);
 
-            let status = if paused {
-                RichText::new("PAUSED").color(ACCENT_ORANGE).strong()
-            } else {
-                let (text, color) = match *stats.bottleneck.lock() {
-                    Bottleneck::CpuRng => ("CPU Limited", ACCENT_ORANGE),
-                    Bottleneck::GpuUpload => ("GPU Upload", ACCENT_RED),
-                    Bottleneck::GpuRender => ("GPU Render", ACCENT_RED),
-                    Bottleneck::Balanced => ("Balanced", ACCENT_GREEN),
-                };
-                RichText::new(text).color(color)
-            };
+            let compare = engines.len() > 1;
+            egui::Grid::new("tests").num_columns(1 + 2 * engines.len()).spacing([16.0, 4.0]).show(ui, |ui| {
+                if compare {
+                    ui.label("");
+                    for label in ENGINE_LABELS.iter().take(engines.len()) {
+                        ui.label(RichText::new(*label).color(TEXT_MUTED).strong());
+                        ui.label("");
+                    }
+                    ui.end_row();
+                }
 
-            ui.horizontal(|ui| {
-                ui.label(RichText::new("Status:").color(TEXT_MUTED));
-                ui.label(status);
+                for (i, result) in engines[0].test_results.iter().enumerate() {
+                    ui.label(RichText::new(result.name).color(TEXT_MUTED));
+                    for engine in engines {
+                        let Some(result) = engine.test_results.get(i) else {
+                            ui.label("");
+                            ui.label("");
+                            continue;
+                        };
+                        let p_text = match (result.p_value, compare) {
+                            (Some(p), false) => format!("p={:.4}", p),
+                            (Some(p), true) => format!("{:.4}", p),
+                            (None, false) => "p=...".to_string(),
+                            (None, true) => "...".to_string(),
+                        };
+                        ui.label(RichText::new(p_text).color(TEXT_PRIMARY))
+                            .on_hover_text(format!("statistic = {:.4}", result.statistic));
+                        let (text, color) = match result.verdict() {
+                            Verdict::Pending => ("WAIT", TEXT_MUTED),
+                            Verdict::Pass => ("PASS", ACCENT_GREEN),
+                            Verdict::Fail => ("FAIL", ACCENT_RED),
+                        };
+                        ui.label(RichText::new(text).color(color).strong());
+                    }
+                    ui.end_row();
+                }
             });
         });
 }
 
+/// Labels the two halves of the comparison view; `rect` is the area left of the side panel.
+pub fn draw_compare_overlay(ctx: &Context, rect: egui::Rect, names: [&str; 2]) {
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("compare_overlay")));
+    let mid = rect.center().x;
+    painter.line_segment(
+        [egui::pos2(mid, rect.top()), egui::pos2(mid, rect.bottom())],
+        egui::Stroke::new(1.0, BORDER_SUBTLE),
+    );
+    for (i, name) in names.iter().enumerate() {
+        let x = if i == 0 { rect.left() } else { mid };
+        painter.text(
+            egui::pos2(x + 12.0, rect.top() + 12.0),
+            egui::Align2::LEFT_TOP,
+            format!("{}: {}", ENGINE_LABELS[i], name),
+            egui::FontId::new(12.0, egui::FontFamily::Monospace),
+            TEXT_MUTED,
+        );
+    }
+}
+
 pub fn draw_help_overlay(ctx: &Context, pos: [f32; 3], speed: f32) {
     egui::Area::new(egui::Id::new("help_overlay"))
         .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(12.0, -12.0))
@@ -524,12 +1478,42 @@ pub fn draw_help_overlay(ctx: &Context, pos: [f32; 3], speed: f32) {
                 .inner_margin(10.0)
                 .show(ui, |ui| {
                     ui.style_mut().override_font_id = Some(egui::FontId::new(11.0, egui::FontFamily::Monospace));
-                    ui.label(RichText::new("WASD - Move | RMB+Drag - Look | Scroll - Speed").color(TEXT_MUTED));
+                    ui.label(RichText::new("WASD - Move | RMB+Drag - Look | LMB - Pick | Scroll - Speed").color(TEXT_MUTED));
                     ui.label(RichText::new(format!("Pos: ({:.0}, {:.0}, {:.0}) | Speed: {:.0}", pos[0], pos[1], pos[2], speed)).color(TEXT_MUTED));
                 });
         });
 }
 
+/// `engine` is the A/B slot while comparing.
+pub fn draw_pick_tooltip(ctx: &Context, pos: egui::Pos2, engine: Option<usize>, origin: &PointOrigin, seed: Option<i64>) {
+    egui::Area::new(egui::Id::new("pick_tooltip"))
+        .fixed_pos(pos + egui::vec2(12.0, 12.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::default()
+                .fill(Color32::from_black_alpha(200))
+                .stroke(egui::Stroke::new(1.0, BORDER_SUBTLE))
+                .rounding(6.0)
+                .inner_margin(8.0)
+                .show(ui, |ui| {
+                    ui.style_mut().override_font_id = Some(egui::FontId::new(11.0, egui::FontFamily::Monospace));
+                    let title = match engine {
+                        Some(slot) => format!("{} call #{}", ENGINE_LABELS[slot], origin.index),
+                        None => format!("call #{}", origin.index),
+                    };
+                    ui.label(RichText::new(title).color(ACCENT_ORANGE).strong());
+                    for (axis, raw) in ["x", "y", "z"].iter().zip(origin.raw) {
+                        ui.label(RichText::new(format!("{}  {:#x}  ({})", axis, raw, raw)).color(TEXT_PRIMARY));
+                    }
+                    let seed = match seed {
+                        Some(seed) => seed.to_string(),
+                        None => "n/a".to_string(),
+                    };
+                    ui.label(RichText::new(format!("seed {}  stream {}", seed, origin.stream)).color(TEXT_MUTED));
+                });
+        });
+}
+
 fn fmt_num(n: usize) -> String {
     if n >= 1_000_000 {
         format!("{:.2}M", n as f64 / 1_000_000.0)
@@ -1,5 +1,11 @@
 use crate::math::examples::MathFunctionKind;
-use crate::renderer::CameraMode;
+use crate::math::ode::OdeMethod;
+use crate::renderer::{CameraMode, Colormap, ToneMap};
+use crate::rng::spectral::SpectralReport;
+use crate::rng::{BitPlot, Embedding, Endian, RNG_EXAMPLES, StreamFormat, StreamSpec, ValueMapping};
+
+/// RANDU, the usual reference for what a bad generator looks like.
+const COMPARE_EXAMPLE: usize = 6;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum AppMode {
@@ -11,6 +17,25 @@ pub enum AppMode {
 pub enum ViewMode {
     Mode3D,
     Mode2D,
+    Bits,
+}
+
+/// What the 3D points are coloured by.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PointColor {
+    Position,
+    /// Order of arrival among the points on screen.
+    Age,
+    /// Call index of the output that completed the point.
+    Index,
+    Batch,
+    Stream,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VerifyAgainst {
+    Native,
+    Aelys,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -26,7 +51,20 @@ pub struct UiState {
     pub selected_example: usize,
 
     pub view_mode: ViewMode,
+    pub embedding: Embedding,
+    pub embedding_lag: u32,
+    pub bit_plot: BitPlot,
+    /// 0 correlates bits within an output, 1 against the previous output.
+    pub bit_lag: usize,
+    pub mapping: ValueMapping,
     pub camera_mode: CameraMode,
+    pub point_color: PointColor,
+    pub colormap: Colormap,
+    /// Colours repeat every `color_period` indices or batches; 0 spreads the map over the whole range.
+    pub color_period: u64,
+    /// Draw hit counts per pixel instead of opaque points.
+    pub density: bool,
+    pub tone_map: ToneMap,
     pub vsync_enabled: bool,
 
     pub bounds_min: [f32; 3],
@@ -35,10 +73,50 @@ pub struct UiState {
 
     pub seed: i64,
 
+    pub step_count: u64,
+    pub record_history: bool,
+    /// Call index selected in the debugger table.
+    pub selected_call: Option<u64>,
+
+    pub selected_native: usize,
+    /// What A and B are currently running, for the comparison overlay.
+    pub source_labels: [&'static str; 2],
+
+    pub verify_against: VerifyAgainst,
+    pub verify_code: String,
+    pub verify_count: u64,
+
+    /// Seeds and ranges for the sweep (see `sweep::parse_seeds`).
+    pub sweep_seeds: String,
+    pub sweep_length: u64,
+
+    pub avalanche_trials: u64,
+
+    /// LCG parameters for the spectral test, as typed (see `spectral::parse_param`).
+    pub spectral_a: String,
+    pub spectral_c: String,
+    pub spectral_m: String,
+    pub spectral_report: Option<SpectralReport>,
+    pub spectral_error: Option<String>,
+    pub show_planes: bool,
+
+    pub stream_path: String,
+    pub stream_format: StreamFormat,
+    pub stream_endian: Endian,
+
+    pub compare_enabled: bool,
+    pub code_b: String,
+    pub selected_example_b: usize,
+    pub code_b_needs_compile: bool,
+    pub output_bits_b: u32,
+    pub seed_b: i64,
+
     pub show_stats: bool,
 
     pub fps_cap_enabled: bool,
     pub fps_cap: u32,
+    /// Parallel generator streams per engine.
+    pub streams: usize,
 
     pub code_needs_compile: bool,
 
@@ -50,6 +128,7 @@ pub struct UiState {
     pub math_view_mode: MathViewMode,
     pub math_x_range: (f32, f32),
     pub math_y_range: (f32, f32),
+    pub math_z_range: (f32, f32),
     pub math_t_range: (f32, f32),
     pub math_resolution: u32,
     pub math_samples: u32,
@@ -57,6 +136,32 @@ pub struct UiState {
     pub math_v_range: (f32, f32),
     pub math_u_samples: u32,
     pub math_v_samples: u32,
+    pub math_implicit_resolution: u32,
+    pub math_iso_level: f32,
+    pub math_initial: [f32; 3],
+    pub math_ode_step: f32,
+    pub math_ode_method: OdeMethod,
+    /// Draw the trajectory as points instead of a line, for runs too long for the curve buffer.
+    pub math_ode_points: bool,
+    /// Arrows per axis.
+    pub math_field_resolution: u32,
+    pub math_streamlines: bool,
+    /// Seeds per side of the square on the seed plane.
+    pub math_stream_seeds: u32,
+    /// Axis normal to the seed plane, 0 to 2 for x to z.
+    pub math_stream_axis: usize,
+    /// Where the seed plane cuts that axis's range, from 0 to 1.
+    pub math_stream_offset: f32,
+    /// Point of the plane at the centre of the fractal view.
+    pub fractal_center: [f64; 2],
+    /// Distance from the centre to the top edge of the fractal view.
+    pub fractal_scale: f64,
+    pub fractal_max_iterations: u32,
+    /// Draw the Julia set of `fractal_c` instead of iterating over c.
+    pub fractal_julia: bool,
+    pub fractal_c: [f64; 2],
+    /// Escape counts per trip through the colormap.
+    pub fractal_cycle: f32,
     pub math_needs_compile: bool,
     pub show_grid: bool,
 }
@@ -70,7 +175,20 @@ impl Default for UiState {
             selected_example: 0,
 
             view_mode: ViewMode::Mode3D,
+            embedding: Embedding::Triples,
+            embedding_lag: 1,
+            bit_plot: BitPlot::Raster,
+            bit_lag: 0,
+            mapping: ValueMapping {
+                output_bits: crate::rng::RNG_EXAMPLES[0].bits,
+                ..ValueMapping::default()
+            },
             camera_mode: CameraMode::Free,
+            point_color: PointColor::Position,
+            colormap: Colormap::Viridis,
+            color_period: 0,
+            density: false,
+            tone_map: ToneMap::Log,
             vsync_enabled: false,
 
             bounds_min: [-500.0, -500.0, -500.0],
@@ -79,10 +197,45 @@ impl Default for UiState {
 
             seed: 12345,
 
+            step_count: 100,
+            record_history: true,
+            selected_call: None,
+
+            selected_native: 0,
+            source_labels: [RNG_EXAMPLES[0].name, RNG_EXAMPLES[COMPARE_EXAMPLE].name],
+
+            verify_against: VerifyAgainst::Native,
+            verify_code: RNG_EXAMPLES[0].code.to_string(),
+            verify_count: 1_000_000,
+
+            sweep_seeds: "0..1000".to_string(),
+            sweep_length: 4096,
+
+            avalanche_trials: 4096,
+
+            spectral_a: "65539".to_string(),
+            spectral_c: "0".to_string(),
+            spectral_m: "2^31".to_string(),
+            spectral_report: None,
+            spectral_error: None,
+            show_planes: false,
+
+            stream_path: String::new(),
+            stream_format: StreamFormat::U32,
+            stream_endian: Endian::Little,
+
+            compare_enabled: false,
+            code_b: RNG_EXAMPLES[COMPARE_EXAMPLE].code.to_string(),
+            selected_example_b: COMPARE_EXAMPLE,
+            code_b_needs_compile: true,
+            output_bits_b: RNG_EXAMPLES[COMPARE_EXAMPLE].bits,
+            seed_b: 12345,
+
             show_stats: true,
 
             fps_cap_enabled: false,
             fps_cap: 144,
+            streams: 1,
 
             code_needs_compile: true,
 
@@ -94,6 +247,7 @@ impl Default for UiState {
             math_view_mode: MathViewMode::Mode3D,
             math_x_range: (-6.28, 6.28),
             math_y_range: (-6.28, 6.28),
+            math_z_range: (-6.28, 6.28),
             math_t_range: (0.0, 6.28),
             math_resolution: 100,
             math_samples: 1000,
@@ -101,8 +255,51 @@ impl Default for UiState {
             math_v_range: (0.0, 6.28),
             math_u_samples: 50,
             math_v_samples: 50,
+            math_implicit_resolution: 48,
+            math_iso_level: 0.0,
+            math_initial: [1.0, 1.0, 1.0],
+            math_ode_step: 0.01,
+            math_ode_method: OdeMethod::Rk4,
+            math_ode_points: false,
+            math_field_resolution: 10,
+            math_streamlines: false,
+            math_stream_seeds: 6,
+            math_stream_axis: 1,
+            math_stream_offset: 0.5,
+            fractal_center: [-0.5, 0.0],
+            fractal_scale: 1.25,
+            fractal_max_iterations: 256,
+            fractal_julia: false,
+            fractal_c: [-0.8, 0.156],
+            fractal_cycle: 32.0,
             math_needs_compile: true,
             show_grid: true,
         }
     }
 }
+
+impl UiState {
+    pub fn stream_spec(&self) -> StreamSpec {
+        let path = self.stream_path.trim();
+        StreamSpec {
+            path: (!path.is_empty()).then(|| path.to_string()),
+            format: self.stream_format,
+            endian: self.stream_endian,
+        }
+    }
+
+    /// Generator B shares the mapping mode with A but keeps its own output width.
+    pub fn mapping_b(&self) -> ValueMapping {
+        ValueMapping {
+            output_bits: self.output_bits_b,
+            ..self.mapping
+        }
+    }
+
+    /// Centres the fractal view on the X and Y ranges, fitting the Y range.
+    pub fn reset_fractal_view(&mut self) {
+        let (x, y) = (self.math_x_range, self.math_y_range);
+        self.fractal_center = [(x.0 + x.1) as f64 / 2.0, (y.0 + y.1) as f64 / 2.0];
+        self.fractal_scale = (y.1 - y.0).abs().max(1e-6) as f64 / 2.0;
+    }
+}